@@ -11,6 +11,8 @@ use ratzilla::ratatui::{
     Frame,
 };
 
+use ratzilla::intl::NumberFormat;
+
 use crate::app::App;
 
 pub fn draw(frame: &mut Frame, app: &mut App) {
@@ -56,7 +58,9 @@ fn draw_gauges(frame: &mut Frame, app: &mut App, area: Rect) {
     let block = Block::bordered().title("Graphs");
     frame.render_widget(block, area);
 
-    let label = format!("{:.2}%", app.progress * 100.0);
+    let label = NumberFormat::percent()
+        .fraction_digits(2, 2)
+        .format(app.progress);
     let gauge = Gauge::default()
         .block(Block::new().title("Gauge:"))
         .gauge_style(
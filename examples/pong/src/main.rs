@@ -108,7 +108,7 @@ fn main() -> std::io::Result<()> {
         f.render_widget(app_state.pong_canvas(), right);
 
         let url = "https://orhun.dev";
-        let link = Hyperlink::new(url);
+        let link = Hyperlink::new(url).target("_blank");
         let area = Rect::new(right.x, right.y + right.height - 1, url.len() as u16, 1);
         f.render_widget(link, area);
     });
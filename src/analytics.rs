@@ -0,0 +1,214 @@
+//! ## Analytics
+//!
+//! A hook for plugging in the analytics of a site. The handler set with
+//! [`set_handler`] receives an [`Interaction`] for the input that reaches the
+//! application and for the route changes, without having to wrap the event
+//! handlers of the application.
+//!
+//! ```rust no_run
+//! use ratzilla::analytics::{self, Interaction};
+//!
+//! analytics::set_handler(|interaction| {
+//!     if let Interaction::Route(route) = interaction {
+//!         // Send a page view to the analytics service.
+//!     }
+//! })
+//! .unwrap();
+//! ```
+//!
+//! The interactions are anonymized: the typed characters, the pasted and
+//! copied text, and the positions of the clicks are left out.
+
+use std::cell::{Cell, RefCell};
+
+use ratatui::layout::Position;
+use web_sys::{
+    wasm_bindgen::{prelude::Closure, JsCast},
+    window,
+};
+
+use crate::{
+    error::Error,
+    event::{self, Event, KeyClass, MouseButton, MouseEventKind},
+    metadata::{self, RegionKind},
+    permalink,
+};
+
+/// Type of the analytics handler.
+type InteractionHandler = Box<dyn FnMut(&Interaction)>;
+
+thread_local! {
+    /// Handler of the interactions.
+    static HANDLER: RefCell<Option<InteractionHandler>> = const { RefCell::new(None) };
+    /// Whether the event handler and the route listeners are added.
+    static LISTENERS_ADDED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// An anonymized interaction.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Interaction {
+    /// A key was pressed.
+    Key {
+        /// Class of the key, the key itself is left out.
+        class: KeyClass,
+        /// Whether the control or the alt key was pressed, i.e. the key is
+        /// likely a shortcut.
+        modified: bool,
+    },
+    /// A mouse button was clicked or the screen was tapped.
+    Click {
+        /// The button.
+        button: MouseButton,
+        /// What was clicked.
+        target: ClickTarget,
+    },
+    /// The content was scrolled.
+    Scroll,
+    /// Text was pasted.
+    Paste,
+    /// Text was copied to the clipboard.
+    Copy {
+        /// Whether the text was copied successfully.
+        success: bool,
+    },
+    /// The page gained (`true`) or lost (`false`) the focus.
+    Focus(bool),
+    /// The route changed, see [`track_route`].
+    Route(String),
+}
+
+/// Target of a [`Interaction::Click`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ClickTarget {
+    /// A [`Hyperlink`] with the given URL.
+    ///
+    /// [`Hyperlink`]: crate::widgets::Hyperlink
+    Hyperlink(String),
+    /// A [`CopyButton`].
+    ///
+    /// [`CopyButton`]: crate::widgets::CopyButton
+    CopyButton,
+    /// Any other part of the grid.
+    Grid,
+}
+
+/// Sets the handler of the interactions.
+///
+/// Only the input that the application listens to is reported, e.g. the key
+/// events once a key event handler is registered. The route changes of the
+/// browser (the back and forward buttons and the changes of the fragment)
+/// are reported as well.
+pub fn set_handler<F>(handler: F) -> Result<(), Error>
+where
+    F: FnMut(&Interaction) + 'static,
+{
+    HANDLER.with(|current| current.replace(Some(Box::new(handler))));
+    add_listeners()
+}
+
+/// Removes the handler of the interactions.
+pub fn clear_handler() {
+    HANDLER.with(|current| current.replace(None));
+}
+
+/// Reports a route change, e.g. when the application switches to another
+/// screen.
+///
+/// Use a name that identifies the screen rather than the data it shows, e.g.
+/// `"/settings"` or `"/items/:id"`.
+pub fn track_route<S: Into<String>>(route: S) {
+    report(&Interaction::Route(route.into()));
+}
+
+/// Passes the interaction to the handler, if any.
+fn report(interaction: &Interaction) {
+    HANDLER.with(|handler| {
+        if let Some(handler) = handler.borrow_mut().as_mut() {
+            handler(interaction);
+        }
+    });
+}
+
+/// Adds the event handler and the route listeners, unless they are already
+/// added.
+fn add_listeners() -> Result<(), Error> {
+    if LISTENERS_ADDED.get() {
+        return Ok(());
+    }
+    event::add_event_handler(|event| {
+        if let Some(interaction) = interaction(event) {
+            report(&interaction);
+        }
+    });
+    let window = window().ok_or(Error::UnableToRetrieveWindow)?;
+    let closure = Closure::<dyn FnMut()>::new(|| {
+        if let Some(route) = current_route() {
+            report(&Interaction::Route(route));
+        }
+    });
+    for name in ["popstate", "hashchange"] {
+        window.add_event_listener_with_callback(name, closure.as_ref().unchecked_ref())?;
+    }
+    closure.forget();
+    LISTENERS_ADDED.set(true);
+    Ok(())
+}
+
+/// Returns the route of the current URL.
+///
+/// This is the path along with the fragment, unless the fragment holds the
+/// state of a permalink.
+fn current_route() -> Option<String> {
+    let location = window()?.location();
+    let path = location.pathname().ok()?;
+    let hash = location.hash().unwrap_or_default();
+    if hash.is_empty() || hash.starts_with(permalink::FRAGMENT_PREFIX) {
+        Some(path)
+    } else {
+        Some(format!("{path}{hash}"))
+    }
+}
+
+/// Returns the interaction of an event, if any.
+fn interaction(event: &Event) -> Option<Interaction> {
+    match event {
+        Event::Key(key) => Some(Interaction::Key {
+            class: key.code.class(),
+            modified: key.ctrl || key.alt,
+        }),
+        Event::Mouse(mouse) => match mouse.kind {
+            MouseEventKind::Up(button) if !event::is_drag() => Some(Interaction::Click {
+                button,
+                target: click_target(mouse.position()),
+            }),
+            MouseEventKind::ScrollUp
+            | MouseEventKind::ScrollDown
+            | MouseEventKind::ScrollLeft
+            | MouseEventKind::ScrollRight => Some(Interaction::Scroll),
+            _ => None,
+        },
+        Event::Paste(_) => Some(Interaction::Paste),
+        Event::Copy(copy) => Some(Interaction::Copy {
+            success: copy.success,
+        }),
+        Event::FocusGained => Some(Interaction::Focus(true)),
+        Event::FocusLost => Some(Interaction::Focus(false)),
+        _ => None,
+    }
+}
+
+/// Returns what is at the given position of the last frame.
+fn click_target(position: Position) -> ClickTarget {
+    metadata::regions()
+        .into_iter()
+        .rev()
+        .filter(|region| region.area.contains(position))
+        .find_map(|region| match region.kind {
+            RegionKind::Hyperlink { url, .. } => Some(ClickTarget::Hyperlink(url)),
+            RegionKind::CopyButton(_) => Some(ClickTarget::CopyButton),
+            _ => None,
+        })
+        .unwrap_or(ClickTarget::Grid)
+}
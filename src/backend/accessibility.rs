@@ -0,0 +1,121 @@
+use web_sys::{Document, Element};
+
+use crate::error::Error;
+
+/// Style that hides an element visually while keeping it available to the
+/// assistive technologies.
+const VISUALLY_HIDDEN_STYLE: &str = "position: absolute; width: 1px; height: 1px; \
+    margin: -1px; padding: 0; overflow: hidden; clip: rect(0 0 0 0); \
+    clip-path: inset(50%); border: 0; white-space: pre;";
+
+/// Hidden plain-text mirror of the grid for screen readers.
+///
+/// The mirror is an `aria-live` region with one element per line of the grid.
+/// Only the lines whose text changed are written, so screen readers announce
+/// the changes instead of the whole screen. A separate status element is used
+/// for announcing messages.
+#[derive(Debug)]
+pub(crate) struct LiveMirror {
+    /// Element that holds the mirror and the status element.
+    root: Element,
+    /// Live region with the lines.
+    region: Element,
+    /// Elements of the lines along with their text.
+    lines: Vec<(Element, String)>,
+    /// Status element for the announcements.
+    status: Element,
+    /// Whether all the lines must be rewritten.
+    stale: bool,
+    /// Document.
+    document: Document,
+}
+
+impl LiveMirror {
+    /// Constructs a new [`LiveMirror`] for the grid with the given id.
+    pub(crate) fn new(document: &Document, grid_id: &str) -> Result<Self, Error> {
+        let root = document.create_element("div")?;
+        root.set_attribute("id", &format!("{grid_id}-a11y"))?;
+        root.set_attribute("style", VISUALLY_HIDDEN_STYLE)?;
+        let region = document.create_element("div")?;
+        region.set_attribute("role", "log")?;
+        region.set_attribute("aria-live", "polite")?;
+        region.set_attribute("aria-relevant", "additions text")?;
+        region.set_attribute("aria-label", "Screen content")?;
+        root.append_child(&region)?;
+        let status = document.create_element("div")?;
+        status.set_attribute("role", "status")?;
+        status.set_attribute("aria-live", "polite")?;
+        root.append_child(&status)?;
+        Ok(Self {
+            root,
+            region,
+            lines: Vec::new(),
+            status,
+            stale: true,
+            document: document.clone(),
+        })
+    }
+
+    /// Appends the mirror to the given parent, unless it is already there.
+    pub(crate) fn attach(&self, parent: &Element) -> Result<(), Error> {
+        if self.root.parent_element().as_ref() != Some(parent) {
+            parent.append_child(&self.root)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the mirror from the document.
+    pub(crate) fn detach(&self) {
+        self.root.remove();
+    }
+
+    /// Sets the number of lines of the mirror.
+    pub(crate) fn resize(&mut self, len: usize) -> Result<(), Error> {
+        for (element, _) in self.lines.drain(len.min(self.lines.len())..) {
+            element.remove();
+        }
+        while self.lines.len() < len {
+            let element = self.document.create_element("div")?;
+            self.region.append_child(&element)?;
+            self.lines.push((element, String::new()));
+            self.stale = true;
+        }
+        Ok(())
+    }
+
+    /// Sets the text of the line at the given row.
+    ///
+    /// Trailing whitespace is removed and the element is only written if the
+    /// text changed.
+    pub(crate) fn set_line(&mut self, y: usize, text: &str) {
+        let text = text.trim_end();
+        if let Some((element, current)) = self.lines.get_mut(y) {
+            if current != text {
+                element.set_text_content(Some(text));
+                *current = text.to_string();
+            }
+        }
+    }
+
+    /// Marks all the lines to be rewritten, e.g. when the masking of the
+    /// secrets changed.
+    pub(crate) fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Returns `true` if all the lines must be rewritten and resets the flag.
+    pub(crate) fn take_stale(&mut self) -> bool {
+        std::mem::take(&mut self.stale)
+    }
+
+    /// Announces a message to the screen readers.
+    pub(crate) fn announce(&self, message: &str) {
+        // The text must change for the message to be announced again.
+        let message = if self.status.text_content().as_deref() == Some(message) {
+            format!("{message}\u{a0}")
+        } else {
+            message.to_string()
+        };
+        self.status.set_text_content(Some(&message));
+    }
+}
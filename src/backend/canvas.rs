@@ -1,19 +1,40 @@
-use std::io::Result as IoResult;
+use std::{
+    cell::Cell as StdCell, collections::HashMap, io::Result as IoResult, rc::Rc, time::Duration,
+};
 
 use ratatui::{
     backend::WindowSize,
     buffer::Cell,
-    layout::{Position, Size},
+    layout::{Position, Rect, Size},
     prelude::Backend,
-    style::Color,
+    style::{Color, Modifier},
 };
 use web_sys::{
     js_sys::{Boolean, Map},
     wasm_bindgen::{JsCast, JsValue},
-    window,
+    window, IntersectionObserver,
+};
+
+use crate::{
+    backend::{
+        font::{FontMetrics, PixelRect},
+        utils::*,
+    },
+    error::Error,
+    event, export, render,
+    search::{self, Match},
+    splash, startup,
+    utils::now,
 };
 
-use crate::{backend::utils::*, error::Error};
+/// Font of the canvas.
+const FONT: &str = "16px monospace";
+
+/// Height of a line of the canvas in pixels.
+const LINE_HEIGHT: f64 = 19.0;
+
+/// Distance of the first cell from the edges of the canvas.
+const PADDING: f64 = 5.0;
 
 /// Canvas renderer.
 #[derive(Debug)]
@@ -54,7 +75,7 @@ impl Canvas {
             .dyn_into::<web_sys::CanvasRenderingContext2d>()
             .expect("Unable to cast canvas context");
 
-        context.set_font("16px monospace");
+        context.set_font(FONT);
         context.set_text_baseline("top");
         let body = document.body().ok_or(Error::UnableToRetrieveBody)?;
         body.append_child(&element)?;
@@ -66,6 +87,15 @@ impl Canvas {
     }
 }
 
+/// Color transition of a cell.
+#[derive(Debug, Clone, Copy)]
+struct Transition {
+    /// Foreground and background colors at the start of the transition.
+    from: (Color, Color),
+    /// Start time of the transition.
+    start: f64,
+}
+
 /// Canvas backend.
 ///
 /// This backend renders the buffer onto a HTML canvas element.
@@ -74,38 +104,285 @@ pub struct CanvasBackend {
     /// Whether the canvas has been initialized.
     initialized: bool,
     /// Current buffer.
-    buffer: Vec<Vec<Cell>>,
+    buffer: Vec<Line>,
     /// Previous buffer.
-    prev_buffer: Vec<Vec<Cell>>,
+    prev_buffer: Vec<Line>,
     /// Canvas.
     canvas: Canvas,
+    /// Callback for the damaged areas.
+    damage_callback: Option<DamageCallback>,
+    /// Whether the window was resized since the previous render.
+    ///
+    /// This is only set if the canvas follows the size of the window.
+    resized: Rc<StdCell<bool>>,
+    /// Observer that suspends the render loop while the canvas is hidden.
+    visibility_observer: Option<IntersectionObserver>,
+    /// Duration of the color transitions in milliseconds.
+    color_transition: Option<f64>,
+    /// Ongoing color transitions by cell position.
+    transitions: HashMap<(usize, usize), Transition>,
+    /// Size of the cells.
+    metrics: FontMetrics,
+    /// Delay of the resize handling in milliseconds.
+    resize_debounce: Rc<StdCell<f64>>,
 }
 
 impl CanvasBackend {
     /// Constructs a new [`CanvasBackend`].
+    ///
+    /// The canvas follows the size of the window.
     pub fn new() -> Result<Self, Error> {
         let (width, height) = get_raw_window_size();
-        Self::new_with_size(width.into(), height.into())
+        let backend = Self::new_with_size(width.into(), height.into())?;
+        backend.add_on_resize_listener()?;
+        Ok(backend)
     }
 
     /// Constructs a new [`CanvasBackend`] with the given size.
     pub fn new_with_size(width: u32, height: u32) -> Result<Self, Error> {
+        startup::measure("backend", || Self::build(width, height))
+    }
+
+    /// Constructs a new [`CanvasBackend`], see [`CanvasBackend::new_with_size`].
+    fn build(width: u32, height: u32) -> Result<Self, Error> {
         let window = window().ok_or(Error::UnableToRetrieveWindow)?;
         let document = window.document().ok_or(Error::UnableToRetrieveDocument)?;
         let canvas = Canvas::new(document, width, height, Color::Black)?;
+        let metrics = FontMetrics::measure_canvas(&canvas.context, LINE_HEIGHT);
+        let element = canvas.inner.clone();
+        let position = Rc::new(move |x: f64, y: f64| {
+            let rect = element.get_bounding_client_rect();
+            let x = ((x - rect.left() - PADDING) / metrics.cell_width).floor();
+            let y = ((y - rect.top() - PADDING) / metrics.cell_height).floor();
+            if x < 0.0 || y < 0.0 {
+                return None;
+            }
+            Some(Position::new(x as u16, y as u16))
+        });
+        event::add_mouse_listeners(
+            &canvas.inner,
+            {
+                let position = position.clone();
+                move |_, x, y| position(x, y)
+            },
+            move || metrics,
+        )?;
+        event::add_multi_touch_listeners(
+            &canvas.inner,
+            move |_, x, y| position(x, y),
+            move || metrics,
+        )?;
+        let buffer = get_sized_buffer_from_canvas(&canvas.inner, metrics);
         Ok(Self {
-            buffer: get_sized_buffer_from_canvas(&canvas.inner),
-            prev_buffer: get_sized_buffer_from_canvas(&canvas.inner),
+            prev_buffer: buffer.clone(),
+            buffer,
             initialized: false,
             canvas,
+            damage_callback: None,
+            resized: Rc::new(StdCell::new(false)),
+            visibility_observer: None,
+            color_transition: None,
+            transitions: HashMap::new(),
+            metrics,
+            resize_debounce: Rc::new(StdCell::new(DEFAULT_RESIZE_DEBOUNCE)),
         })
     }
 
+    /// Add a listener to the window resize event.
+    ///
+    /// The canvas is resized on the next render, once the size settles. See
+    /// [`CanvasBackend::set_resize_debounce`].
+    fn add_on_resize_listener(&self) -> Result<(), Error> {
+        let resized = self.resized.clone();
+        let canvas = self.canvas.inner.clone();
+        add_debounced_resize_listener(
+            &window().ok_or(Error::UnableToRetrieveWindow)?,
+            self.resize_debounce.clone(),
+            move || Some(canvas.clone().into()),
+            get_window_inner_size,
+            move || {
+                resized.set(true);
+                render::request_redraw();
+            },
+        )
+    }
+
+    /// Sets how long the window has to keep its size before the canvas is
+    /// resized for it.
+    ///
+    /// While the window is dragged, the existing canvas is stretched to the
+    /// new size as a placeholder, and the canvas is resized once, for the
+    /// final size. Zero resizes the canvas on every resize event. Defaults to
+    /// 100 milliseconds.
+    pub fn set_resize_debounce(&mut self, delay: Duration) {
+        self.resize_debounce.set(delay.as_secs_f64() * 1000.0);
+    }
+
+    /// Returns the matches of the pattern in the current content.
+    ///
+    /// See the [`search`] module for highlighting the matches.
+    ///
+    /// [`search`]: crate::search
+    pub fn search(&self, pattern: &str) -> Vec<Match> {
+        search::find(self.buffer.iter().map(|line| &line[..]), pattern)
+    }
+
+    /// Returns the current content as plain text.
+    pub fn to_text(&self) -> String {
+        export::to_text(&lines_to_buffer(&self.buffer))
+    }
+
+    /// Returns the current content as self-contained HTML with inline styles.
+    pub fn to_html(&self) -> String {
+        export::to_html(&lines_to_buffer(&self.buffer))
+    }
+
+    /// Returns the canvas as a PNG image in a data URL.
+    pub fn to_png(&self) -> Result<String, Error> {
+        Ok(self.canvas.inner.to_data_url()?)
+    }
+
+    /// Returns the size of the cells.
+    pub fn font_metrics(&self) -> FontMetrics {
+        self.metrics
+    }
+
+    /// Returns the rectangle of the cell at the given position in CSS pixels,
+    /// relative to the viewport.
+    ///
+    /// See [`CanvasBackend::area_rect`].
+    pub fn cell_rect(&self, position: Position) -> Option<PixelRect> {
+        self.area_rect(Rect::new(position.x, position.y, 1, 1))
+    }
+
+    /// Returns the rectangle that covers the given area of the canvas in CSS
+    /// pixels, relative to the viewport.
+    ///
+    /// The rectangle follows the current position of the canvas, see
+    /// [`DomBackend::area_rect`]. Returns `None` if the area is not on the
+    /// canvas.
+    ///
+    /// [`DomBackend::area_rect`]: crate::DomBackend::area_rect
+    pub fn area_rect(&self, area: Rect) -> Option<PixelRect> {
+        let width = self.buffer.first().map_or(0, |line| line.len());
+        if usize::from(area.right()) > width || usize::from(area.bottom()) > self.buffer.len() {
+            return None;
+        }
+        let origin = self.canvas.inner.get_bounding_client_rect();
+        Some(
+            self.metrics
+                .area_rect(area)
+                .offset(origin.left() + PADDING, origin.top() + PADDING),
+        )
+    }
+
     /// Sets the background color of the canvas.
     pub fn set_background_color(&mut self, color: Color) {
         self.canvas.background_color = color;
     }
 
+    /// Sets whether the render loop is suspended while the canvas is scrolled
+    /// out of view.
+    ///
+    /// This saves CPU when the canvas is embedded in a long page, e.g. a demo
+    /// in an article. The render loop resumes as soon as the canvas is
+    /// visible.
+    ///
+    /// This is disabled by default.
+    pub fn set_pause_when_hidden(&mut self, pause: bool) -> Result<(), Error> {
+        if let Some(observer) = self.visibility_observer.take() {
+            observer.disconnect();
+            render::set_visible(true);
+        }
+        if pause {
+            let observer = create_visibility_observer()?;
+            observer.observe(&self.canvas.inner);
+            self.visibility_observer = Some(observer);
+        }
+        Ok(())
+    }
+
+    /// Sets the duration of the color transitions.
+    ///
+    /// When the color of a cell changes, the old and the new color are blended
+    /// over the given duration. This smooths the flicker of rapidly updating
+    /// content such as dashboards.
+    ///
+    /// Pass `None` to disable the transitions.
+    pub fn set_color_transition(&mut self, duration: Option<Duration>) {
+        self.color_transition = duration.map(|duration| duration.as_secs_f64() * 1000.0);
+        if self.color_transition.is_none() {
+            self.transitions.clear();
+        }
+    }
+
+    /// Returns the colors of the cell as RGB colors.
+    fn cell_colors(&self, cell: &Cell) -> (Color, Color) {
+        let fg = match ansi_to_rgb(cell.fg) {
+            Some((r, g, b)) => Color::Rgb(r, g, b),
+            None => Color::Rgb(255, 255, 255),
+        };
+        let bg = match ansi_to_rgb(cell.bg) {
+            Some((r, g, b)) => Color::Rgb(r, g, b),
+            None => match self.canvas.background_color {
+                Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
+                _ => Color::Rgb(0, 0, 0),
+            },
+        };
+        if cell.modifier.contains(Modifier::REVERSED) {
+            (bg, fg)
+        } else {
+            (fg, bg)
+        }
+    }
+
+    /// Returns the colors of the cell at the given position as currently
+    /// shown, taking the ongoing transition into account.
+    ///
+    /// The second value is `true` if the transition is finished.
+    fn displayed_colors(
+        &self,
+        x: usize,
+        y: usize,
+        cell: &Cell,
+        time: f64,
+    ) -> ((Color, Color), bool) {
+        let to = self.cell_colors(cell);
+        let (Some(duration), Some(transition)) =
+            (self.color_transition, self.transitions.get(&(x, y)))
+        else {
+            return (to, true);
+        };
+        let progress = ((time - transition.start) / duration).clamp(0.0, 1.0);
+        let colors = (
+            blend_colors(transition.from.0, to.0, progress),
+            blend_colors(transition.from.1, to.1, progress),
+        );
+        (colors, progress >= 1.0)
+    }
+
+    /// Sets the callback that receives the damaged areas on every flush.
+    ///
+    /// The damaged areas are the bounding rectangles of the cells that changed
+    /// since the previous flush. This is useful for overlays (e.g. HTML
+    /// islands or charts) that only need to re-sync the changed parts.
+    pub fn on_damage<F>(&mut self, callback: F)
+    where
+        F: FnMut(&[Rect]) + 'static,
+    {
+        self.damage_callback = Some(Callback(Box::new(callback)));
+    }
+
+    /// Notifies the damage callback about the changed areas.
+    fn report_damage(&mut self) {
+        if let Some(Callback(callback)) = self.damage_callback.as_mut() {
+            let rects = get_damage_rects(&self.prev_buffer, &self.buffer);
+            if !rects.is_empty() {
+                callback(&rects);
+            }
+        }
+    }
+
     // Compare the current buffer to the previous buffer and updates the canvas
     // accordingly.
     //
@@ -119,13 +396,44 @@ impl CanvasBackend {
                 self.canvas.inner.client_height() as f64,
             );
         }
-        self.canvas.context.translate(5_f64, 5_f64)?;
-        let xmul = 10.0;
-        let ymul = 19.0;
+        let time = now();
+        if self.color_transition.is_some() && !force_redraw {
+            for (y, line) in self.buffer.iter().enumerate() {
+                if !is_line_changed(&self.prev_buffer, &self.buffer, y) {
+                    continue;
+                }
+                for (x, cell) in line.iter().enumerate() {
+                    let prev = &self.prev_buffer[y][x];
+                    if cell == prev || self.cell_colors(cell) == self.cell_colors(prev) {
+                        continue;
+                    }
+                    let (from, _) = self.displayed_colors(x, y, prev, time);
+                    self.transitions
+                        .insert((x, y), Transition { from, start: time });
+                }
+            }
+        }
+        self.canvas.context.translate(PADDING, PADDING)?;
+        let xmul = self.metrics.cell_width;
+        let ymul = self.metrics.cell_height;
+        let mut finished = Vec::new();
         for (y, line) in self.buffer.iter().enumerate() {
+            if !force_redraw
+                && !is_line_changed(&self.prev_buffer, &self.buffer, y)
+                && self.transitions.is_empty()
+            {
+                continue;
+            }
             for (x, cell) in line.iter().enumerate() {
-                if cell != &self.prev_buffer[y][x] || force_redraw {
-                    let colors = get_cell_color_for_canvas(cell, self.canvas.background_color);
+                let transitioning = self.transitions.contains_key(&(x, y));
+                if force_redraw || transitioning || cell != &self.prev_buffer[y][x] {
+                    let ((fg, bg), done) = self.displayed_colors(x, y, cell, time);
+                    if transitioning && done {
+                        finished.push((x, y));
+                    }
+                    let mut display = cell.clone();
+                    display.set_fg(fg).set_bg(bg);
+                    let colors = get_cell_color_for_canvas(&display, self.canvas.background_color);
                     self.canvas.context.set_fill_style_str(colors.1.as_str());
                     self.canvas
                         .context
@@ -140,6 +448,9 @@ impl CanvasBackend {
             }
         }
         self.canvas.context.translate(-5_f64, -5_f64)?;
+        for position in finished {
+            self.transitions.remove(&position);
+        }
         Ok(())
     }
 }
@@ -153,64 +464,101 @@ impl Backend for CanvasBackend {
         for (x, y, cell) in content {
             let y = y as usize;
             let x = x as usize;
-            let line = &mut self.buffer[y];
-            line.extend(std::iter::repeat_with(Cell::default).take(x.saturating_sub(line.len())));
-            line[x] = cell.clone();
+            self.buffer[y].set(x, cell);
         }
         Ok(())
     }
 
+    /// The canvas does not draw a cursor, so this is a no-op.
     fn hide_cursor(&mut self) -> IoResult<()> {
         Ok(())
     }
 
+    /// The canvas does not draw a cursor, so this is a no-op.
     fn show_cursor(&mut self) -> IoResult<()> {
         Ok(())
     }
 
+    /// The canvas does not track a cursor, so this always returns `(0, 0)`.
     fn get_cursor(&mut self) -> IoResult<(u16, u16)> {
         Ok((0, 0))
     }
 
+    /// The canvas does not track a cursor, so this is a no-op.
     fn set_cursor(&mut self, _x: u16, _y: u16) -> IoResult<()> {
         Ok(())
     }
 
     fn clear(&mut self) -> IoResult<()> {
-        self.buffer = get_sized_buffer();
+        if self.resized.take() {
+            let (width, height) = get_raw_window_size();
+            self.canvas.inner.set_width(width.into());
+            self.canvas.inner.set_height(height.into());
+            // Setting the size resets the state of the context.
+            self.canvas.context.set_font(FONT);
+            self.canvas.context.set_text_baseline("top");
+            self.transitions.clear();
+            self.initialized = false;
+        }
+        self.buffer = get_sized_buffer_from_canvas(&self.canvas.inner, self.metrics);
         Ok(())
     }
 
     fn size(&self) -> IoResult<Size> {
+        if self.resized.get() {
+            // Report the new size so that the terminal resizes, which clears
+            // the backend and resizes the canvas.
+            let (width, height) = get_raw_window_size();
+            let (width, height) = self.metrics.grid_size(width.into(), height.into());
+            return Ok(Size::new(width.saturating_sub(1), height.saturating_sub(1)));
+        }
         Ok(Size::new(
-            self.buffer[0].len().saturating_sub(1) as u16,
+            self.buffer
+                .first()
+                .map_or(0, |line| line.len())
+                .saturating_sub(1) as u16,
             self.buffer.len().saturating_sub(1) as u16,
         ))
     }
 
     fn window_size(&mut self) -> IoResult<WindowSize> {
-        unimplemented!()
+        Ok(WindowSize {
+            columns_rows: self.size()?,
+            pixels: Size::new(
+                u16::try_from(self.canvas.inner.width()).unwrap_or(u16::MAX),
+                u16::try_from(self.canvas.inner.height()).unwrap_or(u16::MAX),
+            ),
+        })
     }
 
     fn flush(&mut self) -> IoResult<()> {
         if !self.initialized {
+            self.prev_buffer.clear();
+            self.report_damage();
             self.update_grid(true)?;
             self.prev_buffer = self.buffer.clone();
             self.initialized = true;
+            splash::hide();
             return Ok(());
         }
-        if self.buffer != self.prev_buffer {
+        if has_changed_lines(&self.prev_buffer, &self.buffer) {
+            self.report_damage();
+            self.update_grid(false)?;
+        } else if !self.transitions.is_empty() {
             self.update_grid(false)?;
         }
         self.prev_buffer = self.buffer.clone();
         Ok(())
     }
 
+    /// The canvas does not track a cursor, so this always returns the
+    /// origin.
     fn get_cursor_position(&mut self) -> IoResult<Position> {
-        unimplemented!()
+        Ok(Position::ORIGIN)
     }
 
+    /// The canvas does not track a cursor, so this is a no-op.
     fn set_cursor_position<P: Into<Position>>(&mut self, _: P) -> IoResult<()> {
-        unimplemented!()
+        Ok(())
     }
 }
@@ -1,17 +1,562 @@
-use std::{cell::RefCell, io::Result as IoResult, rc::Rc};
+use std::{
+    borrow::Cow,
+    cell::{Cell as StdCell, RefCell},
+    collections::BTreeSet,
+    io::Result as IoResult,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
 
+use bumpalo::{
+    collections::{String as BumpString, Vec as BumpVec},
+    Bump,
+};
 use ratatui::{
     backend::WindowSize,
-    buffer::Cell,
-    layout::{Position, Size},
+    buffer::{Buffer, Cell},
+    layout::{Position, Rect, Size},
     prelude::Backend,
+    style::Color,
 };
 use web_sys::{
     wasm_bindgen::{prelude::Closure, JsCast},
-    window, Document, Element, Window,
+    window, Document, DocumentFragment, Element, HtmlElement, IntersectionObserver, Window,
+};
+
+use crate::{
+    backend::{
+        accessibility::LiveMirror,
+        font::{FontMetrics, PixelRect},
+        hints::{add_hint_mode_listener, HintMode},
+        utils::*,
+    },
+    clipboard::copy_to_clipboard,
+    effects::CrtEffect,
+    error::Error,
+    event::{self, KeyCode, KeyEvent},
+    export,
+    metadata::{self, Region, RegionKind, COPY_ATTRIBUTE},
+    palette, render,
+    search::{self, Match},
+    splash, startup,
 };
 
-use crate::{backend::utils::*, error::Error, widgets::hyperlink::HYPERLINK_MODIFIER};
+/// Class of the grid elements.
+pub(crate) const GRID_CLASS: &str = "ratzilla-grid";
+
+/// Hook that returns an extra class of a cell element, see
+/// [`DomBackendBuilder::cell_class`].
+pub type CellClassHook = fn(&Cell) -> Option<&'static str>;
+
+/// Markup of the grid of a [`DomBackend`].
+///
+/// By default, every line of the grid is a `<pre>` element and every cell is
+/// a `<span>` element. When the global rules of a CSS framework clobber these
+/// elements (e.g. a `pre` with a border and padding), other tags and classes
+/// can be picked instead:
+///
+/// ```rust no_run
+/// use ratzilla::{backend::dom::DomLayout, DomBackend};
+///
+/// let backend = DomBackend::builder()
+///     .layout(
+///         DomLayout::new()
+///             .row_tag("div")
+///             .cell_tag("i")
+///             .row_class("not-prose"),
+///     )
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// The rows are displayed as blocks and the cells inline, whatever their
+/// tag. Without a `<pre>`, the grid defaults to a monospace font unless one
+/// is set with [`DomBackendBuilder::font_family`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DomLayout {
+    /// Tag of the line elements.
+    row_tag: String,
+    /// Tag of the cell elements.
+    cell_tag: String,
+    /// Class of the line elements.
+    row_class: Option<String>,
+    /// Class of the cell elements.
+    cell_class: Option<String>,
+}
+
+impl Default for DomLayout {
+    fn default() -> Self {
+        Self {
+            row_tag: Self::DEFAULT_ROW_TAG.to_string(),
+            cell_tag: Self::DEFAULT_CELL_TAG.to_string(),
+            row_class: None,
+            cell_class: None,
+        }
+    }
+}
+
+impl DomLayout {
+    /// Default tag of the line elements.
+    pub(crate) const DEFAULT_ROW_TAG: &str = "pre";
+
+    /// Default tag of the cell elements.
+    pub(crate) const DEFAULT_CELL_TAG: &str = "span";
+
+    /// Constructs a new [`DomLayout`] with the default markup.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the tag of the line elements, `pre` by default.
+    pub fn row_tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.row_tag = tag.into();
+        self
+    }
+
+    /// Sets the tag of the cell elements, `span` by default.
+    ///
+    /// The tag must not be `a`, which is used for the hyperlinks.
+    pub fn cell_tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.cell_tag = tag.into();
+        self
+    }
+
+    /// Sets the class of the line elements.
+    pub fn row_class<S: Into<String>>(mut self, class: S) -> Self {
+        self.row_class = Some(class.into());
+        self
+    }
+
+    /// Sets the class of the cell elements.
+    ///
+    /// The class is combined with the classes of
+    /// [`DomBackendBuilder::cell_class`].
+    pub fn cell_class<S: Into<String>>(mut self, class: S) -> Self {
+        self.cell_class = Some(class.into());
+        self
+    }
+
+    /// Returns `true` if this is the default markup.
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns the stylesheet that displays the elements of the grid with the
+    /// given id like the default markup.
+    fn to_css(&self, id: &str) -> String {
+        let Self {
+            row_tag, cell_tag, ..
+        } = self;
+        format!(
+            "#{id} > {row_tag} {{ display: block; margin: 0; padding: 0; border: 0; }} \
+            #{id} > {row_tag} {cell_tag} {{ display: inline; margin: 0; padding: 0; border: 0; }}"
+        )
+    }
+}
+
+/// Number of the next backend instance.
+static NEXT_INSTANCE: AtomicUsize = AtomicUsize::new(0);
+
+/// Builder of a [`DomBackend`].
+///
+/// When the grid is mounted into a container, the options that are not set
+/// here are read from the data attributes of the container, so static sites
+/// can configure the terminal in their markup:
+///
+/// ```html
+/// <div id="terminal" data-font-size="14" data-fixed-size="80x24" data-theme="dark"></div>
+/// ```
+///
+/// - `data-font-size`: see [`DomBackendBuilder::font_size`], in pixels.
+/// - `data-fixed-size`: see [`DomBackendBuilder::fixed_size`], as
+///   `COLUMNSxROWS`.
+/// - `data-theme`: enables the [CSS variables] of the colors, so the
+///   stylesheets of the site can theme the terminal, e.g. with
+///   `[data-theme=dark] { --ansi-red: #e06c75; }`.
+///
+/// [CSS variables]: crate::palette::set_css_variables
+///
+/// Several backends can be mounted on the same page, but only the grid and
+/// the options of this builder are per instance. The event filters and
+/// handlers, the keyboard focus, the post-render hooks and the render layers
+/// are shared by every terminal of the page, e.g. a handler sees the events
+/// of all the grids.
+///
+/// ```rust no_run
+/// use ratzilla::DomBackend;
+///
+/// let backend = DomBackend::builder()
+///     .container_selector("#terminal")
+///     .grid_class("my-terminal")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct DomBackendBuilder {
+    /// Element that the grid is appended to.
+    container: Option<Element>,
+    /// Selector of the element that the grid is appended to.
+    container_selector: Option<String>,
+    /// Id of the grid element.
+    grid_id: Option<String>,
+    /// Extra class of the grid element.
+    grid_class: Option<String>,
+    /// Fixed number of columns and rows of the grid.
+    fixed_size: Option<Size>,
+    /// Font family of the grid.
+    font_family: Option<String>,
+    /// Font size of the grid in pixels.
+    font_size: Option<f64>,
+    /// Distance in pixels that the pointer moves before a press becomes a
+    /// drag.
+    drag_threshold: Option<f64>,
+    /// Shortest touch that is a long press rather than a tap.
+    long_press: Option<Duration>,
+    /// Delay of the resize handling.
+    resize_debounce: Option<Duration>,
+    /// Time budget of the DOM updates of a frame.
+    update_budget: Option<Duration>,
+    /// Hook that returns an extra class of a cell element.
+    cell_class: Option<CellClassHook>,
+    /// Markup of the grid.
+    layout: DomLayout,
+    /// Whether the columns are laid out from the right.
+    mirrored: bool,
+    /// When the keyboard focus is grabbed.
+    focus_capture: FocusCapture,
+    /// Label of the overlay shown until the grid is clicked.
+    focus_label: Option<String>,
+}
+
+impl DomBackendBuilder {
+    /// Constructs a new [`DomBackendBuilder`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the element that the grid is appended to.
+    ///
+    /// The grid then follows the size of the element instead of the size of
+    /// the window.
+    pub fn container(mut self, container: Element) -> Self {
+        self.container = Some(container);
+        self
+    }
+
+    /// Sets the selector of the element that the grid is appended to.
+    ///
+    /// The element is looked up when the backend is built. See
+    /// [`DomBackendBuilder::container`].
+    pub fn container_selector<S: Into<String>>(mut self, selector: S) -> Self {
+        self.container_selector = Some(selector.into());
+        self
+    }
+
+    /// Sets the id of the grid element.
+    ///
+    /// The id must be unique in the document, it defaults to `ratzilla-N`
+    /// where `N` is the number of the backend instance.
+    pub fn grid_id<S: Into<String>>(mut self, id: S) -> Self {
+        self.grid_id = Some(id.into());
+        self
+    }
+
+    /// Sets an extra class of the grid element.
+    ///
+    /// The grid element always has the `ratzilla-grid` class.
+    pub fn grid_class<S: Into<String>>(mut self, class: S) -> Self {
+        self.grid_class = Some(class.into());
+        self
+    }
+
+    /// Sets the font family of the grid, e.g. `"'Fira Code', monospace"`.
+    ///
+    /// By default, the font is inherited from the page.
+    pub fn font_family<S: Into<String>>(mut self, family: S) -> Self {
+        self.font_family = Some(family.into());
+        self
+    }
+
+    /// Sets the font size of the grid in pixels.
+    ///
+    /// By default, the font size is inherited from the page.
+    pub fn font_size(mut self, size: f64) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+
+    /// Sets a fixed number of columns and rows of the grid.
+    ///
+    /// By default, the grid fills the container (or the window) and follows
+    /// its size.
+    pub fn fixed_size(mut self, size: Size) -> Self {
+        self.fixed_size = Some(size);
+        self
+    }
+
+    /// Sets the distance in pixels that the pointer moves before a press
+    /// becomes a drag.
+    ///
+    /// The actions of the clickable regions and the hyperlinks are skipped
+    /// after a drag, see [`event::is_drag`]. Defaults to 4 pixels.
+    pub fn drag_threshold(mut self, distance: f64) -> Self {
+        self.drag_threshold = Some(distance);
+        self
+    }
+
+    /// Sets the shortest touch that is a long press rather than a tap.
+    ///
+    /// A tap is dispatched as a click, a long press is left to the browser.
+    /// Defaults to 500 milliseconds.
+    pub fn long_press(mut self, duration: Duration) -> Self {
+        self.long_press = Some(duration);
+        self
+    }
+
+    /// Sets how long the window has to keep its size before the grid is
+    /// rebuilt for it.
+    ///
+    /// While the window is dragged, the existing grid is stretched to the new
+    /// size as a placeholder, and the grid is rebuilt once, for the final
+    /// size. Zero rebuilds the grid on every resize event. Defaults to 100
+    /// milliseconds.
+    pub fn resize_debounce(mut self, delay: Duration) -> Self {
+        self.resize_debounce = Some(delay);
+        self
+    }
+
+    /// Sets the time budget of the DOM updates of a frame.
+    ///
+    /// When updating the changed lines takes longer, the remaining lines are
+    /// deferred to the next frame. The lines of the [`Priority`] regions and
+    /// the line of the cursor are updated first, so they stay responsive
+    /// under load. By default, all the changed lines are updated in the same
+    /// frame.
+    ///
+    /// [`Priority`]: crate::widgets::Priority
+    pub fn update_budget(mut self, budget: Duration) -> Self {
+        self.update_budget = Some(budget);
+        self
+    }
+
+    /// Sets a hook that returns an extra class of a cell element, based on
+    /// the content and the style of the cell.
+    ///
+    /// This allows styling the cells with the stylesheets of the site, e.g.
+    /// when a color is used as a semantic marker:
+    ///
+    /// ```rust no_run
+    /// use ratzilla::{ratatui::style::Color, DomBackend};
+    ///
+    /// let backend = DomBackend::builder()
+    ///     .cell_class(|cell| (cell.fg == Color::Red).then_some("error"))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// The cells are shown as displayed, e.g. masked in secret regions. With
+    /// [`DomBackend::set_merge_runs`] or [`DomBackend::set_collapse_spaces`],
+    /// cells with different classes are not merged.
+    pub fn cell_class(mut self, hook: CellClassHook) -> Self {
+        self.cell_class = Some(hook);
+        self
+    }
+
+    /// Sets the markup of the grid, see [`DomLayout`].
+    pub fn layout(mut self, layout: DomLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets whether the columns are laid out from the right, see
+    /// [`DomBackend::set_mirrored`].
+    pub fn mirrored(mut self, mirrored: bool) -> Self {
+        self.mirrored = mirrored;
+        self
+    }
+
+    /// Sets when the keyboard focus is grabbed, see [`FocusCapture`].
+    pub fn focus_capture(mut self, focus_capture: FocusCapture) -> Self {
+        self.focus_capture = focus_capture;
+        self
+    }
+
+    /// Sets the label of the overlay that is shown over the grid until it is
+    /// clicked, see [`FocusCapture::OnClick`].
+    ///
+    /// Defaults to "Click to interact".
+    pub fn focus_label<T: Into<String>>(mut self, label: T) -> Self {
+        self.focus_label = Some(label.into());
+        self
+    }
+
+    /// Builds the [`DomBackend`].
+    ///
+    /// The event listeners are added on the first render, see
+    /// [`DomBackend::init`].
+    pub fn build(self) -> Result<DomBackend, Error> {
+        startup::measure("backend", || self.build_backend())
+    }
+
+    /// Builds the [`DomBackend`], see [`DomBackendBuilder::build`].
+    fn build_backend(self) -> Result<DomBackend, Error> {
+        if let Some(distance) = self.drag_threshold {
+            event::set_drag_threshold(distance);
+        }
+        if let Some(duration) = self.long_press {
+            event::set_long_press(duration.as_secs_f64() * 1000.0);
+        }
+        let window = window().ok_or(Error::UnableToRetrieveWindow)?;
+        let document = window.document().ok_or(Error::UnableToRetrieveDocument)?;
+        let container = match (self.container, self.container_selector) {
+            (Some(container), _) => Some(container),
+            (None, Some(selector)) => Some(
+                document
+                    .query_selector(&selector)?
+                    .ok_or(Error::ElementNotFound(selector))?,
+            ),
+            (None, None) => None,
+        };
+        let config = container
+            .as_ref()
+            .map(read_container_config)
+            .unwrap_or_default();
+        if config.theme.is_some() {
+            palette::set_css_variables(true);
+        }
+        let mut font_style = String::new();
+        if let Some(family) = &self.font_family {
+            font_style.push_str(&format!("font-family: {family}; "));
+        }
+        if let Some(size) = self.font_size.or(config.font_size) {
+            font_style.push_str(&format!("font-size: {size}px; "));
+        }
+        if self.font_family.is_none() && self.layout.row_tag != DomLayout::DEFAULT_ROW_TAG {
+            font_style.push_str("font-family: monospace; ");
+        }
+        let id = self.grid_id.unwrap_or_else(|| {
+            format!("ratzilla-{}", NEXT_INSTANCE.fetch_add(1, Ordering::Relaxed))
+        });
+        let mut backend = DomBackend {
+            hint_mode: Rc::new(RefCell::new(HintMode::new(id.clone()))),
+            id,
+            initialized: Rc::new(RefCell::new(false)),
+            listeners_added: false,
+            link_navigation_key: Rc::new(RefCell::new(None)),
+            buffer: vec![],
+            prev_buffer: vec![],
+            cells: vec![],
+            cell_styles: vec![],
+            lines: vec![],
+            line_backgrounds: vec![],
+            dirty_lines: vec![],
+            secrets: vec![],
+            links: vec![],
+            mirror: None,
+            collapse_spaces: false,
+            row_background: false,
+            mirrored: self.mirrored,
+            focus_capture: self.focus_capture,
+            focus_label: self
+                .focus_label
+                .unwrap_or_else(|| DEFAULT_FOCUS_LABEL.to_string()),
+            focus_overlay: false,
+            merge_runs: false,
+            cell_class: self.cell_class,
+            layout: self.layout,
+            arena: Rc::new(Bump::new()),
+            run_break: None,
+            regions: vec![],
+            damage_callback: None,
+            visibility_observer: None,
+            cursor_position: Position::ORIGIN,
+            cursor_visible: false,
+            cursor_shape: CursorShape::default(),
+            cursor_element: None,
+            grid: document.create_element("div")?,
+            grid_class: self.grid_class,
+            fixed_size: self.fixed_size.or(config.fixed_size),
+            resize_debounce: Rc::new(StdCell::new(
+                self.resize_debounce
+                    .map_or(DEFAULT_RESIZE_DEBOUNCE, |delay| {
+                        delay.as_secs_f64() * 1000.0
+                    }),
+            )),
+            update_budget: self
+                .update_budget
+                .map(|budget| budget.as_secs_f64() * 1000.0),
+            font_style,
+            metrics: Rc::new(StdCell::new(None)),
+            container,
+            window,
+            document,
+        };
+        // The hint mode has to see the keys before the application does.
+        add_hint_mode_listener(&backend.document, backend.hint_mode.clone())?;
+        backend.reset_grid()?;
+        Ok(backend)
+    }
+}
+
+/// A hyperlink on the screen.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Link {
+    /// URL of the hyperlink.
+    pub url: String,
+    /// Browsing context to open the URL in, e.g. `_blank`.
+    pub target: Option<String>,
+    /// Area that the hyperlink covers.
+    pub area: Rect,
+}
+
+/// Shape of the cursor of a [`DomBackend`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum CursorShape {
+    /// A block that reverses the colors of the cell.
+    #[default]
+    Block,
+    /// A vertical bar at the left of the cell.
+    Bar,
+    /// A line at the bottom of the cell.
+    Underline,
+}
+
+impl CursorShape {
+    /// Returns the value of the cursor attribute for this shape.
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Block => "block",
+            Self::Bar => "bar",
+            Self::Underline => "underline",
+        }
+    }
+}
+
+/// When a [`DomBackend`] grabs the keyboard focus.
+///
+/// Key events are only dispatched while the terminal has the keyboard focus,
+/// see [`event::set_keyboard_focus`]. Otherwise, the keys are left to the
+/// rest of the page, e.g. for scrolling it or for its other inputs.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum FocusCapture {
+    /// The focus is grabbed when the grid is mounted, all the keys of the page
+    /// go to the terminal.
+    #[default]
+    OnMount,
+    /// The focus is grabbed when the grid is clicked and released when the
+    /// page is clicked elsewhere. A "click to interact" overlay is shown over
+    /// the grid until then, see [`DomBackendBuilder::focus_label`].
+    ///
+    /// This suits terminals that are one widget among many on a page.
+    OnClick,
+    /// The focus is never grabbed automatically, the application grabs it
+    /// with [`event::set_keyboard_focus`].
+    Never,
+}
+
+/// Default label of the focus overlay.
+const DEFAULT_FOCUS_LABEL: &str = "Click to interact";
 
 /// DOM backend.
 ///
@@ -21,16 +566,95 @@ use crate::{backend::utils::*, error::Error, widgets::hyperlink::HYPERLINK_MODIF
 /// appended to a `<pre>` element.
 #[derive(Debug)]
 pub struct DomBackend {
+    /// Unique id of the backend instance, used as the id of the grid element.
+    id: String,
     /// Whether the backend has been initialized.
     initialized: Rc<RefCell<bool>>,
+    /// Whether the event listeners and the stylesheets have been added.
+    listeners_added: bool,
+    /// Key that moves the focus between the hyperlinks.
+    link_navigation_key: Rc<RefCell<Option<KeyCode>>>,
+    /// State of the hint mode.
+    hint_mode: Rc<RefCell<HintMode>>,
     /// Current buffer.
-    buffer: Vec<Vec<Cell>>,
+    buffer: Vec<Line>,
     /// Previous buffer.
-    prev_buffer: Vec<Vec<Cell>>,
+    prev_buffer: Vec<Line>,
     /// Cells.
     cells: Vec<Element>,
+    /// Styles last written to the cell elements, by cell index.
+    cell_styles: Vec<Option<CellStyle>>,
+    /// Line elements.
+    lines: Vec<Element>,
+    /// Background colors painted on the line elements.
+    line_backgrounds: Vec<Option<Color>>,
+    /// Lines that need to be redrawn regardless of their content.
+    dirty_lines: Vec<bool>,
+    /// Areas of the secret regions and their mask characters.
+    secrets: Vec<(Rect, char)>,
+    /// Hyperlinks that the lines are rendered with.
+    links: Vec<Link>,
+    /// Plain-text mirror for screen readers, if the accessibility mode is
+    /// enabled.
+    mirror: Option<LiveMirror>,
+    /// Whether runs of blank cells are collapsed.
+    collapse_spaces: bool,
+    /// Whether uniform line backgrounds are painted on the line elements.
+    row_background: bool,
+    /// Whether the columns are laid out from the right.
+    mirrored: bool,
+    /// When the keyboard focus is grabbed.
+    focus_capture: FocusCapture,
+    /// Label of the overlay shown until the grid is clicked.
+    focus_label: String,
+    /// Whether the focus overlay is shown.
+    focus_overlay: bool,
+    /// Whether runs of cells with the same style are merged.
+    merge_runs: bool,
+    /// Hook that returns an extra class of a cell element.
+    cell_class: Option<CellClassHook>,
+    /// Markup of the grid.
+    layout: DomLayout,
+    /// Arena for the temporaries of a flush, reset before every flush.
+    ///
+    /// It is shared with the methods that borrow the backend mutably while
+    /// the temporaries are alive.
+    arena: Rc<Bump>,
+    /// Cell that is kept out of the merged runs, i.e. the cursor.
+    run_break: Option<Position>,
+    /// Regions whose metadata is applied to the cells.
+    regions: Vec<Region>,
+    /// Callback for the damaged areas.
+    damage_callback: Option<DamageCallback>,
+    /// Observer that suspends the render loop while the grid is hidden.
+    visibility_observer: Option<IntersectionObserver>,
+    /// Position of the cursor.
+    cursor_position: Position,
+    /// Whether the cursor is shown.
+    cursor_visible: bool,
+    /// Shape of the cursor.
+    cursor_shape: CursorShape,
+    /// Cell element that the cursor is drawn on.
+    cursor_element: Option<Element>,
     /// Grid element.
     grid: Element,
+    /// Extra class of the grid element.
+    grid_class: Option<String>,
+    /// Fixed number of columns and rows of the grid.
+    fixed_size: Option<Size>,
+    /// Delay of the resize handling in milliseconds.
+    resize_debounce: Rc<StdCell<f64>>,
+    /// Time budget of the DOM updates of a frame in milliseconds.
+    update_budget: Option<f64>,
+    /// Inline font style of the grid element.
+    font_style: String,
+    /// Measured size of the cells, `None` until measured.
+    ///
+    /// This is reset when the window is resized, since zooming the page
+    /// resizes the window.
+    metrics: Rc<StdCell<Option<FontMetrics>>>,
+    /// Element that the grid is appended to, the body if `None`.
+    container: Option<Element>,
     /// Window.
     window: Window,
     /// Document.
@@ -39,108 +663,1188 @@ pub struct DomBackend {
 
 impl DomBackend {
     /// Constructs a new [`DomBackend`].
+    ///
+    /// The grid is appended to the body of the document and follows the size
+    /// of the window.
     pub fn new() -> Result<Self, Error> {
-        let window = window().ok_or(Error::UnableToRetrieveWindow)?;
-        let document = window.document().ok_or(Error::UnableToRetrieveDocument)?;
-        let mut backend = Self {
-            initialized: Rc::new(RefCell::new(false)),
-            buffer: vec![],
-            prev_buffer: vec![],
-            cells: vec![],
-            grid: document.create_element("div")?,
-            window,
-            document,
-        };
-        backend.add_on_resize_listener();
-        backend.reset_grid()?;
-        Ok(backend)
+        DomBackendBuilder::new().build()
+    }
+
+    /// Constructs a new [`DomBackend`] that renders into the given element.
+    ///
+    /// The grid follows the size of the element.
+    pub fn new_in(container: &Element) -> Result<Self, Error> {
+        DomBackendBuilder::new()
+            .container(container.clone())
+            .build()
+    }
+
+    /// Returns a builder for configuring a [`DomBackend`].
+    pub fn builder() -> DomBackendBuilder {
+        DomBackendBuilder::new()
+    }
+
+    /// Adds the event listeners and the stylesheets of the backend, unless
+    /// they are already added.
+    ///
+    /// This is done on the first render, so building the backend stays cheap
+    /// and nothing is listened to before there is a grid. Call it to pay the
+    /// cost earlier instead, e.g. while a [`splash`] screen is shown.
+    ///
+    /// [`splash`]: crate::splash
+    pub fn init(&mut self) -> Result<(), Error> {
+        if self.listeners_added {
+            return Ok(());
+        }
+        startup::measure("listeners", || {
+            set_stylesheet(&self.document, BLINK_STYLESHEET_ID, Some(BLINK_CSS))?;
+            set_stylesheet(&self.document, CURSOR_STYLESHEET_ID, Some(CURSOR_CSS))?;
+            set_stylesheet(
+                &self.document,
+                WHITE_SPACE_STYLESHEET_ID,
+                Some(WHITE_SPACE_CSS),
+            )?;
+            self.update_direction()?;
+            self.add_focus_listener()?;
+            if !self.layout.is_default() {
+                set_stylesheet(
+                    &self.document,
+                    &format!("{}-layout", self.id),
+                    Some(&self.layout.to_css(&self.id)),
+                )?;
+            }
+            self.add_on_resize_listener()?;
+            self.add_on_click_listener()?;
+            self.add_on_link_navigation_listener()?;
+            self.add_mouse_listeners()
+        })?;
+        self.listeners_added = true;
+        Ok(())
     }
 
     /// Add a listener to the window resize event.
-    fn add_on_resize_listener(&mut self) {
+    ///
+    /// The grid is rebuilt with the new size on the next render, once the
+    /// size settles. See [`DomBackendBuilder::resize_debounce`].
+    fn add_on_resize_listener(&mut self) -> Result<(), Error> {
         let initialized = self.initialized.clone();
-        let closure = Closure::<dyn FnMut(_)>::new(move |_: web_sys::Event| {
-            initialized.replace(false);
+        let metrics = self.metrics.clone();
+        let document = self.document.clone();
+        let id = self.id.clone();
+        let container = self.container.clone();
+        add_debounced_resize_listener(
+            &self.window,
+            self.resize_debounce.clone(),
+            move || {
+                document
+                    .get_element_by_id(&id)
+                    .and_then(|grid| grid.dyn_into().ok())
+            },
+            move || match &container {
+                Some(container) => (
+                    f64::from(container.client_width()),
+                    f64::from(container.client_height()),
+                ),
+                None => get_window_inner_size(),
+            },
+            move || {
+                initialized.replace(false);
+                metrics.set(None);
+                render::request_redraw();
+            },
+        )
+    }
+
+    /// Add a listener to the document click event.
+    ///
+    /// This handles the clicks on the regions that have an action, such as
+    /// copy buttons. The clicks that end a drag are ignored.
+    fn add_on_click_listener(&mut self) -> Result<(), Error> {
+        let link_selector = format!("#{} a", self.id);
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::Event| {
+            let Some(target) = event
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+            else {
+                return;
+            };
+            // Clicks triggered by the hint mode are not trusted.
+            if event::is_drag() && event.is_trusted() {
+                if target.closest(&link_selector).ok().flatten().is_some() {
+                    event.prevent_default();
+                }
+                return;
+            }
+            let Some(button) = target
+                .closest(&format!("[{COPY_ATTRIBUTE}]"))
+                .ok()
+                .flatten()
+            else {
+                return;
+            };
+            let Some(text) = button.get_attribute(COPY_ATTRIBUTE) else {
+                return;
+            };
+            copy_to_clipboard(&text, move |success| {
+                let message = if success { "Copied!" } else { "Copy failed" };
+                let _ = show_transient_label(&button, message);
+            });
+        });
+        self.document
+            .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+        Ok(())
+    }
+
+    /// Releases the keyboard focus unless it is grabbed on mount and, for
+    /// [`FocusCapture::OnClick`], adds the listener that grabs it when the
+    /// grid is clicked.
+    fn add_focus_listener(&mut self) -> Result<(), Error> {
+        if self.focus_capture == FocusCapture::OnMount {
+            return Ok(());
+        }
+        event::set_keyboard_focus(false);
+        if self.focus_capture == FocusCapture::Never {
+            return Ok(());
+        }
+        set_stylesheet(&self.document, FOCUS_STYLESHEET_ID, Some(FOCUS_CSS))?;
+        let grid_selector = format!("#{}", self.id);
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::Event| {
+            let on_grid = event
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .and_then(|target| target.closest(&grid_selector).ok().flatten())
+                .is_some();
+            event::set_keyboard_focus(on_grid);
         });
-        self.window
-            .set_onresize(Some(closure.as_ref().unchecked_ref()));
+        self.document
+            .add_event_listener_with_callback("pointerdown", closure.as_ref().unchecked_ref())?;
         closure.forget();
+        Ok(())
+    }
+
+    /// Shows the focus overlay over the grid while it does not have the
+    /// keyboard focus, see [`FocusCapture::OnClick`].
+    fn update_focus_overlay(&mut self) -> Result<(), Error> {
+        let shown = self.focus_capture == FocusCapture::OnClick && !event::has_keyboard_focus();
+        if shown == self.focus_overlay {
+            return Ok(());
+        }
+        if shown {
+            self.grid
+                .set_attribute(FOCUS_ATTRIBUTE, &self.focus_label)?;
+        } else {
+            self.grid.remove_attribute(FOCUS_ATTRIBUTE)?;
+        }
+        self.focus_overlay = shown;
+        Ok(())
+    }
+
+    /// Add a listener to the document keydown event for moving the focus
+    /// between the hyperlinks.
+    fn add_on_link_navigation_listener(&mut self) -> Result<(), Error> {
+        let link_navigation_key = self.link_navigation_key.clone();
+        let document = self.document.clone();
+        let selector = format!("#{} a", self.id);
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::KeyboardEvent| {
+            let key_event = KeyEvent::from(event.clone());
+            if link_navigation_key.borrow().as_ref() != Some(&key_event.code) {
+                return;
+            }
+            let Ok(anchors) = document.query_selector_all(&selector) else {
+                return;
+            };
+            if anchors.length() == 0 {
+                return;
+            }
+            event.prevent_default();
+            let active = document.active_element();
+            let current = (0..anchors.length()).find(|&i| {
+                anchors
+                    .item(i)
+                    .zip(active.as_ref())
+                    .is_some_and(|(anchor, active)| anchor == **active)
+            });
+            let next = match (current, key_event.shift) {
+                (Some(i), false) => (i + 1) % anchors.length(),
+                (Some(i), true) => (i + anchors.length() - 1) % anchors.length(),
+                (None, false) => 0,
+                (None, true) => anchors.length() - 1,
+            };
+            if let Some(anchor) = anchors
+                .item(next)
+                .and_then(|anchor| anchor.dyn_into::<HtmlElement>().ok())
+            {
+                let _ = anchor.focus();
+            }
+        });
+        self.document
+            .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+        Ok(())
+    }
+
+    /// Add the listeners that dispatch the mouse events happening on the grid.
+    ///
+    /// The listeners are added to the document since the grid element is
+    /// recreated on resize, except the multi-touch listeners which are moved
+    /// to the new grid element in [`DomBackend::reset_grid`].
+    fn add_mouse_listeners(&self) -> Result<(), Error> {
+        let selector = Rc::new(format!("#{}", self.id));
+        let position = move |selector: &str, target: &Element| {
+            target.closest(selector).ok()??;
+            get_cell_position(target)
+        };
+        event::add_mouse_listeners(
+            &self.document,
+            {
+                let selector = selector.clone();
+                move |target, _, _| position(&selector, target)
+            },
+            {
+                let metrics = self.metrics.clone();
+                move || metrics.get().unwrap_or_default()
+            },
+        )?;
+        let metrics = self.metrics.clone();
+        event::add_multi_touch_listeners(
+            &self.grid,
+            move |target, _, _| position(&selector, target),
+            move || metrics.get().unwrap_or_default(),
+        )
+    }
+
+    /// Sets the key that moves the keyboard focus between the hyperlinks.
+    ///
+    /// Pressing the key focuses the next hyperlink on the screen (or the
+    /// previous one while holding shift) and pressing Enter opens the focused
+    /// hyperlink.
+    ///
+    /// This is disabled (`None`) by default.
+    pub fn set_link_navigation_key(&mut self, key: Option<KeyCode>) {
+        self.link_navigation_key.replace(key);
+    }
+
+    /// Sets the key that enters the hint mode.
+    ///
+    /// In hint mode, short letter codes are shown over all the hyperlinks and
+    /// clickable regions (e.g. [`CopyButton`]s). Typing a code activates the
+    /// corresponding target, any other key leaves the hint mode. The keys
+    /// typed in hint mode are not passed to the key event handlers.
+    ///
+    /// This is disabled (`None`) by default.
+    ///
+    /// [`CopyButton`]: crate::widgets::CopyButton
+    pub fn set_hint_key(&mut self, key: Option<KeyCode>) {
+        self.hint_mode.borrow_mut().trigger = key;
+    }
+
+    /// Sets whether the columns are laid out from the right.
+    ///
+    /// The grid is laid out from the left by default, also in right-to-left
+    /// documents (`dir="rtl"`), since the columns of the terminal do not
+    /// follow the direction of the page. Mirroring puts the first column on
+    /// the right, e.g. for applications that lay out right-to-left text
+    /// themselves. The cells are not reordered by the browser either way.
+    pub fn set_mirrored(&mut self, mirrored: bool) -> Result<(), Error> {
+        self.mirrored = mirrored;
+        self.grid.set_attribute("dir", get_direction(mirrored))?;
+        self.update_direction()
+    }
+
+    /// Updates the stylesheet of the direction of the columns.
+    fn update_direction(&self) -> Result<(), Error> {
+        set_stylesheet(
+            &self.document,
+            &format!("{}-direction", self.id),
+            Some(&get_direction_css(&self.id, self.mirrored)),
+        )
+    }
+
+    /// Sets the CRT effect of the grid.
+    ///
+    /// Pass `None` to remove the effect.
+    pub fn set_crt_effect(&mut self, effect: Option<CrtEffect>) -> Result<(), Error> {
+        let css = effect.map(|effect| effect.to_css(&self.id));
+        set_stylesheet(&self.document, &format!("{}-crt", self.id), css.as_deref())
+    }
+
+    /// Sets the duration of the color transitions.
+    ///
+    /// When the color of a cell changes, the browser interpolates between the
+    /// old and the new color over the given duration. This smooths the
+    /// flicker of rapidly updating content such as dashboards.
+    ///
+    /// Lines that are recreated (e.g. with [`DomBackend::set_collapse_spaces`]
+    /// or [`DomBackend::set_merge_runs`]) are not transitioned. Pass `None` to
+    /// disable the transitions.
+    pub fn set_color_transition(&mut self, duration: Option<Duration>) -> Result<(), Error> {
+        let css = duration.map(|duration| {
+            let duration = duration.as_millis();
+            format!(
+                "#{} {} {{ transition: color {duration}ms, background-color {duration}ms; }}",
+                self.id, self.layout.cell_tag
+            )
+        });
+        set_stylesheet(
+            &self.document,
+            &format!("{}-transition", self.id),
+            css.as_deref(),
+        )
+    }
+
+    /// Sets whether the render loop is suspended while the grid is scrolled
+    /// out of view.
+    ///
+    /// This saves CPU when the grid is embedded in a long page, e.g. a demo in
+    /// an article. The render loop resumes as soon as the grid is visible.
+    ///
+    /// This is disabled by default.
+    pub fn set_pause_when_hidden(&mut self, pause: bool) -> Result<(), Error> {
+        if let Some(observer) = self.visibility_observer.take() {
+            observer.disconnect();
+            render::set_visible(true);
+        }
+        if pause {
+            let observer = create_visibility_observer()?;
+            if self.grid.is_connected() {
+                observer.observe(&self.grid);
+            }
+            self.visibility_observer = Some(observer);
+        }
+        Ok(())
+    }
+
+    /// Returns the position of the cell that the given element belongs to.
+    ///
+    /// This translates the targets of DOM events (e.g. from custom event
+    /// listeners) into buffer coordinates. Returns `None` if the element is not
+    /// part of a cell of a [`DomBackend`] grid.
+    pub fn cell_position(element: &Element) -> Option<Position> {
+        get_cell_position(element)
+    }
+
+    /// Returns the matches of the pattern in the current content.
+    ///
+    /// See the [`search`] module for highlighting the matches.
+    ///
+    /// [`search`]: crate::search
+    pub fn search(&self, pattern: &str) -> Vec<Match> {
+        search::find(self.buffer.iter().map(|line| &line[..]), pattern)
+    }
+
+    /// Returns the current content as plain text.
+    ///
+    /// The contents of [`Secret`] widgets are masked, like on the screen.
+    ///
+    /// [`Secret`]: crate::widgets::Secret
+    pub fn to_text(&self) -> String {
+        export::to_text(&self.display_buffer())
+    }
+
+    /// Returns the current content as self-contained HTML with inline styles.
+    ///
+    /// See [`DomBackend::to_text`].
+    pub fn to_html(&self) -> String {
+        export::to_html(&self.display_buffer())
+    }
+
+    /// Renders the current content as a PNG image and returns it as a data
+    /// URL.
+    ///
+    /// See [`DomBackend::to_text`].
+    pub fn to_png(&self) -> Result<String, Error> {
+        export::to_png(&self.display_buffer())
+    }
+
+    /// Returns the current content as it is displayed, i.e. with the secrets
+    /// masked.
+    fn display_buffer(&self) -> Buffer {
+        let mut buffer = lines_to_buffer(&self.buffer);
+        for (area, _) in &self.secrets {
+            for y in area.top()..area.bottom().min(buffer.area.height) {
+                for x in area.left()..area.right().min(buffer.area.width) {
+                    let cell = self.display_cell(x as usize, y as usize, None).into_owned();
+                    buffer[(x, y)] = cell;
+                }
+            }
+        }
+        buffer
+    }
+
+    /// Returns the hyperlinks that are currently on the screen.
+    pub fn links(&self) -> Vec<Link> {
+        self.links.clone()
     }
 
     /// Reset the grid and clear the cells.
     fn reset_grid(&mut self) -> Result<(), Error> {
-        self.grid = self.document.create_element("div")?;
-        self.grid.set_attribute("id", "grid")?;
+        if let Some(observer) = &self.visibility_observer {
+            observer.disconnect();
+        }
+        let grid = std::mem::replace(&mut self.grid, self.document.create_element("div")?);
+        event::move_multi_touch_listeners(&grid, &self.grid)?;
+        self.grid.set_attribute("id", &self.id)?;
+        let class = match &self.grid_class {
+            Some(class) => format!("{GRID_CLASS} {class}"),
+            None => GRID_CLASS.to_string(),
+        };
+        self.grid.set_attribute("class", &class)?;
+        self.grid
+            .set_attribute("dir", get_direction(self.mirrored))?;
+        self.grid
+            .set_attribute(ROW_TAG_ATTRIBUTE, &self.layout.row_tag)?;
+        self.grid
+            .set_attribute(CELL_TAG_ATTRIBUTE, &self.layout.cell_tag)?;
+        self.update_grid_roles()?;
+        if !self.font_style.is_empty() {
+            self.grid.set_attribute("style", &self.font_style)?;
+        }
         self.cells.clear();
-        self.buffer = get_sized_buffer();
+        self.cell_styles.clear();
+        self.cursor_element = None;
+        self.lines.clear();
+        self.line_backgrounds.clear();
+        self.regions.clear();
+        self.secrets.clear();
+        self.links.clear();
+        let (width, height) = self.grid_size();
+        self.buffer = blank_buffer(width, height);
+        self.dirty_lines = vec![false; self.buffer.len()];
         self.prev_buffer = self.buffer.clone();
         Ok(())
     }
 
+    /// Returns the number of characters that fit in the container.
+    fn grid_size(&self) -> (u16, u16) {
+        if let Some(size) = self.fixed_size {
+            return (size.width, size.height);
+        }
+        let metrics = self.font_metrics();
+        match &self.container {
+            Some(container) => get_element_grid_size(container, metrics),
+            None => get_grid_size(metrics),
+        }
+    }
+
+    /// Returns the size of the cells.
+    ///
+    /// The cells are measured with the font of the grid the first time and
+    /// again after the window is resized.
+    pub fn font_metrics(&self) -> FontMetrics {
+        if let Some(metrics) = self.metrics.get() {
+            return metrics;
+        }
+        let parent = match &self.container {
+            Some(container) => Some(container.clone()),
+            None => self.document.body().map(Element::from),
+        };
+        let metrics = parent
+            .and_then(|parent| {
+                FontMetrics::measure_dom(
+                    &self.document,
+                    &parent,
+                    &self.layout.row_tag,
+                    &self.font_style,
+                )
+                .ok()
+            })
+            .unwrap_or_default();
+        self.metrics.set(Some(metrics));
+        metrics
+    }
+
+    /// Returns the rectangle of the cell at the given position in CSS pixels,
+    /// relative to the viewport.
+    ///
+    /// See [`DomBackend::area_rect`].
+    pub fn cell_rect(&self, position: Position) -> Option<PixelRect> {
+        self.area_rect(Rect::new(position.x, position.y, 1, 1))
+    }
+
+    /// Returns the rectangle that covers the given area of the grid in CSS
+    /// pixels, relative to the viewport.
+    ///
+    /// This positions custom overlays (e.g. tooltips or HTML elements) over
+    /// the cells. The rectangle is computed from the current layout of the
+    /// grid, so it follows the zoom level, the resizes and the scrolling of
+    /// the page: compute it again after every frame or [`Event::Resize`]. Add
+    /// `window.scrollX` and `window.scrollY` for the coordinates in the
+    /// document.
+    ///
+    /// Returns `None` if the area is not on the grid or the grid is not
+    /// rendered yet.
+    ///
+    /// [`Event::Resize`]: crate::event::Event::Resize
+    pub fn area_rect(&self, area: Rect) -> Option<PixelRect> {
+        let first = self.lines.first()?;
+        let width = self.buffer.first().map_or(0, |line| line.len());
+        if usize::from(area.right()) > width || usize::from(area.bottom()) > self.lines.len() {
+            return None;
+        }
+        let origin = first.get_bounding_client_rect();
+        let rect = self.font_metrics().area_rect(area);
+        let x = if self.mirrored {
+            origin.right() - rect.x - rect.width
+        } else {
+            origin.left() + rect.x
+        };
+        Some(PixelRect {
+            x,
+            y: origin.top() + rect.y,
+            ..rect
+        })
+    }
+
+    /// Returns the unique id of this backend instance.
+    ///
+    /// This is the id of the grid element, which can be used for scoping
+    /// custom stylesheets to this instance (e.g. `#ratzilla-0 pre { ... }`).
+    /// The grid element also has the `ratzilla-grid` class.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Sets whether runs of blank cells are collapsed into a single element.
+    ///
+    /// When enabled, consecutive space cells that share the same background
+    /// and modifiers are rendered as a single `<span>`, which greatly reduces
+    /// the number of DOM nodes for UIs that are mostly padding. Lines that
+    /// change are then re-rendered as a whole.
+    ///
+    /// This is disabled by default.
+    pub fn set_collapse_spaces(&mut self, collapse: bool) {
+        self.collapse_spaces = collapse;
+        self.initialized.replace(false);
+    }
+
+    /// Sets whether lines with a uniform background are painted as a whole.
+    ///
+    /// When enabled and all the cells of a line share the same background
+    /// color, the background is set once on the line element instead of on
+    /// every cell. This reduces style churn for e.g. striped tables and
+    /// selection bars.
+    ///
+    /// This is disabled by default.
+    pub fn set_row_background(&mut self, row_background: bool) {
+        self.row_background = row_background;
+        self.initialized.replace(false);
+    }
+
+    /// Sets whether runs of cells with the same style are merged into a single
+    /// element.
+    ///
+    /// When enabled, consecutive cells that share the same colors and
+    /// modifiers are rendered as a single `<span>`, e.g. a whole line of a
+    /// file viewer often takes a handful of elements instead of one per cell.
+    /// Lines that change are then re-rendered as a whole, which is cheaper
+    /// than restyling many cells but does not allow the color transitions.
+    ///
+    /// The cell under the cursor is always kept in its own element. This
+    /// supersedes [`DomBackend::set_collapse_spaces`] and is disabled by
+    /// default.
+    pub fn set_merge_runs(&mut self, merge: bool) {
+        self.merge_runs = merge;
+        self.initialized.replace(false);
+    }
+
+    /// Sets whether the accessibility mode is enabled.
+    ///
+    /// When enabled, the grid is labeled as a terminal and a visually hidden
+    /// `aria-live` region mirrors the plain text of the grid, so screen
+    /// readers announce the lines that change. The contents of [`Secret`]
+    /// widgets are masked in the mirror as well. Use
+    /// [`DomBackend::announce`] for messages that are not on the screen.
+    ///
+    /// This is disabled by default.
+    ///
+    /// [`Secret`]: crate::widgets::Secret
+    pub fn set_accessibility(&mut self, enabled: bool) -> Result<(), Error> {
+        match (&self.mirror, enabled) {
+            (None, true) => {
+                self.mirror = Some(LiveMirror::new(&self.document, &self.id)?);
+            }
+            (Some(mirror), false) => {
+                mirror.detach();
+                self.mirror = None;
+            }
+            _ => return Ok(()),
+        }
+        self.update_grid_roles()
+    }
+
+    /// Sets the ARIA attributes of the grid element for the accessibility
+    /// mode.
+    fn update_grid_roles(&self) -> Result<(), Error> {
+        const ATTRIBUTES: [(&str, &str); 3] = [
+            ("role", "region"),
+            ("aria-roledescription", "terminal"),
+            ("aria-label", "Terminal"),
+        ];
+        for (name, value) in ATTRIBUTES {
+            if self.mirror.is_some() {
+                self.grid.set_attribute(name, value)?;
+            } else {
+                self.grid.remove_attribute(name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Announces a message to screen readers.
+    ///
+    /// This does nothing unless the accessibility mode is enabled with
+    /// [`DomBackend::set_accessibility`].
+    pub fn announce(&self, message: &str) {
+        if let Some(mirror) = &self.mirror {
+            mirror.announce(message);
+        }
+    }
+
+    /// Updates the lines of the accessibility mirror that changed.
+    fn update_mirror(&mut self) -> Result<(), Error> {
+        let Some(mut mirror) = self.mirror.take() else {
+            return Ok(());
+        };
+        let parent = self.grid.parent_element();
+        let result = (|| {
+            if let Some(parent) = &parent {
+                mirror.attach(parent)?;
+            }
+            mirror.resize(self.buffer.len())?;
+            let stale = mirror.take_stale();
+            for y in 0..self.buffer.len() {
+                if !stale && !is_line_changed(&self.prev_buffer, &self.buffer, y) {
+                    continue;
+                }
+                let text = (0..self.buffer[y].len())
+                    .map(|x| self.display_cell(x, y, None).symbol().to_string())
+                    .collect::<String>();
+                mirror.set_line(y, &text);
+            }
+            Ok(())
+        })();
+        self.mirror = Some(mirror);
+        result
+    }
+
+    /// Sets the shape of the cursor.
+    ///
+    /// The cursor is shown with [`Frame::set_cursor_position`] and blinks. It
+    /// covers the whole run of blank cells when
+    /// [`DomBackend::set_collapse_spaces`] is enabled.
+    ///
+    /// [`Frame::set_cursor_position`]: ratatui::Frame::set_cursor_position
+    pub fn set_cursor_shape(&mut self, shape: CursorShape) {
+        self.cursor_shape = shape;
+    }
+
+    /// Sets the callback that receives the damaged areas on every flush.
+    ///
+    /// The damaged areas are the bounding rectangles of the cells that changed
+    /// since the previous flush. This is useful for overlays (e.g. HTML
+    /// islands or charts) that only need to re-sync the changed parts.
+    pub fn on_damage<F>(&mut self, callback: F)
+    where
+        F: FnMut(&[Rect]) + 'static,
+    {
+        self.damage_callback = Some(Callback(Box::new(callback)));
+    }
+
     /// Pre-render the content to the screen.
     ///
     /// This function is called from [`flush`] once to render the initial
     /// content to the screen.
     fn prerender(&mut self) -> Result<(), Error> {
-        for line in self.buffer.iter() {
-            let mut line_cells: Vec<Element> = Vec::new();
-            let mut hyperlink: Vec<Cell> = Vec::new();
-            for (i, cell) in line.iter().enumerate() {
-                if cell.modifier.contains(HYPERLINK_MODIFIER) {
-                    hyperlink.push(cell.clone());
-                    // If the next cell is not part of the hyperlink, close it
-                    if !line
-                        .get(i + 1)
-                        .map(|c| c.modifier.contains(HYPERLINK_MODIFIER))
-                        .unwrap_or(false)
-                    {
-                        let anchor = create_anchor(&self.document, &hyperlink)?;
-                        for link_cell in &hyperlink {
-                            let span = create_span(&self.document, link_cell)?;
-                            self.cells.push(span.clone());
-                            anchor.append_child(&span)?;
-                        }
-                        line_cells.push(anchor);
-                        hyperlink.clear();
-                    }
-                } else {
-                    let span = create_span(&self.document, cell)?;
-                    self.cells.push(span.clone());
-                    line_cells.push(span);
+        // Build the lines off the document so the grid is laid out once.
+        let fragment = DocumentFragment::new()?;
+        for (y, line) in self.buffer.iter().enumerate() {
+            let background = self.line_background(line);
+            let (pre, cells) = self.create_line(y, background)?;
+            fragment.append_child(&pre)?;
+            self.lines.push(pre);
+            self.cells.extend(cells);
+            self.line_backgrounds.push(background);
+        }
+        self.grid.append_child(&fragment)?;
+        Ok(())
+    }
+
+    /// Returns `true` if the changed lines are re-rendered as a whole instead
+    /// of updating their cells.
+    fn redraws_lines(&self) -> bool {
+        self.collapse_spaces || self.merge_runs
+    }
+
+    /// Marks the lines of the old and the new cursor position to be redrawn
+    /// if the cursor moved, since the cell under the cursor is not merged.
+    fn update_run_break(&mut self) {
+        let run_break = (self.merge_runs && self.cursor_visible).then_some(self.cursor_position);
+        if run_break == self.run_break {
+            return;
+        }
+        for position in [self.run_break, run_break].into_iter().flatten() {
+            if let Some(dirty) = self.dirty_lines.get_mut(position.y as usize) {
+                *dirty = true;
+            }
+        }
+        self.run_break = run_break;
+    }
+
+    /// Returns the number of cells of the run that starts at the given column.
+    ///
+    /// The run ends at the first cell with a different style, at a hyperlink
+    /// or at the cell under the cursor.
+    fn run_len(&self, x: usize, y: usize, background: Option<Color>) -> usize {
+        let is_break = |x: usize| {
+            self.run_break
+                .is_some_and(|position| position == Position::new(x as u16, y as u16))
+        };
+        if is_break(x) {
+            return 1;
+        }
+        let line = &self.buffer[y];
+        let first = self.display_cell(x, y, background);
+        1 + (x + 1..line.len())
+            .take_while(|&next| {
+                !is_break(next)
+                    && self.link_at(next, y).is_none()
+                    && is_same_style(&first, &self.display_cell(next, y, background))
+                    && self.class_of(&first)
+                        == self.class_of(&self.display_cell(next, y, background))
+            })
+            .count()
+    }
+
+    /// Returns the extra class of a cell element, if any.
+    fn class_of(&self, cell: &Cell) -> Option<&'static str> {
+        self.cell_class.and_then(|hook| hook(cell))
+    }
+
+    /// Sets the class of the layout and the extra class of a cell element.
+    fn set_class(&self, element: &Element, cell: &Cell) -> Result<(), Error> {
+        if self.cell_class.is_none() && self.layout.cell_class.is_none() {
+            return Ok(());
+        }
+        match (self.layout.cell_class.as_deref(), self.class_of(cell)) {
+            (Some(class), Some(extra)) => {
+                element.set_attribute("class", &format!("{class} {extra}"))?
+            }
+            (Some(class), None) | (None, Some(class)) => element.set_attribute("class", class)?,
+            (None, None) => element.remove_attribute("class")?,
+        }
+        Ok(())
+    }
+
+    /// Returns the background color to paint the whole line with, if any.
+    fn line_background(&self, line: &[Cell]) -> Option<Color> {
+        if self.row_background {
+            get_uniform_background(line)
+        } else {
+            None
+        }
+    }
+
+    /// Creates the line element for the line at the given row.
+    ///
+    /// Returns the element along with the elements of each cell, in order.
+    fn create_line(
+        &self,
+        y: usize,
+        background: Option<Color>,
+    ) -> Result<(Element, Vec<Element>), Error> {
+        let line = &self.buffer[y];
+        let pre = self.document.create_element(&self.layout.row_tag)?;
+        if let Some(class) = &self.layout.row_class {
+            pre.set_attribute("class", class)?;
+        }
+        if let Some(background) = background {
+            set_style_attribute(&pre, &get_background_as_css(background))?;
+        }
+        let mut cells = Vec::with_capacity(line.len());
+        let mut x = 0;
+        while x < line.len() {
+            let cell = &*self.display_cell(x, y, background);
+            if let Some(link) = self.link_at(x, y) {
+                let end = (link.area.right() as usize).min(line.len());
+                let anchor = create_anchor(&self.document, link, cell)?;
+                for x in x..end {
+                    let cell = self.display_cell(x, y, background);
+                    let span = create_span(&self.document, &self.layout.cell_tag, &cell)?;
+                    self.set_class(&span, &cell)?;
+                    cells.push(span.clone());
+                    anchor.append_child(&span)?;
                 }
+                pre.append_child(&anchor)?;
+                x = end;
+            } else if self.merge_runs {
+                let len = self.run_len(x, y, background);
+                let run_cells = BumpVec::from_iter_in(
+                    (x..x + len).map(|x| self.display_cell(x, y, background).into_owned()),
+                    &self.arena,
+                );
+                let span = create_run_span(&self.document, &self.layout.cell_tag, &run_cells)?;
+                self.set_class(&span, &run_cells[0])?;
+                cells.extend(std::iter::repeat_n(span.clone(), len));
+                pre.append_child(&span)?;
+                x += len;
+            } else if self.collapse_spaces && is_blank(cell) {
+                let len = line[x..]
+                    .iter()
+                    .enumerate()
+                    .take_while(|(i, c)| {
+                        is_blank(c)
+                            && is_same_blank(&line[x], c)
+                            && self.class_of(&line[x]) == self.class_of(c)
+                            && self.link_at(x + i, y).is_none()
+                    })
+                    .count();
+                let span = create_blank_span(&self.document, &self.layout.cell_tag, cell, len)?;
+                self.set_class(&span, cell)?;
+                cells.extend(std::iter::repeat_n(span.clone(), len));
+                pre.append_child(&span)?;
+                x += len;
+            } else {
+                let span = create_span(&self.document, &self.layout.cell_tag, cell)?;
+                self.set_class(&span, cell)?;
+                cells.push(span.clone());
+                pre.append_child(&span)?;
+                x += 1;
             }
+        }
+        Ok((pre, cells))
+    }
 
-            // Create a <pre> element for the line
-            let pre = self.document.create_element("pre")?;
+    /// Returns the cell at the given position as it should be displayed.
+    ///
+    /// Cells in secret regions are masked and the line background is removed
+    /// if it is already painted by the line element.
+    fn display_cell(&self, x: usize, y: usize, background: Option<Color>) -> Cow<'_, Cell> {
+        let cell = &self.buffer[y][x];
+        let mask = self
+            .secrets
+            .iter()
+            .find(|(area, _)| area.contains(Position::new(x as u16, y as u16)))
+            .map(|(_, mask)| *mask);
+        match mask {
+            Some(mask) if cell.symbol() != " " => {
+                let mut cell = without_background(cell, background).into_owned();
+                cell.set_char(mask);
+                Cow::Owned(cell)
+            }
+            _ => without_background(cell, background),
+        }
+    }
 
-            // Append all elements (spans and anchors) to the <pre>
-            for elem in line_cells {
-                pre.append_child(&elem)?;
+    /// Updates the secret regions from the regions registered during the
+    /// current frame.
+    ///
+    /// The lines that are affected by a change are marked to be redrawn.
+    fn update_secrets(&mut self) {
+        let secrets = metadata::regions()
+            .into_iter()
+            .filter_map(|region| match region.kind {
+                RegionKind::Secret(mask) => Some((region.area, mask)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if secrets == self.secrets {
+            return;
+        }
+        for (area, _) in self.secrets.iter().chain(secrets.iter()) {
+            for y in area.top()..area.bottom() {
+                if let Some(dirty) = self.dirty_lines.get_mut(y as usize) {
+                    *dirty = true;
+                }
             }
+        }
+        self.secrets = secrets;
+        if let Some(mirror) = &mut self.mirror {
+            mirror.invalidate();
+        }
+    }
+
+    /// Returns the hyperlink that starts at the given position, if any.
+    fn link_at(&self, x: usize, y: usize) -> Option<&Link> {
+        self.links.iter().find(|link| {
+            link.area.left() as usize == x
+                && (link.area.top()..link.area.bottom()).contains(&(y as u16))
+        })
+    }
 
-            // Append the <pre> to the grid
-            self.grid.append_child(&pre)?;
+    /// Updates the hyperlinks from the regions registered during the current
+    /// frame.
+    ///
+    /// The anchors are part of the structure of the lines, so the lines that
+    /// are affected by a change are redrawn.
+    fn update_links(&mut self) -> Result<(), Error> {
+        let links = metadata::regions()
+            .into_iter()
+            .filter_map(|region| match region.kind {
+                RegionKind::Hyperlink { url, target } if !region.area.is_empty() => Some(Link {
+                    url,
+                    target,
+                    area: region.area,
+                }),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if links == self.links {
+            return Ok(());
+        }
+        let mut rows = self
+            .links
+            .iter()
+            .chain(links.iter())
+            .flat_map(|link| link.area.top()..link.area.bottom())
+            .map(usize::from)
+            .filter(|&y| y < self.lines.len())
+            .collect::<Vec<_>>();
+        rows.sort_unstable();
+        rows.dedup();
+        self.links = links;
+        for y in rows {
+            self.redraw_line(y)?;
         }
         Ok(())
     }
 
+    /// Re-renders the line at the given row.
+    fn redraw_line(&mut self, y: usize) -> Result<(), Error> {
+        let background = self.line_background(&self.buffer[y]);
+        let (pre, cells) = self.create_line(y, background)?;
+        self.grid.replace_child(&pre, &self.lines[y])?;
+        self.lines[y] = pre;
+        self.line_backgrounds[y] = background;
+        let width = self.buffer.first().map_or(0, |line| line.len());
+        self.cells.splice(y * width..(y + 1) * width, cells);
+        self.cell_styles.clear();
+        // The metadata of the old cells is gone, apply it again.
+        self.regions.clear();
+        Ok(())
+    }
+
+    /// Returns whether each line is covered by a [`Priority`] region or holds
+    /// the cursor.
+    ///
+    /// [`Priority`]: crate::widgets::Priority
+    fn priority_lines(&self) -> Vec<bool> {
+        let mut priority = vec![false; self.buffer.len()];
+        let regions = metadata::regions();
+        let areas = regions
+            .iter()
+            .filter(|region| region.kind == RegionKind::Priority)
+            .map(|region| region.area);
+        for area in areas {
+            for y in area.top() as usize..(area.bottom() as usize).min(priority.len()) {
+                priority[y] = true;
+            }
+        }
+        if self.cursor_visible {
+            if let Some(line) = priority.get_mut(self.cursor_position.y as usize) {
+                *line = true;
+            }
+        }
+        priority
+    }
+
     /// Compare the current buffer to the previous buffer and updates the grid
     /// accordingly.
+    ///
+    /// The priority lines are updated first. Once the update budget is
+    /// exceeded, the remaining lines are deferred to the next frame, see
+    /// [`DomBackendBuilder::update_budget`].
     fn update_grid(&mut self) -> Result<(), Error> {
-        for (y, line) in self.buffer.iter().enumerate() {
-            for (x, cell) in line.iter().enumerate() {
-                if cell.modifier.contains(HYPERLINK_MODIFIER) {
-                    continue;
+        let dirty_lines = std::mem::replace(&mut self.dirty_lines, vec![false; self.buffer.len()]);
+        let is_dirty = |y: usize| dirty_lines.get(y) == Some(&true);
+        let arena = Rc::clone(&self.arena);
+        let changed_lines = changed_lines_in(&arena, &self.prev_buffer, &self.buffer);
+        let changed_lines: &[bool] = &changed_lines;
+        // Comparing the cells of the changed lines is the bulk of the diff,
+        // which runs in parallel with the `threads` feature.
+        let (buffer, prev_buffer) = (&self.buffer, &self.prev_buffer);
+        let width = buffer.first().map_or(0, |line| line.len());
+        let updated_lines = map_rows(buffer.len(), width, |y| {
+            is_dirty(y) || (changed_lines[y] && buffer[y] != prev_buffer[y])
+        });
+        let priority = match self.update_budget {
+            Some(_) => self.priority_lines(),
+            None => vec![false; self.buffer.len()],
+        };
+        let mut order = (0..self.buffer.len())
+            .filter(|&y| updated_lines[y])
+            .collect::<Vec<_>>();
+        order.sort_by_key(|&y| !priority[y]);
+        let deadline = self
+            .update_budget
+            .map(|budget| crate::utils::performance_now() + budget);
+        let is_over_budget = |y: usize| {
+            !priority[y]
+                && deadline.is_some_and(|deadline| crate::utils::performance_now() > deadline)
+        };
+        if self.redraws_lines() {
+            for (i, &y) in order.iter().enumerate() {
+                if is_over_budget(y) {
+                    self.defer_lines(&order[i..]);
+                    break;
+                }
+                self.redraw_line(y)?;
+            }
+            return Ok(());
+        }
+        self.cell_styles.resize(self.cells.len(), None);
+        for (i, &y) in order.iter().enumerate() {
+            if is_over_budget(y) {
+                self.defer_lines(&order[i..]);
+                break;
+            }
+            let line = &self.buffer[y];
+            // Restyle the whole line if its background changed.
+            let background = self.line_background(line);
+            let restyle = is_dirty(y) || background != self.line_backgrounds[y];
+            if background != self.line_backgrounds[y] {
+                match background {
+                    Some(background) => {
+                        set_style_attribute(&self.lines[y], &get_background_as_css(background))?
+                    }
+                    None => self.lines[y].remove_attribute("style")?,
                 }
-                if cell != &self.prev_buffer[y][x] {
-                    let elem = self.cells[y * self.buffer[0].len() + x].clone();
+                self.line_backgrounds[y] = background;
+            }
+            for (x, cell) in line.iter().enumerate() {
+                if restyle || cell != &self.prev_buffer[y][x] {
+                    let index = y * self.buffer[0].len() + x;
+                    let elem = self.cells[index].clone();
+                    let cell = self.display_cell(x, y, background);
                     elem.set_inner_html(cell.symbol());
-                    elem.set_attribute("style", &get_cell_style_as_css(cell))?;
+                    self.set_class(&elem, &cell)?;
+                    let style = get_cell_style(&cell);
+                    match &self.cell_styles[index] {
+                        Some(prev) => update_cell_style(elem.unchecked_ref(), prev, &style)?,
+                        None => {
+                            let mut css = BumpString::new_in(&arena);
+                            write_style_as_css(&style, &mut css);
+                            set_style_attribute(&elem, &css)?
+                        }
+                    }
+                    self.cell_styles[index] = Some(style);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks the given lines to be updated on the next frame and requests it.
+    fn defer_lines(&mut self, lines: &[usize]) {
+        for &y in lines {
+            if let Some(dirty) = self.dirty_lines.get_mut(y) {
+                *dirty = true;
+            }
+        }
+        render::request_redraw();
+    }
+
+    /// Moves the cursor to the cell element at its position.
+    ///
+    /// The cell elements are recreated when the lines are redrawn, so this
+    /// runs on every flush.
+    fn update_cursor(&mut self) -> Result<(), Error> {
+        let width = self.buffer.first().map(|line| line.len()).unwrap_or(0);
+        let (x, y) = (
+            self.cursor_position.x as usize,
+            self.cursor_position.y as usize,
+        );
+        let target = (self.cursor_visible && x < width)
+            .then(|| self.cells.get(y * width + x))
+            .flatten()
+            .cloned();
+        if let Some(element) = &self.cursor_element {
+            if Some(element) != target.as_ref() {
+                element.remove_attribute(CURSOR_ATTRIBUTE)?;
+            }
+        }
+        if let Some(element) = &target {
+            let shape = self.cursor_shape.as_str();
+            if element.get_attribute(CURSOR_ATTRIBUTE).as_deref() != Some(shape) {
+                element.set_attribute(CURSOR_ATTRIBUTE, shape)?;
+            }
+        }
+        self.cursor_element = target;
+        Ok(())
+    }
+
+    /// Notifies the damage callback about the changed areas.
+    fn report_damage(&mut self) {
+        if let Some(Callback(callback)) = self.damage_callback.as_mut() {
+            let rects = get_damage_rects(&self.prev_buffer, &self.buffer);
+            if !rects.is_empty() {
+                callback(&rects);
+            }
+        }
+    }
+
+    /// Returns the cell elements that are within the given area.
+    fn cells_in(&self, area: Rect) -> impl Iterator<Item = &Element> {
+        let width = self.buffer.first().map(|line| line.len()).unwrap_or(0);
+        let height = self.buffer.len();
+        let (left, top) = (area.left() as usize, area.top() as usize);
+        let right = (area.right() as usize).min(width);
+        let bottom = (area.bottom() as usize).min(height);
+        (top..bottom)
+            .flat_map(move |y| (left..right).map(move |x| y * width + x))
+            .filter_map(|i| self.cells.get(i))
+    }
+
+    /// Applies the metadata of the regions registered during the current frame
+    /// to the cells.
+    fn update_regions(&mut self) -> Result<(), Error> {
+        let regions = metadata::regions();
+        if regions == self.regions {
+            return Ok(());
+        }
+        for region in &self.regions {
+            let attributes = region.kind.attributes();
+            for cell in self.cells_in(region.area) {
+                for (name, _) in &attributes {
+                    cell.remove_attribute(name)?;
                 }
             }
         }
+        for region in &regions {
+            let attributes = region.kind.attributes();
+            for cell in self.cells_in(region.area) {
+                for (name, value) in &attributes {
+                    cell.set_attribute(name, value)?;
+                }
+            }
+        }
+        self.update_decorations(&regions)?;
+        self.regions = regions;
         Ok(())
     }
+
+    /// Updates the stylesheet that draws the decorations of the regions.
+    fn update_decorations(&self, regions: &[Region]) -> Result<(), Error> {
+        let mut overlines = BTreeSet::new();
+        let mut strikethroughs = BTreeSet::new();
+        for region in regions {
+            if let RegionKind::Decoration {
+                overline,
+                strikethrough,
+            } = &region.kind
+            {
+                overlines.extend(overline.as_deref());
+                strikethroughs.extend(strikethrough.as_deref());
+            }
+        }
+        let css = (!overlines.is_empty() || !strikethroughs.is_empty())
+            .then(|| get_decoration_css(overlines, strikethroughs));
+        set_stylesheet(
+            &self.document,
+            &format!("{}-decorations", self.id),
+            css.as_deref(),
+        )
+    }
 }
 
 impl Backend for DomBackend {
@@ -151,7 +1855,7 @@ impl Backend for DomBackend {
     {
         if !*self.initialized.borrow() {
             // Only runs on resize event.
-            if let Some(grid) = self.document.get_element_by_id("grid") {
+            if let Some(grid) = self.document.get_element_by_id(&self.id) {
                 grid.remove();
                 self.reset_grid()?;
             }
@@ -161,14 +1865,11 @@ impl Backend for DomBackend {
         for (x, y, cell) in content {
             let y = y as usize;
             let x = x as usize;
-            if y < self.buffer.len() {
-                let line = &mut self.buffer[y];
-                line.extend(
-                    std::iter::repeat_with(Cell::default).take(x.saturating_sub(line.len())),
-                );
-                if x < line.len() {
-                    line[x] = cell.clone();
-                }
+            let Some(line) = self.buffer.get_mut(y) else {
+                continue;
+            };
+            if x < line.len() {
+                line.set(x, cell);
             }
         }
         Ok(())
@@ -179,59 +1880,125 @@ impl Backend for DomBackend {
     /// This function is called after the [`DomBackend::draw`] function to
     /// actually render the content to the screen.
     fn flush(&mut self) -> IoResult<()> {
+        if let Some(arena) = Rc::get_mut(&mut self.arena) {
+            arena.reset();
+        }
+        if *self.initialized.borrow() && !self.grid.is_connected() {
+            // The grid was removed from the document, start over on the next
+            // render.
+            self.initialized.replace(false);
+            self.reset_grid()?;
+            return Err(Error::DetachedContainer.into());
+        }
         if !*self.initialized.borrow() {
+            self.init()?;
             self.initialized.replace(true);
-            let body = self.document.body().ok_or(Error::UnableToRetrieveBody)?;
-            body.append_child(&self.grid).map_err(Error::from)?;
+            self.update_secrets();
+            self.update_links()?;
+            match &self.container {
+                Some(container) => container.append_child(&self.grid),
+                None => self
+                    .document
+                    .body()
+                    .ok_or(Error::UnableToRetrieveBody)?
+                    .append_child(&self.grid),
+            }
+            .map_err(Error::from)?;
+            if let Some(observer) = &self.visibility_observer {
+                observer.observe(&self.grid);
+            }
             self.prerender()?;
+            self.dirty_lines = vec![false; self.buffer.len()];
+            // Everything is damaged on the first render
+            self.prev_buffer.clear();
+            self.report_damage();
+            if let Some(mirror) = &mut self.mirror {
+                mirror.invalidate();
+            }
             // Set the previous buffer to the current buffer for the first render
             self.prev_buffer = self.buffer.clone();
+            splash::hide();
         }
+        self.update_secrets();
+        self.update_links()?;
+        self.update_run_break();
         // Check if the buffer has changed since the last render and update the grid
-        if self.buffer != self.prev_buffer {
+        if has_changed_lines(&self.prev_buffer, &self.buffer) || self.dirty_lines.contains(&true) {
+            self.report_damage();
             self.update_grid()?;
         }
+        self.update_regions()?;
+        self.update_cursor()?;
+        self.update_focus_overlay()?;
+        self.update_mirror()?;
         self.prev_buffer = self.buffer.clone();
         Ok(())
     }
 
     fn hide_cursor(&mut self) -> IoResult<()> {
+        self.cursor_visible = false;
         Ok(())
     }
 
     fn show_cursor(&mut self) -> IoResult<()> {
+        self.cursor_visible = true;
         Ok(())
     }
 
     fn get_cursor(&mut self) -> IoResult<(u16, u16)> {
-        Ok((0, 0))
+        Ok(self.cursor_position.into())
     }
 
-    fn set_cursor(&mut self, _x: u16, _y: u16) -> IoResult<()> {
+    fn set_cursor(&mut self, x: u16, y: u16) -> IoResult<()> {
+        self.cursor_position = Position::new(x, y);
         Ok(())
     }
 
     fn clear(&mut self) -> IoResult<()> {
-        self.buffer = get_sized_buffer();
+        let (width, height) = self.grid_size();
+        self.buffer = blank_buffer(width, height);
         Ok(())
     }
 
     fn size(&self) -> IoResult<Size> {
+        // A measured grid keeps a spare column and row, so the rounding of the
+        // cell size does not overflow the container.
+        let spare = if self.fixed_size.is_some() { 0 } else { 1 };
+        if !*self.initialized.borrow() {
+            // The window may have been resized, report the size of the grid
+            // that is going to be built so that the terminal resizes now.
+            let (width, height) = self.grid_size();
+            return Ok(Size::new(
+                width.saturating_sub(spare),
+                height.saturating_sub(spare),
+            ));
+        }
         Ok(Size::new(
-            self.buffer[0].len().saturating_sub(1) as u16,
-            self.buffer.len().saturating_sub(1) as u16,
+            (self.buffer.first().map_or(0, |line| line.len()) as u16).saturating_sub(spare),
+            (self.buffer.len() as u16).saturating_sub(spare),
         ))
     }
 
     fn window_size(&mut self) -> IoResult<WindowSize> {
-        unimplemented!()
+        let pixels = match &self.container {
+            Some(container) => Size::new(
+                u16::try_from(container.client_width()).unwrap_or(0),
+                u16::try_from(container.client_height()).unwrap_or(0),
+            ),
+            None => get_raw_window_size().into(),
+        };
+        Ok(WindowSize {
+            columns_rows: self.size()?,
+            pixels,
+        })
     }
 
     fn get_cursor_position(&mut self) -> IoResult<Position> {
-        unimplemented!()
+        Ok(self.cursor_position)
     }
 
-    fn set_cursor_position<P: Into<Position>>(&mut self, _: P) -> IoResult<()> {
-        unimplemented!()
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> IoResult<()> {
+        self.cursor_position = position.into();
+        Ok(())
     }
 }
@@ -0,0 +1,177 @@
+//! ## Font metrics
+//!
+//! The number of columns and rows of the grid depends on the size of a cell,
+//! which depends on the font, its size and the zoom level of the page. The
+//! cell size is measured from the rendered text instead of being guessed.
+
+use ratatui::layout::Rect;
+use web_sys::{CanvasRenderingContext2d, Document, Element};
+
+use crate::{backend::dom::GRID_CLASS, error::Error};
+
+/// Number of characters of the probe, more characters average out the
+/// rounding of the text width.
+const PROBE_LEN: usize = 100;
+
+/// Size of a cell in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontMetrics {
+    /// Width of a cell.
+    pub cell_width: f64,
+    /// Height of a cell.
+    pub cell_height: f64,
+}
+
+/// A rectangle in CSS pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PixelRect {
+    /// Distance of the left edge from the origin.
+    pub x: f64,
+    /// Distance of the top edge from the origin.
+    pub y: f64,
+    /// Width of the rectangle.
+    pub width: f64,
+    /// Height of the rectangle.
+    pub height: f64,
+}
+
+impl PixelRect {
+    /// Returns the rectangle moved by the given distances.
+    pub fn offset(self, dx: f64, dy: f64) -> Self {
+        Self {
+            x: self.x + dx,
+            y: self.y + dy,
+            ..self
+        }
+    }
+}
+
+impl Default for FontMetrics {
+    /// Returns the estimated cell size of a 16px monospace font, which is used
+    /// when the font cannot be measured.
+    fn default() -> Self {
+        Self {
+            cell_width: 10.0,
+            cell_height: 20.0,
+        }
+    }
+}
+
+impl FontMetrics {
+    /// Measures the size of the cells of a DOM grid.
+    ///
+    /// A hidden line with the given tag is rendered into `parent` with the
+    /// grid class and the given inline style, so the page styles of the grid
+    /// apply to it as well. Vertical margins of the lines are not taken into
+    /// account.
+    pub(crate) fn measure_dom(
+        document: &Document,
+        parent: &Element,
+        row_tag: &str,
+        style: &str,
+    ) -> Result<Self, Error> {
+        let probe = document.create_element("div")?;
+        probe.set_attribute("class", GRID_CLASS)?;
+        probe.set_attribute(
+            "style",
+            &format!("position: absolute; visibility: hidden; left: -9999px; {style}"),
+        )?;
+        let line = document.create_element(row_tag)?;
+        line.set_text_content(Some(&"M".repeat(PROBE_LEN)));
+        probe.append_child(&line)?;
+        parent.append_child(&probe)?;
+        let rect = line.get_bounding_client_rect();
+        probe.remove();
+        Ok(Self::new(rect.width() / PROBE_LEN as f64, rect.height()))
+    }
+
+    /// Measures the size of the cells drawn with the font of a canvas
+    /// context.
+    ///
+    /// The height of the cells is given, since the canvas does not have a
+    /// line height.
+    pub(crate) fn measure_canvas(context: &CanvasRenderingContext2d, cell_height: f64) -> Self {
+        let width = context
+            .measure_text(&"M".repeat(PROBE_LEN))
+            .map(|metrics| metrics.width() / PROBE_LEN as f64)
+            .unwrap_or_default();
+        Self::new(width, cell_height)
+    }
+
+    /// Constructs new [`FontMetrics`], falling back to the default if the
+    /// size is not positive (e.g. the text is not rendered).
+    fn new(cell_width: f64, cell_height: f64) -> Self {
+        if cell_width > 0.0 && cell_height > 0.0 {
+            Self {
+                cell_width,
+                cell_height,
+            }
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Returns the rectangle that covers the given area of the grid, relative
+    /// to the top-left corner of the first cell.
+    pub fn area_rect(&self, area: Rect) -> PixelRect {
+        PixelRect {
+            x: f64::from(area.x) * self.cell_width,
+            y: f64::from(area.y) * self.cell_height,
+            width: f64::from(area.width) * self.cell_width,
+            height: f64::from(area.height) * self.cell_height,
+        }
+    }
+
+    /// Returns the number of columns and rows that fit in the given number of
+    /// pixels.
+    ///
+    /// The grid has at least one column and one row, e.g. for an empty
+    /// container that has no height yet.
+    pub fn grid_size(&self, width: f64, height: f64) -> (u16, u16) {
+        (
+            (width / self.cell_width)
+                .floor()
+                .clamp(1.0, f64::from(u16::MAX)) as u16,
+            (height / self.cell_height)
+                .floor()
+                .clamp(1.0, f64::from(u16::MAX)) as u16,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn area_rect() {
+        let metrics = FontMetrics::new(10.0, 20.0);
+        assert_eq!(
+            metrics.area_rect(Rect::new(2, 3, 4, 1)),
+            PixelRect {
+                x: 20.0,
+                y: 60.0,
+                width: 40.0,
+                height: 20.0,
+            }
+        );
+        assert_eq!(
+            metrics.area_rect(Rect::new(1, 1, 1, 1)).offset(5.0, -5.0),
+            PixelRect {
+                x: 15.0,
+                y: 15.0,
+                width: 10.0,
+                height: 20.0,
+            }
+        );
+    }
+
+    #[test]
+    fn grid_size() {
+        let metrics = FontMetrics::new(10.0, 20.0);
+        assert_eq!(metrics.grid_size(805.0, 410.0), (80, 20));
+        // An empty container has no height.
+        assert_eq!(metrics.grid_size(800.0, 0.0), (80, 1));
+        assert_eq!(metrics.grid_size(0.0, 0.0), (1, 1));
+    }
+}
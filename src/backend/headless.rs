@@ -0,0 +1,371 @@
+use std::io::Result as IoResult;
+
+use ratatui::{
+    backend::{TestBackend, WindowSize},
+    buffer::{Buffer, Cell},
+    layout::{Position, Rect, Size},
+    prelude::Backend,
+    Frame, Terminal,
+};
+
+use crate::backend::utils::{blank_buffer, get_damage_rects, lines_to_buffer, Line};
+
+/// Headless backend.
+///
+/// This backend keeps the content in memory and does not need a document, so
+/// it can be used for testing the rendering of a UI, e.g. with
+/// `wasm-bindgen-test` or even natively with `cargo test`. It uses the same
+/// cell buffers and diffing as the [`DomBackend`], and records the areas
+/// that changed on every flush.
+///
+/// ```rust
+/// use ratzilla::{
+///     backend::headless::{assert_buffer_eq, HeadlessBackend},
+///     ratatui::{buffer::Buffer, widgets::Paragraph, Terminal},
+/// };
+///
+/// let mut terminal = Terminal::new(HeadlessBackend::new(5, 2)).unwrap();
+/// terminal
+///     .draw(|frame| frame.render_widget(Paragraph::new("hello"), frame.area()))
+///     .unwrap();
+/// assert_buffer_eq(
+///     &terminal.backend().buffer(),
+///     &Buffer::with_lines(["hello", "     "]),
+/// );
+/// ```
+///
+/// [`DomBackend`]: crate::DomBackend
+#[derive(Debug, Clone)]
+pub struct HeadlessBackend {
+    /// Current buffer.
+    buffer: Vec<Line>,
+    /// Content as of the last flush.
+    displayed: Vec<Line>,
+    /// Areas that changed in the last flush.
+    damage: Vec<Rect>,
+    /// Number of flushes.
+    flushes: usize,
+    /// Position of the cursor.
+    cursor_position: Position,
+    /// Whether the cursor is shown.
+    cursor_visible: bool,
+}
+
+impl HeadlessBackend {
+    /// Constructs a new [`HeadlessBackend`] with the given number of columns
+    /// and rows.
+    pub fn new(width: u16, height: u16) -> Self {
+        let buffer = blank_buffer(width, height);
+        Self {
+            displayed: buffer.clone(),
+            buffer,
+            damage: Vec::new(),
+            flushes: 0,
+            cursor_position: Position::ORIGIN,
+            cursor_visible: false,
+        }
+    }
+
+    /// Resizes the grid, which clears it.
+    ///
+    /// The terminal picks up the new size on the next draw, like after a
+    /// resize of the window.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        *self = Self {
+            flushes: self.flushes,
+            ..Self::new(width, height)
+        };
+    }
+
+    /// Returns the content as of the last flush.
+    pub fn buffer(&self) -> Buffer {
+        lines_to_buffer(&self.displayed)
+    }
+
+    /// Returns the text of the content as of the last flush.
+    ///
+    /// See [`snapshot`].
+    pub fn snapshot(&self) -> String {
+        snapshot(&self.buffer())
+    }
+
+    /// Returns the areas that changed in the last flush.
+    ///
+    /// Everything changed in the first flush.
+    pub fn damage(&self) -> &[Rect] {
+        &self.damage
+    }
+
+    /// Returns the number of flushes so far.
+    pub fn flushes(&self) -> usize {
+        self.flushes
+    }
+
+    /// Returns the position of the cursor if it is shown.
+    pub fn cursor(&self) -> Option<Position> {
+        self.cursor_visible.then_some(self.cursor_position)
+    }
+}
+
+impl Backend for HeadlessBackend {
+    fn draw<'a, I>(&mut self, content: I) -> IoResult<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        for (x, y, cell) in content {
+            let Some(line) = self.buffer.get_mut(y as usize) else {
+                continue;
+            };
+            if (x as usize) < line.len() {
+                line.set(x as usize, cell);
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        let prev = if self.flushes == 0 {
+            &[][..]
+        } else {
+            &self.displayed[..]
+        };
+        self.damage = get_damage_rects(prev, &self.buffer);
+        self.displayed = self.buffer.clone();
+        self.flushes += 1;
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> IoResult<()> {
+        self.cursor_visible = false;
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> IoResult<()> {
+        self.cursor_visible = true;
+        Ok(())
+    }
+
+    fn get_cursor(&mut self) -> IoResult<(u16, u16)> {
+        Ok(self.cursor_position.into())
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> IoResult<()> {
+        self.cursor_position = Position::new(x, y);
+        Ok(())
+    }
+
+    fn clear(&mut self) -> IoResult<()> {
+        let size = self.size()?;
+        self.buffer = blank_buffer(size.width, size.height);
+        Ok(())
+    }
+
+    fn size(&self) -> IoResult<Size> {
+        Ok(Size::new(
+            self.buffer.first().map_or(0, |line| line.len()) as u16,
+            self.buffer.len() as u16,
+        ))
+    }
+
+    fn window_size(&mut self) -> IoResult<WindowSize> {
+        Ok(WindowSize {
+            columns_rows: self.size()?,
+            pixels: Size::default(),
+        })
+    }
+
+    fn get_cursor_position(&mut self) -> IoResult<Position> {
+        Ok(self.cursor_position)
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> IoResult<()> {
+        self.cursor_position = position.into();
+        Ok(())
+    }
+}
+
+/// Returns the text of a buffer.
+///
+/// Rows are separated with newlines and keep their trailing whitespace, so
+/// the snapshot shows the size of the buffer. Styles are not included.
+pub fn snapshot(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    (area.top()..area.bottom())
+        .map(|y| {
+            (area.left()..area.right())
+                .filter_map(|x| buffer.cell((x, y)))
+                .map(|cell| cell.symbol())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Asserts that two buffers are equal, including the styles of the cells.
+///
+/// On failure, the panic message shows the snapshots of both buffers and the
+/// first cells that differ.
+#[track_caller]
+pub fn assert_buffer_eq(actual: &Buffer, expected: &Buffer) {
+    if let Some(message) = buffer_difference(actual, expected) {
+        panic!("{message}");
+    }
+}
+
+/// Asserts that an application renders the same frames with the
+/// [`HeadlessBackend`] as with ratatui's [`TestBackend`].
+///
+/// This checks that the web rendering is on par with the native rendering in
+/// CI, without a browser. The render callback is called with the index of the
+/// frame, so consecutive frames can differ and exercise the diffing of the
+/// backend. On failure, the panic message shows the first frame that differs,
+/// see [`assert_buffer_eq`].
+///
+/// ```rust
+/// use ratzilla::{backend::headless::assert_native_parity, ratatui::widgets::Paragraph};
+///
+/// assert_native_parity(10, 2, 3, |frame, i| {
+///     frame.render_widget(Paragraph::new(format!("frame {i}")), frame.area());
+/// });
+/// ```
+#[track_caller]
+pub fn assert_native_parity<F>(width: u16, height: u16, frames: usize, mut render: F)
+where
+    F: FnMut(&mut Frame, usize),
+{
+    let mut web = Terminal::new(HeadlessBackend::new(width, height)).expect("headless terminal");
+    let mut native = Terminal::new(TestBackend::new(width, height)).expect("test terminal");
+    for i in 0..frames {
+        web.draw(|frame| render(frame, i)).expect("headless draw");
+        native.draw(|frame| render(frame, i)).expect("test draw");
+        if let Some(message) = buffer_difference(&web.backend().buffer(), native.backend().buffer())
+        {
+            panic!("frame {i} differs from the native rendering: {message}");
+        }
+    }
+}
+
+/// Returns a description of the difference between two buffers, if any.
+fn buffer_difference(actual: &Buffer, expected: &Buffer) -> Option<String> {
+    if actual == expected {
+        return None;
+    }
+    let mut message = format!(
+        "buffers are not equal\n\nactual ({}x{}):\n{}\n\nexpected ({}x{}):\n{}\n",
+        actual.area.width,
+        actual.area.height,
+        snapshot(actual),
+        expected.area.width,
+        expected.area.height,
+        snapshot(expected),
+    );
+    if actual.area == expected.area {
+        let differences = actual
+            .content
+            .iter()
+            .zip(&expected.content)
+            .enumerate()
+            .filter(|(_, (actual, expected))| actual != expected)
+            .take(10);
+        message.push_str("\ndifferences:\n");
+        for (i, (actual_cell, expected_cell)) in differences {
+            let position = actual.pos_of(i);
+            message.push_str(&format!(
+                "  ({}, {}): {actual_cell:?} != {expected_cell:?}\n",
+                position.0, position.1
+            ));
+        }
+    }
+    Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        style::{Color, Style},
+        widgets::Paragraph,
+        Terminal,
+    };
+
+    use super::*;
+
+    fn draw(terminal: &mut Terminal<HeadlessBackend>, text: &str) {
+        terminal
+            .draw(|frame| frame.render_widget(Paragraph::new(text), frame.area()))
+            .expect("draw");
+    }
+
+    #[test]
+    fn renders_into_memory() {
+        let mut terminal = Terminal::new(HeadlessBackend::new(6, 2)).expect("terminal");
+        draw(&mut terminal, "hello\nworld");
+        assert_eq!(terminal.backend().snapshot(), "hello \nworld ");
+        assert_buffer_eq(
+            &terminal.backend().buffer(),
+            &Buffer::with_lines(["hello ", "world "]),
+        );
+        assert_eq!(terminal.backend().flushes(), 1);
+    }
+
+    #[test]
+    fn first_flush_damages_everything() {
+        let mut terminal = Terminal::new(HeadlessBackend::new(4, 2)).expect("terminal");
+        draw(&mut terminal, "");
+        assert_eq!(terminal.backend().damage(), &[Rect::new(0, 0, 4, 2)]);
+    }
+
+    #[test]
+    fn damage_covers_changed_cells() {
+        let mut terminal = Terminal::new(HeadlessBackend::new(8, 3)).expect("terminal");
+        draw(&mut terminal, "abc\ndef\nghi");
+        draw(&mut terminal, "abc\ndxf\nghi");
+        assert_eq!(terminal.backend().damage(), &[Rect::new(1, 1, 1, 1)]);
+        draw(&mut terminal, "abc\ndxf\nghi");
+        assert!(terminal.backend().damage().is_empty());
+    }
+
+    #[test]
+    fn style_changes_are_damage() {
+        let mut terminal = Terminal::new(HeadlessBackend::new(3, 1)).expect("terminal");
+        draw(&mut terminal, "abc");
+        terminal
+            .draw(|frame| {
+                let paragraph = Paragraph::new("abc").style(Style::default().fg(Color::Red));
+                frame.render_widget(paragraph, frame.area());
+            })
+            .expect("draw");
+        assert_eq!(terminal.backend().damage(), &[Rect::new(0, 0, 3, 1)]);
+    }
+
+    #[test]
+    fn resize_clears_the_grid() {
+        let mut terminal = Terminal::new(HeadlessBackend::new(3, 1)).expect("terminal");
+        draw(&mut terminal, "abc");
+        terminal.backend_mut().resize(2, 2);
+        draw(&mut terminal, "xy\nz");
+        assert_eq!(terminal.backend().snapshot(), "xy\nz ");
+    }
+
+    #[test]
+    fn cursor() {
+        let mut terminal = Terminal::new(HeadlessBackend::new(3, 1)).expect("terminal");
+        terminal
+            .draw(|frame| frame.set_cursor_position((1, 0)))
+            .expect("draw");
+        assert_eq!(terminal.backend().cursor(), Some(Position::new(1, 0)));
+    }
+
+    #[test]
+    fn native_parity() {
+        assert_native_parity(6, 3, 3, |frame, i| {
+            let text = ["abc\ndef", "abc\nxyz\n123", ""][i];
+            frame.render_widget(Paragraph::new(text), frame.area());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "buffers are not equal")]
+    fn assert_buffer_eq_panics_on_difference() {
+        assert_buffer_eq(&Buffer::with_lines(["ab"]), &Buffer::with_lines(["ac"]));
+    }
+}
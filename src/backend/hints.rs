@@ -0,0 +1,194 @@
+use std::{cell::RefCell, rc::Rc};
+
+use web_sys::{
+    wasm_bindgen::{prelude::Closure, JsCast},
+    Document, Element, HtmlElement, KeyboardEvent,
+};
+
+use crate::{
+    error::Error,
+    event::{KeyCode, KeyEvent},
+    metadata::COPY_ATTRIBUTE,
+};
+
+/// Characters used for the hint labels.
+const HINT_CHARS: &str = "asdfghjkl";
+
+/// Style of a hint label.
+const HINT_STYLE: &str = "position: fixed; padding: 0 0.2em; \
+    background-color: rgb(255, 255, 0); color: rgb(0, 0, 0); font-family: monospace; \
+    font-weight: bold; pointer-events: none; z-index: 2147483647;";
+
+/// State of the hint mode.
+#[derive(Debug, Default)]
+pub(crate) struct HintMode {
+    /// Key that enters the hint mode.
+    pub(crate) trigger: Option<KeyCode>,
+    /// Id of the grid element whose targets get hints.
+    grid_id: String,
+    /// Labels and targets of the hints, if the hint mode is active.
+    targets: Vec<(String, HtmlElement)>,
+    /// Characters typed so far.
+    typed: String,
+}
+
+impl HintMode {
+    /// Constructs a new [`HintMode`] for the grid with the given id.
+    pub(crate) fn new(grid_id: String) -> Self {
+        Self {
+            grid_id,
+            ..Self::default()
+        }
+    }
+
+    /// Returns `true` if the hint labels are shown.
+    fn is_active(&self) -> bool {
+        !self.targets.is_empty()
+    }
+}
+
+/// Returns the id of the element that contains the hint labels.
+fn hints_id(grid_id: &str) -> String {
+    format!("{grid_id}-hints")
+}
+
+/// Returns `count` distinct hint labels of equal length.
+fn hint_labels(count: usize) -> Vec<String> {
+    let chars = HINT_CHARS.chars().collect::<Vec<_>>();
+    let mut len = 1;
+    while chars.len().pow(len) < count {
+        len += 1;
+    }
+    (0..count)
+        .map(|mut i| {
+            let mut label = vec![chars[0]; len as usize];
+            for c in label.iter_mut().rev() {
+                *c = chars[i % chars.len()];
+                i /= chars.len();
+            }
+            label.into_iter().collect()
+        })
+        .collect()
+}
+
+/// Adds a listener to the document keydown event that drives the hint mode.
+///
+/// The listener has to be added before the listeners of the application so
+/// that the keys typed in hint mode are not propagated to it.
+pub(crate) fn add_hint_mode_listener(
+    document: &Document,
+    state: Rc<RefCell<HintMode>>,
+) -> Result<(), Error> {
+    let closure = Closure::<dyn FnMut(_)>::new({
+        let document = document.clone();
+        move |event: KeyboardEvent| {
+            let key_event = KeyEvent::from(event.clone());
+            let mut state = state.borrow_mut();
+            if !state.is_active() {
+                if state.trigger.as_ref() == Some(&key_event.code) {
+                    event.prevent_default();
+                    event.stop_immediate_propagation();
+                    let _ = show_hints(&document, &mut state);
+                }
+                return;
+            }
+            event.prevent_default();
+            event.stop_immediate_propagation();
+            let KeyCode::Char(c) = key_event.code else {
+                hide_hints(&document, &mut state);
+                return;
+            };
+            state.typed.push(c.to_ascii_lowercase());
+            let typed = state.typed.clone();
+            if let Some((_, target)) = state.targets.iter().find(|(label, _)| *label == typed) {
+                let target = target.clone();
+                hide_hints(&document, &mut state);
+                target.click();
+            } else if !state
+                .targets
+                .iter()
+                .any(|(label, _)| label.starts_with(&typed))
+            {
+                hide_hints(&document, &mut state);
+            }
+        }
+    });
+    document.add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    Ok(())
+}
+
+/// Shows the hint labels over the hyperlinks and the clickable regions.
+fn show_hints(document: &Document, state: &mut HintMode) -> Result<(), Error> {
+    let grid_id = &state.grid_id;
+    let elements =
+        document.query_selector_all(&format!("#{grid_id} a, #{grid_id} [{COPY_ATTRIBUTE}]"))?;
+    let mut targets: Vec<HtmlElement> = Vec::new();
+    for i in 0..elements.length() {
+        let Some(element) = elements
+            .item(i)
+            .and_then(|e| e.dyn_into::<HtmlElement>().ok())
+        else {
+            continue;
+        };
+        // The cells of a region share the attribute, only keep the first one.
+        let copy_text = element.get_attribute(COPY_ATTRIBUTE);
+        if copy_text.is_some()
+            && element
+                .previous_element_sibling()
+                .and_then(|prev| prev.get_attribute(COPY_ATTRIBUTE))
+                == copy_text
+        {
+            continue;
+        }
+        // Skip the cells of the hyperlinks, the anchor is the target.
+        if element
+            .parent_element()
+            .is_some_and(|parent| parent.tag_name().eq_ignore_ascii_case("a"))
+        {
+            continue;
+        }
+        targets.push(element);
+    }
+    if targets.is_empty() {
+        return Ok(());
+    }
+
+    let container = document.create_element("div")?;
+    container.set_attribute("id", &hints_id(&state.grid_id))?;
+    for (label, target) in hint_labels(targets.len()).into_iter().zip(targets) {
+        let hint = create_hint(document, &label, &target)?;
+        container.append_child(&hint)?;
+        state.targets.push((label, target));
+    }
+    document
+        .body()
+        .ok_or(Error::UnableToRetrieveBody)?
+        .append_child(&container)?;
+    Ok(())
+}
+
+/// Creates a hint label that is placed over the target.
+fn create_hint(document: &Document, label: &str, target: &Element) -> Result<Element, Error> {
+    let rect = target.get_bounding_client_rect();
+    let hint = document.create_element("span")?;
+    hint.set_text_content(Some(label));
+    hint.set_attribute(
+        "style",
+        &format!(
+            "{HINT_STYLE} left: {}px; top: {}px;",
+            rect.left(),
+            rect.top()
+        ),
+    )?;
+    Ok(hint)
+}
+
+/// Hides the hint labels and leaves the hint mode.
+fn hide_hints(document: &Document, state: &mut HintMode) {
+    if let Some(container) = document.get_element_by_id(&hints_id(&state.grid_id)) {
+        container.remove();
+    }
+    state.targets.clear();
+    state.typed.clear();
+}
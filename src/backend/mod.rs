@@ -9,11 +9,23 @@
 //!
 //! The [`DomBackend`] is more flexible and easier to style, but it can be slower for large TUIs. The [`CanvasBackend`] is faster and more efficient, but does not support all the features of the [`DomBackend`] such as hyperlinks.
 
+/// Accessibility mirror.
+pub(crate) mod accessibility;
+
 /// Canvas backend.
 pub mod canvas;
 
 /// DOM backend.
 pub mod dom;
 
+/// Font metrics.
+pub mod font;
+
+/// Headless backend.
+pub mod headless;
+
+/// Link hint mode.
+pub(crate) mod hints;
+
 /// Backend utilities.
 pub(crate) mod utils;
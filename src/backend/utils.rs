@@ -1,71 +1,930 @@
+use std::{
+    borrow::Cow,
+    cell::{Cell as StdCell, RefCell},
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Write},
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Deref,
+    rc::Rc,
+};
+
+#[cfg(feature = "threads")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use bumpalo::{collections::Vec as BumpVec, Bump};
 use ratatui::{
-    buffer::Cell,
+    buffer::{Buffer, Cell},
+    layout::{Position, Rect, Size},
     style::{Color, Modifier},
 };
-use web_sys::{wasm_bindgen::JsValue, Document, Element, HtmlCanvasElement};
+use web_sys::{
+    js_sys::Array,
+    wasm_bindgen::{intern, prelude::Closure, unintern, JsCast, JsValue},
+    Document, Element, HtmlCanvasElement, HtmlElement, IntersectionObserver,
+    IntersectionObserverEntry, Window,
+};
+
+use crate::{
+    backend::{
+        dom::{DomLayout, Link, GRID_CLASS},
+        font::FontMetrics,
+    },
+    capture::merge_rows,
+    error::Error,
+    palette, render,
+};
+
+/// Largest number of style strings kept interned, see [`intern_style`].
+pub(crate) const STYLE_CACHE_SIZE: usize = 512;
+
+thread_local! {
+    /// Interned style strings, see [`intern_style`].
+    static STYLE_CACHE: RefCell<StyleCache> = RefCell::new(StyleCache::default());
+}
+
+/// Least recently used style strings that are interned.
+#[derive(Debug, Default)]
+struct StyleCache {
+    /// Last use of each string.
+    uses: HashMap<Rc<str>, u64>,
+    /// Strings by a use that is not later than their last use.
+    ///
+    /// A hit only updates [`uses`](Self::uses), the entry is moved when it
+    /// comes up for eviction instead.
+    by_use: BTreeMap<u64, Rc<str>>,
+    /// Counter of the uses.
+    clock: u64,
+}
+
+impl StyleCache {
+    /// Records a use of a string.
+    ///
+    /// Returns `true` if the string is new, along with the least recently
+    /// used string that is evicted to make room for it, if any.
+    fn touch(&mut self, style: &str) -> (bool, Option<Rc<str>>) {
+        self.clock += 1;
+        if let Some(last_use) = self.uses.get_mut(style) {
+            *last_use = self.clock;
+            return (false, None);
+        }
+        let style = Rc::<str>::from(style);
+        self.uses.insert(Rc::clone(&style), self.clock);
+        self.by_use.insert(self.clock, style);
+        if self.uses.len() <= STYLE_CACHE_SIZE {
+            return (true, None);
+        }
+        while let Some((used, candidate)) = self.by_use.pop_first() {
+            match self.uses.get(&candidate) {
+                Some(&last_use) if last_use != used => {
+                    // Used again since, so it is not the least recently used.
+                    self.by_use.insert(last_use, candidate);
+                }
+                _ => {
+                    self.uses.remove(&candidate);
+                    return (true, Some(candidate));
+                }
+            }
+        }
+        (true, None)
+    }
+}
+
+/// Interns a style string, so passing it to JavaScript again does not encode
+/// it again.
+///
+/// The styles of the cells repeat a lot, but the strings that cross the
+/// boundary are encoded from UTF-8 to UTF-16 every time. The interned strings
+/// are looked up by `wasm-bindgen` instead. Only the [`STYLE_CACHE_SIZE`]
+/// most recently used strings are kept, so animated colors do not grow the
+/// cache forever.
+pub(crate) fn intern_style(style: &str) -> &str {
+    let (is_new, evicted) = STYLE_CACHE.with(|cache| cache.borrow_mut().touch(style));
+    if let Some(evicted) = evicted {
+        unintern(&evicted);
+    }
+    if is_new {
+        intern(style);
+    }
+    style
+}
+
+/// Sets the `style` attribute of an element to an interned style string.
+pub(crate) fn set_style_attribute(element: &Element, style: &str) -> Result<(), Error> {
+    element.set_attribute("style", intern_style(style))?;
+    Ok(())
+}
+
+/// Duration of the labels shown by [`show_transient_label`] in milliseconds.
+const TRANSIENT_LABEL_DURATION_MS: i32 = 1500;
+
+/// A callback stored in a backend.
+///
+/// This only exists to allow deriving [`Debug`] for the backends.
+pub(crate) struct Callback<F: ?Sized>(pub(crate) Box<F>);
+
+impl<F: ?Sized> fmt::Debug for Callback<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+/// Shared ownership of the cells of a [`Line`].
+///
+/// With the `threads` feature, the lines are read from the worker threads, so
+/// the cells are shared atomically.
+#[cfg(feature = "threads")]
+type Shared<T> = std::sync::Arc<T>;
+
+/// Shared ownership of the cells of a [`Line`].
+#[cfg(not(feature = "threads"))]
+type Shared<T> = Rc<T>;
+
+/// Minimum number of cells of a grid for processing its rows in parallel.
+///
+/// Smaller grids are processed faster on the main thread than the work can be
+/// handed over to the thread pool.
+#[cfg(feature = "threads")]
+const MIN_PARALLEL_CELLS: usize = 16 * 1024;
+
+/// Maps the rows `0..height` of a grid with `width` columns.
+///
+/// With the `threads` feature, the rows of large grids are mapped in parallel
+/// on the thread pool, which is started with `ratzilla::init_thread_pool`.
+/// The palette is thread-local, so it is copied to the workers.
+#[cfg(feature = "threads")]
+pub(crate) fn map_rows<R: Send>(
+    height: usize,
+    width: usize,
+    f: impl Fn(usize) -> R + Send + Sync,
+) -> Vec<R> {
+    if height.saturating_mul(width) < MIN_PARALLEL_CELLS || rayon::current_num_threads() < 2 {
+        return (0..height).map(f).collect();
+    }
+    let (colors, css_variables) = (palette::palette(), palette::css_variables());
+    (0..height)
+        .into_par_iter()
+        .map_init(
+            || {
+                palette::set_palette(colors);
+                palette::set_css_variables(css_variables);
+            },
+            |_, y| f(y),
+        )
+        .collect()
+}
+
+/// Maps the rows `0..height` of a grid with `width` columns.
+#[cfg(not(feature = "threads"))]
+pub(crate) fn map_rows<R>(height: usize, _width: usize, f: impl Fn(usize) -> R) -> Vec<R> {
+    (0..height).map(f).collect()
+}
+
+/// Callback for the damaged areas of a flush.
+pub(crate) type DamageCallback = Callback<dyn FnMut(&[Rect])>;
+
+/// A line of a buffer.
+///
+/// The cells are shared between the current and the previous buffer until
+/// they are written to (copy-on-write), so keeping the previous buffer around
+/// does not require cloning the cells every frame.
+///
+/// A hash of the cells is updated on every write, which detects lines that
+/// were written to but ended up with the same content in O(1).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Line {
+    /// Cells of the line.
+    cells: Shared<Vec<Cell>>,
+    /// Combined hash of the cells.
+    hash: u64,
+}
+
+impl Line {
+    /// Constructs a new [`Line`] from the given cells.
+    pub(crate) fn new(cells: Vec<Cell>) -> Self {
+        let hash = cells
+            .iter()
+            .enumerate()
+            .fold(0, |hash, (x, cell)| hash ^ cell_hash(x, cell));
+        Self {
+            cells: Shared::new(cells),
+            hash,
+        }
+    }
+
+    /// Sets the cell at the given column, growing the line if needed.
+    pub(crate) fn set(&mut self, x: usize, cell: &Cell) {
+        if self.cells.get(x) == Some(cell) {
+            return;
+        }
+        // Only clones the cells if they are still shared with the previous
+        // buffer.
+        let cells = Shared::make_mut(&mut self.cells);
+        for i in cells.len()..=x {
+            self.hash ^= cell_hash(i, &Cell::default());
+        }
+        if cells.len() <= x {
+            cells.resize(x + 1, Cell::default());
+        }
+        self.hash ^= cell_hash(x, &cells[x]) ^ cell_hash(x, cell);
+        cells[x] = cell.clone();
+    }
+
+    /// Returns `true` if the line differs from the given previous line.
+    ///
+    /// This never compares the cells.
+    pub(crate) fn is_changed_from(&self, prev: &Line) -> bool {
+        !Shared::ptr_eq(&self.cells, &prev.cells) && self.hash != prev.hash
+    }
+}
+
+impl Deref for Line {
+    type Target = [Cell];
+
+    fn deref(&self) -> &Self::Target {
+        &self.cells
+    }
+}
+
+/// Returns the hash of a cell at the given column.
+///
+/// The hashes of the cells of a line are combined with XOR, so a single cell
+/// can be replaced without rehashing the whole line.
+fn cell_hash(x: usize, cell: &Cell) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (x, cell).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns whether each line of the buffer differs from the previous buffer.
+///
+/// This compares the hashes of all the lines at once, which is vectorized
+/// with the `simd` feature, see [`differing_hashes`].
+pub(crate) fn changed_lines(prev: &[Line], current: &[Line]) -> Vec<bool> {
+    let mut changed = Vec::with_capacity(current.len());
+    differing_hashes(prev, current, &mut changed);
+    // Lines that are missing from the previous buffer are new.
+    changed.resize(current.len(), true);
+    changed
+}
+
+/// Same as [`changed_lines`], allocated in the given frame arena.
+pub(crate) fn changed_lines_in<'a>(
+    arena: &'a Bump,
+    prev: &[Line],
+    current: &[Line],
+) -> BumpVec<'a, bool> {
+    let mut changed = BumpVec::with_capacity_in(current.len(), arena);
+    differing_hashes(prev, current, &mut changed);
+    changed.resize(current.len(), true);
+    changed
+}
+
+/// Pushes whether the hashes of each pair of lines differ.
+///
+/// This uses the 128-bit SIMD instructions of WebAssembly, which compare two
+/// hashes at a time. WebAssembly has no runtime feature detection inside a
+/// module: a module with SIMD instructions fails to load in engines without
+/// SIMD. The instructions are therefore only used when the `simd` feature is
+/// enabled and the crate is built with `-C target-feature=+simd128`, and the
+/// page picks between a SIMD build and a scalar build at load time, see the
+/// "SIMD" section of the README. Otherwise, the hashes are compared one by
+/// one.
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+fn differing_hashes(prev: &[Line], current: &[Line], changed: &mut impl Extend<bool>) {
+    differing_hashes_by_two(prev, current, changed, equal_lanes);
+}
+
+/// Returns whether each lane of two pairs of hashes is equal, with a single
+/// SIMD comparison.
+#[cfg(all(
+    any(test, feature = "simd"),
+    target_arch = "wasm32",
+    target_feature = "simd128"
+))]
+fn equal_lanes(prev: [u64; 2], current: [u64; 2]) -> [bool; 2] {
+    use std::arch::wasm32::{u64x2, u64x2_eq, u64x2_extract_lane};
+
+    let equal = u64x2_eq(u64x2(prev[0], prev[1]), u64x2(current[0], current[1]));
+    [
+        u64x2_extract_lane::<0>(equal) != 0,
+        u64x2_extract_lane::<1>(equal) != 0,
+    ]
+}
+
+/// Pushes whether the hashes of each pair of lines differ, comparing two
+/// pairs at a time with `equal_lanes`.
+#[cfg(any(
+    test,
+    all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")
+))]
+fn differing_hashes_by_two<F>(
+    prev: &[Line],
+    current: &[Line],
+    changed: &mut impl Extend<bool>,
+    equal_lanes: F,
+) where
+    F: Fn([u64; 2], [u64; 2]) -> [bool; 2],
+{
+    let pairs = prev.chunks_exact(2).zip(current.chunks_exact(2));
+    for (prev, current) in pairs {
+        let equal = equal_lanes(
+            [prev[0].hash, prev[1].hash],
+            [current[0].hash, current[1].hash],
+        );
+        changed.extend(equal.map(|equal| !equal));
+    }
+    let rest = prev.len().min(current.len()) / 2 * 2;
+    differing_hashes_scalar(&prev[rest..], &current[rest..], changed);
+}
+
+/// Pushes whether the hashes of each pair of lines differ.
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+fn differing_hashes(prev: &[Line], current: &[Line], changed: &mut impl Extend<bool>) {
+    differing_hashes_scalar(prev, current, changed);
+}
+
+/// Pushes whether the hashes of each pair of lines differ, one pair at a
+/// time.
+fn differing_hashes_scalar(prev: &[Line], current: &[Line], changed: &mut impl Extend<bool>) {
+    changed.extend(
+        prev.iter()
+            .zip(current)
+            .map(|(prev, current)| prev.hash != current.hash),
+    );
+}
 
-use crate::error::Error;
+/// Returns `true` if the line at `y` differs from the previous buffer.
+pub(crate) fn is_line_changed(prev: &[Line], current: &[Line], y: usize) -> bool {
+    match (prev.get(y), current.get(y)) {
+        (Some(prev), Some(current)) => current.is_changed_from(prev),
+        (None, None) => false,
+        _ => true,
+    }
+}
+
+/// Returns `true` if any line of the buffer differs from the previous buffer.
+pub(crate) fn has_changed_lines(prev: &[Line], current: &[Line]) -> bool {
+    prev.len() != current.len()
+        || prev
+            .iter()
+            .zip(current)
+            .any(|(prev, current)| current.is_changed_from(prev))
+}
 
-/// Creates a new `<span>` element with the given cell.
-pub(crate) fn create_span(document: &Document, cell: &Cell) -> Result<Element, Error> {
-    let span = document.create_element("span")?;
+/// Calculates the bounding rectangles of the cells that differ between the
+/// two buffers.
+///
+/// The rows are compared in parallel with the `threads` feature, see
+/// [`map_rows`].
+pub(crate) fn get_damage_rects(prev: &[Line], current: &[Line]) -> Vec<Rect> {
+    let changed = changed_lines(prev, current);
+    let width = current.first().map_or(0, |line| line.len());
+    let rows = map_rows(current.len(), width, |y| {
+        let mut runs = Vec::new();
+        if !changed[y] {
+            return runs;
+        }
+        let prev_line = prev.get(y);
+        let mut run: Option<Rect> = None;
+        for (x, cell) in current[y].iter().enumerate() {
+            if prev_line.and_then(|l| l.get(x)) == Some(cell) {
+                runs.extend(run.take());
+                continue;
+            }
+            match run.as_mut() {
+                Some(rect) => rect.width += 1,
+                None => run = Some(Rect::new(x as u16, y as u16, 1, 1)),
+            }
+        }
+        runs.extend(run);
+        runs
+    });
+    merge_rows(rows.into_iter().flatten().collect())
+}
+
+/// Sets the contents of the `<style>` element with the given id.
+///
+/// The element is created in the document head if it does not exist yet and
+/// removed if `css` is `None`.
+pub(crate) fn set_stylesheet(
+    document: &Document,
+    id: &str,
+    css: Option<&str>,
+) -> Result<(), Error> {
+    let existing = document.get_element_by_id(id);
+    let Some(css) = css else {
+        if let Some(style) = existing {
+            style.remove();
+        }
+        return Ok(());
+    };
+    let style = match existing {
+        Some(style) => style,
+        None => {
+            let style = document.create_element("style")?;
+            style.set_attribute("id", id)?;
+            match document.head() {
+                Some(head) => head.append_child(&style)?,
+                None => document
+                    .body()
+                    .ok_or(Error::UnableToRetrieveBody)?
+                    .append_child(&style)?,
+            };
+            style
+        }
+    };
+    style.set_text_content(Some(css));
+    Ok(())
+}
+
+/// Creates an observer that suspends the render loop while the observed
+/// elements are scrolled out of view.
+pub(crate) fn create_visibility_observer() -> Result<IntersectionObserver, Error> {
+    let closure = Closure::<dyn FnMut(_)>::new(move |entries: Array| {
+        let visible = entries.iter().any(|entry| {
+            entry
+                .unchecked_into::<IntersectionObserverEntry>()
+                .is_intersecting()
+        });
+        render::set_visible(visible);
+    });
+    let observer = IntersectionObserver::new(closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    Ok(observer)
+}
+
+/// Creates a new cell element with the given tag and cell.
+pub(crate) fn create_span(document: &Document, tag: &str, cell: &Cell) -> Result<Element, Error> {
+    let span = document.create_element(tag)?;
     span.set_inner_html(cell.symbol());
 
     let style = get_cell_style_as_css(cell);
-    span.set_attribute("style", &style)?;
+    set_style_attribute(&span, &style)?;
     Ok(span)
 }
 
-/// Creates a new `<a>` element with the given cells.
-pub(crate) fn create_anchor(document: &Document, cells: &[Cell]) -> Result<Element, Error> {
-    let anchor = document.create_element("a")?;
-    anchor.set_attribute(
-        "href",
-        &cells.iter().map(|c| c.symbol()).collect::<String>(),
+/// Creates a new cell element that stands in for `len` blank cells.
+///
+/// The style is taken from the given cell.
+pub(crate) fn create_blank_span(
+    document: &Document,
+    tag: &str,
+    cell: &Cell,
+    len: usize,
+) -> Result<Element, Error> {
+    let span = document.create_element(tag)?;
+    span.set_inner_html(&" ".repeat(len));
+    set_style_attribute(&span, &get_cell_style_as_css(cell))?;
+    span.set_attribute(CELLS_ATTRIBUTE, &len.to_string())?;
+    Ok(span)
+}
+
+/// Creates a new cell element for a run of cells with the same style.
+///
+/// The style is taken from the first cell, see [`is_same_style`].
+pub(crate) fn create_run_span(
+    document: &Document,
+    tag: &str,
+    cells: &[Cell],
+) -> Result<Element, Error> {
+    let span = document.create_element(tag)?;
+    let text = cells.iter().map(|cell| cell.symbol()).collect::<String>();
+    span.set_text_content(Some(&text));
+    if let Some(first) = cells.first() {
+        set_style_attribute(&span, &get_cell_style_as_css(first))?;
+    }
+    if cells.len() > 1 {
+        span.set_attribute(CELLS_ATTRIBUTE, &cells.len().to_string())?;
+    }
+    Ok(span)
+}
+
+/// Returns `true` if two cells are drawn with the same style and can be
+/// merged.
+pub(crate) fn is_same_style(a: &Cell, b: &Cell) -> bool {
+    a.fg == b.fg && a.bg == b.bg && a.modifier == b.modifier
+}
+
+/// Attribute that holds the number of cells of a collapsed `<span>`.
+const CELLS_ATTRIBUTE: &str = "data-cells";
+
+/// Attribute of the grid element that holds the tag of the line elements.
+pub(crate) const ROW_TAG_ATTRIBUTE: &str = "data-ratzilla-row";
+
+/// Attribute of the grid element that holds the tag of the cell elements.
+pub(crate) const CELL_TAG_ATTRIBUTE: &str = "data-ratzilla-cell";
+
+/// Returns the position of the cell that the given element belongs to.
+///
+/// The position is calculated from the structure of the grid: the row is the
+/// index of the line element and the column is the number of cells that come
+/// before the cell element on its line. Collapsed elements resolve to their
+/// first cell. The tags of the elements are read from the grid, see
+/// [`DomLayout`].
+pub(crate) fn get_cell_position(element: &Element) -> Option<Position> {
+    let grid = element.closest(&format!(".{GRID_CLASS}")).ok()??;
+    let row_tag = grid
+        .get_attribute(ROW_TAG_ATTRIBUTE)
+        .unwrap_or_else(|| DomLayout::DEFAULT_ROW_TAG.to_string());
+    let cell_tag = grid
+        .get_attribute(CELL_TAG_ATTRIBUTE)
+        .unwrap_or_else(|| DomLayout::DEFAULT_CELL_TAG.to_string());
+    let span = element.closest(&cell_tag).ok()??;
+    let line = span.closest(&format!(".{GRID_CLASS} > {row_tag}")).ok()??;
+
+    let mut y = 0;
+    let mut sibling = line.previous_element_sibling();
+    while let Some(prev) = sibling {
+        y += 1;
+        sibling = prev.previous_element_sibling();
+    }
+
+    let spans = line.query_selector_all(&cell_tag).ok()?;
+    let mut x = 0;
+    for i in 0..spans.length() {
+        let current = spans.item(i)?.dyn_into::<Element>().ok()?;
+        if current == span {
+            return Some(Position::new(x, y));
+        }
+        x += current
+            .get_attribute(CELLS_ATTRIBUTE)
+            .and_then(|len| len.parse().ok())
+            .unwrap_or(1);
+    }
+    None
+}
+
+/// Configuration read from the data attributes of a container, see
+/// [`DomBackendBuilder`].
+///
+/// [`DomBackendBuilder`]: crate::backend::dom::DomBackendBuilder
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ContainerConfig {
+    /// Value of `data-theme`.
+    pub(crate) theme: Option<String>,
+    /// Value of `data-font-size` in pixels.
+    pub(crate) font_size: Option<f64>,
+    /// Value of `data-fixed-size`.
+    pub(crate) fixed_size: Option<Size>,
+}
+
+/// Reads the configuration from the data attributes of a container.
+///
+/// Invalid values are ignored with a warning.
+pub(crate) fn read_container_config(container: &Element) -> ContainerConfig {
+    ContainerConfig {
+        theme: container
+            .get_attribute("data-theme")
+            .filter(|theme| !theme.is_empty()),
+        font_size: parse_attribute(container, "data-font-size", parse_font_size),
+        fixed_size: parse_attribute(container, "data-fixed-size", parse_size),
+    }
+}
+
+/// Parses an attribute of an element, warning about invalid values.
+fn parse_attribute<T>(element: &Element, name: &str, parse: fn(&str) -> Option<T>) -> Option<T> {
+    let value = element.get_attribute(name)?;
+    let parsed = parse(&value);
+    if parsed.is_none() {
+        crate::web_warn!("ignoring invalid {name}: {value:?}");
+    }
+    parsed
+}
+
+/// Parses a font size in pixels, e.g. `14` or `14px`.
+fn parse_font_size(value: &str) -> Option<f64> {
+    let value = value.trim();
+    value
+        .strip_suffix("px")
+        .unwrap_or(value)
+        .trim()
+        .parse()
+        .ok()
+        .filter(|size: &f64| size.is_finite() && *size > 0.0)
+}
+
+/// Parses a number of columns and rows, e.g. `80x24`.
+fn parse_size(value: &str) -> Option<Size> {
+    let (width, height) = value.trim().split_once(['x', 'X'])?;
+    let width = width.trim().parse().ok().filter(|width| *width > 0)?;
+    let height = height.trim().parse().ok().filter(|height| *height > 0)?;
+    Some(Size::new(width, height))
+}
+
+/// Returns `true` if the cell renders as an empty space.
+pub(crate) fn is_blank(cell: &Cell) -> bool {
+    cell.symbol() == " "
+}
+
+/// Returns `true` if two blank cells look the same and can be merged.
+///
+/// The foreground color only matters when a modifier (e.g. underline) draws
+/// something on the blank cells.
+pub(crate) fn is_same_blank(a: &Cell, b: &Cell) -> bool {
+    a.bg == b.bg && a.modifier == b.modifier && (a.modifier.is_empty() || a.fg == b.fg)
+}
+
+/// Shows a short-lived label above the given element.
+///
+/// This is used for giving feedback about actions such as copying text.
+pub(crate) fn show_transient_label(element: &Element, text: &str) -> Result<(), Error> {
+    let window = web_sys::window().ok_or(Error::UnableToRetrieveWindow)?;
+    let document = window.document().ok_or(Error::UnableToRetrieveDocument)?;
+    let rect = element.get_bounding_client_rect();
+    let label = document.create_element("div")?;
+    label.set_text_content(Some(text));
+    label.set_attribute("role", "status")?;
+    label.set_attribute(
+        "style",
+        &format!(
+            "position: fixed; left: {}px; top: {}px; transform: translateY(-100%); \
+            padding: 0 0.5em; background-color: rgb(255, 255, 255); color: rgb(0, 0, 0); \
+            font-family: monospace; pointer-events: none; z-index: 2147483647;",
+            rect.left(),
+            rect.top()
+        ),
     )?;
-    anchor.set_attribute("style", &get_cell_style_as_css(&cells[0]))?;
+    document
+        .body()
+        .ok_or(Error::UnableToRetrieveBody)?
+        .append_child(&label)?;
+    let remove = Closure::once_into_js(move || label.remove());
+    window.set_timeout_with_callback_and_timeout_and_arguments_0(
+        remove.unchecked_ref(),
+        TRANSIENT_LABEL_DURATION_MS,
+    )?;
+    Ok(())
+}
+
+/// Creates a new `<a>` element for the given hyperlink.
+///
+/// The style is taken from the first cell of the hyperlink.
+pub(crate) fn create_anchor(
+    document: &Document,
+    link: &Link,
+    first: &Cell,
+) -> Result<Element, Error> {
+    let anchor = document.create_element("a")?;
+    anchor.set_attribute("href", &link.url)?;
+    if let Some(target) = &link.target {
+        anchor.set_attribute("target", target)?;
+        // Do not give the opened page access to this one.
+        anchor.set_attribute("rel", "noopener noreferrer")?;
+    }
+    set_style_attribute(&anchor, &get_cell_style_as_css(first))?;
     Ok(anchor)
 }
 
-/// Converts a cell to a CSS style.
-pub(crate) fn get_cell_style_as_css(cell: &Cell) -> String {
-    let fg = ansi_to_rgb(cell.fg);
-    let bg = ansi_to_rgb(cell.bg);
+/// CSS properties that are set on the cell elements.
+const CELL_STYLE_PROPERTIES: [&str; 8] = [
+    "color",
+    "background-color",
+    "font-weight",
+    "opacity",
+    "font-style",
+    "text-decoration",
+    "visibility",
+    "animation",
+];
 
-    let fg_style = match fg {
-        Some(color) => format!("color: rgb({}, {}, {});", color.0, color.1, color.2),
-        None => "color: rgb(255, 255, 255);".to_string(),
-    };
+/// Values of the [`CELL_STYLE_PROPERTIES`] of a cell, `None` if unset.
+pub(crate) type CellStyle = [Option<String>; CELL_STYLE_PROPERTIES.len()];
 
-    let bg_style = match bg {
-        Some(color) => format!(
-            "background-color: rgb({}, {}, {});",
-            color.0, color.1, color.2
-        ),
-        None => "background-color: transparent;".to_string(),
-    };
+/// Stylesheet of the blink animation of the cells.
+pub(crate) const BLINK_CSS: &str = "@keyframes ratzilla-blink { 50% { visibility: hidden; } }";
+
+/// Id of the stylesheet of the blink animation.
+pub(crate) const BLINK_STYLESHEET_ID: &str = "ratzilla-blink";
 
-    let mut modifier_style = String::new();
-    if cell.modifier.contains(Modifier::BOLD) {
-        modifier_style.push_str("font-weight: bold; ");
+/// Stylesheet that preserves the spaces of the grid.
+///
+/// `<pre>` elements preserve them by default, but the stylesheets of the page
+/// (e.g. a CSS reset) may not, which collapses the trailing and painted
+/// spaces of status bars and selection highlights. The other tags of a
+/// [`DomLayout`] do not preserve them at all.
+pub(crate) const WHITE_SPACE_CSS: &str = ".ratzilla-grid, .ratzilla-grid * { white-space: pre; }";
+
+/// Id of the stylesheet that preserves the spaces of the grid.
+pub(crate) const WHITE_SPACE_STYLESHEET_ID: &str = "ratzilla-white-space";
+
+/// Stylesheet that positions the lines of the decorated cells.
+///
+/// The lines are drawn with pseudo-elements, so they do not interfere with
+/// the `text-decoration` of the cells.
+const DECORATION_CSS: &str = "[data-ratzilla-overline], [data-ratzilla-strikethrough] { \
+    position: relative; } \
+    [data-ratzilla-overline]::before, [data-ratzilla-strikethrough]::after { \
+    content: \"\"; position: absolute; left: 0; right: 0; height: 1px; \
+    pointer-events: none; } \
+    [data-ratzilla-overline]::before { top: 0; } \
+    [data-ratzilla-strikethrough]::after { top: 50%; }";
+
+/// Returns the stylesheet of the decorations with the given CSS colors.
+pub(crate) fn get_decoration_css<'a>(
+    overlines: impl IntoIterator<Item = &'a str>,
+    strikethroughs: impl IntoIterator<Item = &'a str>,
+) -> String {
+    let mut css = DECORATION_CSS.to_string();
+    for color in overlines {
+        css.push_str(&format!(
+            " [data-ratzilla-overline=\"{color}\"]::before {{ background: {color}; }}"
+        ));
     }
-    if cell.modifier.contains(Modifier::DIM) {
-        modifier_style.push_str("opacity: 0.5; ");
+    for color in strikethroughs {
+        css.push_str(&format!(
+            " [data-ratzilla-strikethrough=\"{color}\"]::after {{ background: {color}; }}"
+        ));
     }
-    if cell.modifier.contains(Modifier::ITALIC) {
-        modifier_style.push_str("font-style: italic; ");
+    css
+}
+
+/// Attribute that marks the cell element with the cursor, its value is the
+/// shape of the cursor.
+pub(crate) const CURSOR_ATTRIBUTE: &str = "data-ratzilla-cursor";
+
+/// Stylesheet of the cursor.
+pub(crate) const CURSOR_CSS: &str = "[data-ratzilla-cursor=block] { \
+    color: rgb(0, 0, 0) !important; background-color: rgb(255, 255, 255) !important; \
+    animation: ratzilla-cursor-block 1s step-end infinite; } \
+    [data-ratzilla-cursor=bar] { box-shadow: inset 2px 0 0 currentColor; \
+    animation: ratzilla-cursor-line 1s step-end infinite; } \
+    [data-ratzilla-cursor=underline] { box-shadow: inset 0 -2px 0 currentColor; \
+    animation: ratzilla-cursor-line 1s step-end infinite; } \
+    @keyframes ratzilla-cursor-block { 50% { filter: invert(1); } } \
+    @keyframes ratzilla-cursor-line { 50% { box-shadow: none; } }";
+
+/// Id of the stylesheet of the cursor.
+pub(crate) const CURSOR_STYLESHEET_ID: &str = "ratzilla-cursor";
+
+/// Attribute of a grid without the keyboard focus, its value is the label of
+/// the focus overlay.
+pub(crate) const FOCUS_ATTRIBUTE: &str = "data-ratzilla-focus";
+
+/// Stylesheet of the focus overlay.
+///
+/// The overlay is a pseudo-element of the grid, so it is not affected by the
+/// updates of the lines and clicks on it target the grid. It is the `::before`
+/// pseudo-element, since the scanlines of the [`CrtEffect`] are drawn with
+/// `::after`.
+///
+/// [`CrtEffect`]: crate::effects::CrtEffect
+pub(crate) const FOCUS_CSS: &str = ".ratzilla-grid[data-ratzilla-focus] { \
+    position: relative; cursor: pointer; } \
+    .ratzilla-grid[data-ratzilla-focus]::before { content: attr(data-ratzilla-focus); \
+    position: absolute; inset: 0; z-index: 1; display: flex; align-items: center; \
+    justify-content: center; background-color: rgba(0, 0, 0, 0.6); \
+    color: rgb(255, 255, 255); font-family: monospace; }";
+
+/// Id of the stylesheet of the focus overlay.
+pub(crate) const FOCUS_STYLESHEET_ID: &str = "ratzilla-focus";
+
+/// Returns the value of the `dir` attribute of a grid.
+pub(crate) fn get_direction(mirrored: bool) -> &'static str {
+    if mirrored {
+        "rtl"
+    } else {
+        "ltr"
     }
-    if cell.modifier.contains(Modifier::UNDERLINED) {
-        modifier_style.push_str("text-decoration: underline; ");
+}
+
+/// Returns the stylesheet that lays out the columns of the grid with the
+/// given id.
+///
+/// The bidirectional algorithm is overridden on the lines, so the cells are
+/// laid out in the order of the columns even on right-to-left pages or with
+/// right-to-left text in the cells: from the left, or from the right if the
+/// grid is mirrored.
+pub(crate) fn get_direction_css(id: &str, mirrored: bool) -> String {
+    let direction = get_direction(mirrored);
+    format!(
+        "#{id} {{ direction: {direction}; text-align: start; }} \
+        #{id} > * {{ unicode-bidi: bidi-override; }}"
+    )
+}
+
+/// Returns the style of a cell.
+pub(crate) fn get_cell_style(cell: &Cell) -> CellStyle {
+    let (fg, bg) = if cell.modifier.contains(Modifier::REVERSED) {
+        // The default background is assumed to be dark, like the default
+        // foreground is light.
+        (
+            ansi_to_css(cell.bg).unwrap_or_else(|| "rgb(0, 0, 0)".to_string()),
+            ansi_to_css(cell.fg).unwrap_or_else(|| "rgb(255, 255, 255)".to_string()),
+        )
+    } else {
+        (
+            ansi_to_css(cell.fg).unwrap_or_else(|| "rgb(255, 255, 255)".to_string()),
+            ansi_to_css(cell.bg).unwrap_or_else(|| "transparent".to_string()),
+        )
+    };
+    let flag = |modifier: Modifier, value: &str| {
+        cell.modifier.contains(modifier).then(|| value.to_string())
+    };
+    let decoration = [
+        flag(Modifier::UNDERLINED, "underline"),
+        flag(Modifier::CROSSED_OUT, "line-through"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    [
+        Some(fg),
+        Some(bg),
+        flag(Modifier::BOLD, "bold"),
+        flag(Modifier::DIM, "0.5"),
+        flag(Modifier::ITALIC, "italic"),
+        (!decoration.is_empty()).then(|| decoration.join(" ")),
+        flag(Modifier::HIDDEN, "hidden"),
+        flag(
+            Modifier::RAPID_BLINK,
+            "ratzilla-blink 0.5s step-end infinite",
+        )
+        .or(flag(
+            Modifier::SLOW_BLINK,
+            "ratzilla-blink 1s step-end infinite",
+        )),
+    ]
+}
+
+/// Converts a cell style to a CSS declaration block.
+pub(crate) fn get_style_as_css(style: &CellStyle) -> String {
+    let mut css = String::new();
+    write_style_as_css(style, &mut css);
+    css
+}
+
+/// Writes a cell style as a CSS declaration block, e.g. to a string of the
+/// frame arena.
+pub(crate) fn write_style_as_css(style: &CellStyle, css: &mut impl Write) {
+    for (name, value) in CELL_STYLE_PROPERTIES.iter().zip(style) {
+        if let Some(value) = value {
+            // Writing to a string never fails.
+            let _ = write!(css, "{name}: {value}; ");
+        }
     }
-    if cell.modifier.contains(Modifier::HIDDEN) {
-        modifier_style.push_str("visibility: hidden; ");
+}
+
+/// Converts a cell to a CSS style.
+pub(crate) fn get_cell_style_as_css(cell: &Cell) -> String {
+    get_style_as_css(&get_cell_style(cell))
+}
+
+/// Updates the style of an element from `prev` to `style`.
+///
+/// Only the properties that differ are written, which is cheaper than
+/// formatting and writing the whole `style` attribute.
+pub(crate) fn update_cell_style(
+    element: &HtmlElement,
+    prev: &CellStyle,
+    style: &CellStyle,
+) -> Result<(), Error> {
+    let declaration = element.style();
+    for ((name, prev), value) in CELL_STYLE_PROPERTIES.iter().zip(prev).zip(style) {
+        if prev == value {
+            continue;
+        }
+        match value {
+            Some(value) => declaration.set_property(name, intern_style(value))?,
+            None => {
+                declaration.remove_property(name)?;
+            }
+        }
     }
-    if cell.modifier.contains(Modifier::CROSSED_OUT) {
-        modifier_style.push_str("text-decoration: line-through; ");
+    Ok(())
+}
+
+/// Converts a background color to a CSS style.
+pub(crate) fn get_background_as_css(color: Color) -> String {
+    match ansi_to_css(color) {
+        Some(color) => format!("background-color: {color};"),
+        None => "background-color: transparent;".to_string(),
     }
+}
 
-    format!("{fg_style} {bg_style} {modifier_style}")
+/// Returns the background color shared by all cells of the line.
+///
+/// Returns `None` if the line is empty, the cells have different backgrounds,
+/// the background is not set or a cell is reversed.
+pub(crate) fn get_uniform_background(line: &[Cell]) -> Option<Color> {
+    let background = line.first()?.bg;
+    // Reversed cells show their background as the foreground.
+    (background != Color::Reset
+        && line
+            .iter()
+            .all(|cell| cell.bg == background && !cell.modifier.contains(Modifier::REVERSED)))
+    .then_some(background)
+}
+
+/// Returns the cell with its background removed if it is already painted by
+/// the line.
+pub(crate) fn without_background(cell: &Cell, line_background: Option<Color>) -> Cow<'_, Cell> {
+    if line_background.is_some_and(|bg| bg == cell.bg) {
+        let mut cell = cell.clone();
+        cell.bg = Color::Reset;
+        Cow::Owned(cell)
+    } else {
+        Cow::Borrowed(cell)
+    }
 }
 
 /// Converts a cell to a CSS style.
@@ -89,35 +948,130 @@ pub(crate) fn get_cell_color_for_canvas(cell: &Cell, background_color: Color) ->
     (fg_style, bg_style)
 }
 
-/// Converts an ANSI color to an RGB tuple.
-fn ansi_to_rgb(color: Color) -> Option<(u8, u8, u8)> {
-    match color {
-        Color::Black => Some((0, 0, 0)),
-        Color::Red => Some((128, 0, 0)),
-        Color::Green => Some((0, 128, 0)),
-        Color::Yellow => Some((128, 128, 0)),
-        Color::Blue => Some((0, 0, 128)),
-        Color::Magenta => Some((128, 0, 128)),
-        Color::Cyan => Some((0, 128, 128)),
-        Color::Gray => Some((192, 192, 192)),
-        Color::DarkGray => Some((128, 128, 128)),
-        Color::LightRed => Some((255, 0, 0)),
-        Color::LightGreen => Some((0, 255, 0)),
-        Color::LightYellow => Some((255, 255, 0)),
-        Color::LightBlue => Some((0, 0, 255)),
-        Color::LightMagenta => Some((255, 0, 255)),
-        Color::LightCyan => Some((0, 255, 255)),
-        Color::White => Some((255, 255, 255)),
-        Color::Rgb(r, g, b) => Some((r, g, b)),
-        _ => None,
+/// Blends two colors.
+///
+/// Colors that cannot be converted to RGB (e.g. [`Color::Reset`]) are
+/// switched halfway through.
+pub(crate) fn blend_colors(from: Color, to: Color, progress: f64) -> Color {
+    match (ansi_to_rgb(from), ansi_to_rgb(to)) {
+        (Some(from), Some(to)) => {
+            let mix = |a: u8, b: u8| {
+                (f64::from(a) + (f64::from(b) - f64::from(a)) * progress).round() as u8
+            };
+            Color::Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+        }
+        _ if progress < 0.5 => from,
+        _ => to,
+    }
+}
+
+/// Converts an ANSI color to an RGB tuple using the global palette.
+pub(crate) fn ansi_to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    palette::palette().to_rgb(color)
+}
+
+/// Converts an ANSI color to a CSS color using the global palette.
+pub(crate) fn ansi_to_css(color: Color) -> Option<String> {
+    palette::palette().to_css_color(color)
+}
+
+/// Default delay of the resize handling in milliseconds.
+pub(crate) const DEFAULT_RESIZE_DEBOUNCE: f64 = 100.0;
+
+/// Adds a debounced listener to the window resize event.
+///
+/// `on_resize` is called once no resize event happened for `delay`
+/// milliseconds, so it always sees the settled size, or on every event if
+/// the delay is zero. In the meantime, the `element` is scaled with a CSS
+/// transform in proportion to the size returned by `target_size`, as a cheap
+/// placeholder of the content at the new size.
+pub(crate) fn add_debounced_resize_listener<E, S, F>(
+    window: &Window,
+    delay: Rc<StdCell<f64>>,
+    element: E,
+    target_size: S,
+    on_resize: F,
+) -> Result<(), Error>
+where
+    E: Fn() -> Option<HtmlElement> + 'static,
+    S: Fn() -> (f64, f64) + 'static,
+    F: FnMut() + 'static,
+{
+    let element = Rc::new(element);
+    let target_size = Rc::new(target_size);
+    let on_resize = Rc::new(RefCell::new(on_resize));
+    // Size that the element was rendered for.
+    let rendered_size = Rc::new(StdCell::new(target_size()));
+    let timeout = Rc::new(StdCell::new(None));
+    let settle = Closure::<dyn FnMut()>::new({
+        let element = element.clone();
+        let target_size = target_size.clone();
+        let on_resize = on_resize.clone();
+        let rendered_size = rendered_size.clone();
+        let timeout = timeout.clone();
+        move || {
+            timeout.set(None);
+            if let Some(element) = element() {
+                let style = element.style();
+                let _ = style.remove_property("transform");
+                let _ = style.remove_property("transform-origin");
+            }
+            rendered_size.set(target_size());
+            on_resize.borrow_mut()();
+        }
+    });
+    let closure = Closure::<dyn FnMut()>::new({
+        let window = window.clone();
+        move || {
+            let delay = delay.get();
+            if delay <= 0.0 {
+                rendered_size.set(target_size());
+                on_resize.borrow_mut()();
+                return;
+            }
+            if let Some(element) = element() {
+                scale_placeholder(&element, rendered_size.get(), target_size());
+            }
+            if let Some(handle) = timeout.take() {
+                window.clear_timeout_with_handle(handle);
+            }
+            if let Ok(handle) = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                settle.as_ref().unchecked_ref(),
+                delay.min(f64::from(i32::MAX)) as i32,
+            ) {
+                timeout.set(Some(handle));
+            }
+        }
+    });
+    window.add_event_listener_with_callback("resize", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    Ok(())
+}
+
+/// Scales an element rendered for the size `from` to the size `to` with a
+/// CSS transform.
+fn scale_placeholder(element: &HtmlElement, from: (f64, f64), to: (f64, f64)) {
+    if from.0 <= 0.0 || from.1 <= 0.0 {
+        return;
     }
+    let style = element.style();
+    let _ = style.set_property("transform-origin", "0 0");
+    let _ = style.set_property(
+        "transform",
+        &format!("scale({:.4}, {:.4})", to.0 / from.0, to.1 / from.1),
+    );
+}
+
+/// Returns the inner size of the window in CSS pixels.
+pub(crate) fn get_window_inner_size() -> (f64, f64) {
+    let (width, height) = get_raw_window_size();
+    (f64::from(width), f64::from(height))
 }
 
 /// Calculates the number of characters that can fit in the window.
-pub(crate) fn get_window_size() -> (u16, u16) {
+pub(crate) fn get_window_size(metrics: FontMetrics) -> (u16, u16) {
     let (w, h) = get_raw_window_size();
-    // These are mildly magical numbers... make them more precise
-    (w / 10, h / 20)
+    metrics.grid_size(w.into(), h.into())
 }
 
 /// Calculates the number of pixels that can fit in the window.
@@ -144,30 +1098,145 @@ fn is_mobile() -> bool {
 
 /// Calculates the number of pixels that can fit in the window.
 fn get_raw_screen_size() -> (i32, i32) {
-    let s = web_sys::window().unwrap().screen().unwrap();
-    (s.width().unwrap(), s.height().unwrap())
+    web_sys::window()
+        .and_then(|w| w.screen().ok())
+        .and_then(|s| s.width().ok().zip(s.height().ok()))
+        .unwrap_or((120, 120))
 }
 
-/// Calculates the number of characters that can fit in the window.
-fn get_screen_size() -> (u16, u16) {
+/// Calculates the number of characters that can fit in the screen.
+fn get_screen_size(metrics: FontMetrics) -> (u16, u16) {
     let (w, h) = get_raw_screen_size();
-    // These are mildly magical numbers... make them more precise
-    (w as u16 / 10, h as u16 / 19)
+    metrics.grid_size(w.into(), h.into())
 }
 
-/// Returns a buffer based on the screen size.
-pub(crate) fn get_sized_buffer() -> Vec<Vec<Cell>> {
-    let (width, height) = if is_mobile() {
-        get_screen_size()
+/// Converts lines of cells to a buffer.
+pub(crate) fn lines_to_buffer(lines: &[Line]) -> Buffer {
+    let width = lines.first().map_or(0, |line| line.len());
+    let mut buffer = Buffer::empty(Rect::new(0, 0, width as u16, lines.len() as u16));
+    for (y, line) in lines.iter().enumerate() {
+        for (x, cell) in line.iter().enumerate() {
+            buffer.content[y * width + x] = cell.clone();
+        }
+    }
+    buffer
+}
+
+/// Returns a buffer of blank cells with the given size.
+///
+/// The lines are shared until they are written to.
+pub(crate) fn blank_buffer(width: u16, height: u16) -> Vec<Line> {
+    vec![Line::new(vec![Cell::default(); width as usize]); height as usize]
+}
+
+/// Calculates the number of characters of the grid based on the screen size.
+pub(crate) fn get_grid_size(metrics: FontMetrics) -> (u16, u16) {
+    if is_mobile() {
+        get_screen_size(metrics)
     } else {
-        get_window_size()
-    };
-    vec![vec![Cell::default(); width as usize]; height as usize]
+        get_window_size(metrics)
+    }
+}
+
+/// Calculates the number of characters that fit in the given element.
+pub(crate) fn get_element_grid_size(element: &Element, metrics: FontMetrics) -> (u16, u16) {
+    metrics.grid_size(
+        element.client_width().into(),
+        element.client_height().into(),
+    )
 }
 
 /// Returns a buffer based on the canvas size.
-pub(crate) fn get_sized_buffer_from_canvas(canvas: &HtmlCanvasElement) -> Vec<Vec<Cell>> {
-    let width = canvas.client_width() as u16 / 10_u16;
-    let height = canvas.client_height() as u16 / 19_u16;
-    vec![vec![Cell::default(); width as usize]; height as usize]
+pub(crate) fn get_sized_buffer_from_canvas(
+    canvas: &HtmlCanvasElement,
+    metrics: FontMetrics,
+) -> Vec<Line> {
+    let (width, height) = get_element_grid_size(canvas, metrics);
+    blank_buffer(width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns lines with the given symbols.
+    fn lines(symbols: &str) -> Vec<Line> {
+        symbols
+            .chars()
+            .map(|symbol| {
+                let mut cell = Cell::default();
+                cell.set_char(symbol);
+                Line::new(vec![cell])
+            })
+            .collect()
+    }
+
+    /// Asserts that comparing the hashes two at a time agrees with the scalar
+    /// comparison.
+    fn assert_agrees_with_scalar(equal_lanes: fn([u64; 2], [u64; 2]) -> [bool; 2]) {
+        for (prev, current) in [
+            ("", ""),
+            ("abcd", "abcd"),
+            ("abcd", "xbcy"),
+            ("abcde", "abxdy"),
+            ("abc", "abcdef"),
+            ("abcdef", "xb"),
+        ] {
+            let (prev, current) = (lines(prev), lines(current));
+            let mut scalar = Vec::new();
+            differing_hashes_scalar(&prev, &current, &mut scalar);
+            let mut by_two = Vec::new();
+            differing_hashes_by_two(&prev, &current, &mut by_two, equal_lanes);
+            assert_eq!(by_two, scalar);
+        }
+    }
+
+    #[test]
+    fn differing_hashes_by_two_agrees_with_scalar() {
+        assert_agrees_with_scalar(|prev, current| [prev[0] == current[0], prev[1] == current[1]]);
+    }
+
+    /// Run with a WebAssembly test runner, e.g. `RUSTFLAGS="-C
+    /// target-feature=+simd128" cargo test --target wasm32-wasip1`.
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    #[test]
+    fn simd_agrees_with_scalar() {
+        assert_agrees_with_scalar(equal_lanes);
+    }
+
+    #[test]
+    fn style_cache_evicts_least_recently_used() {
+        let mut cache = StyleCache::default();
+        for index in 0..STYLE_CACHE_SIZE {
+            assert_eq!(cache.touch(&index.to_string()), (true, None));
+        }
+        assert_eq!(cache.touch("0"), (false, None));
+        let (is_new, evicted) = cache.touch("new");
+        assert!(is_new);
+        assert_eq!(evicted.as_deref(), Some("1"));
+        assert_eq!(cache.uses.len(), STYLE_CACHE_SIZE);
+        assert_eq!(cache.by_use.len(), STYLE_CACHE_SIZE);
+    }
+
+    #[test]
+    fn container_config() {
+        assert_eq!(parse_font_size("14"), Some(14.0));
+        assert_eq!(parse_font_size(" 12.5px "), Some(12.5));
+        assert_eq!(parse_font_size("-1"), None);
+        assert_eq!(parse_font_size("large"), None);
+        assert_eq!(parse_size("80x24"), Some(Size::new(80, 24)));
+        assert_eq!(parse_size("120 X 40"), Some(Size::new(120, 40)));
+        assert_eq!(parse_size("0x24"), None);
+        assert_eq!(parse_size("80"), None);
+    }
+
+    #[test]
+    fn direction() {
+        assert_eq!(get_direction(false), "ltr");
+        assert_eq!(get_direction(true), "rtl");
+        let css = get_direction_css("ratzilla-0", false);
+        assert!(css.contains("#ratzilla-0 { direction: ltr;"));
+        assert!(css.contains("#ratzilla-0 > * { unicode-bidi: bidi-override; }"));
+        assert!(get_direction_css("ratzilla-0", true).contains("direction: rtl;"));
+    }
 }
@@ -0,0 +1,399 @@
+//! ## Frame capture
+//!
+//! [`FrameCapture`] records the buffers of consecutive frames as they are
+//! rendered by [`WebRenderer::draw_web`] and computes structured diffs between
+//! them. This can be used for building visual regression checks.
+//!
+//! ```rust no_run
+//! use ratzilla::capture::FrameCapture;
+//!
+//! // Capture the next 5 frames.
+//! let capture = FrameCapture::new(5);
+//!
+//! // Later on, e.g. in a key event handler:
+//! if capture.is_complete() {
+//!     let json = capture.to_json();
+//!     // Send the JSON somewhere...
+//! }
+//! ```
+//!
+//! [`WebRenderer::draw_web`]: crate::WebRenderer::draw_web
+
+use std::{cell::RefCell, fmt::Write, rc::Rc};
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::{Position, Rect},
+    style::Style,
+};
+
+/// State of a capture.
+#[derive(Debug)]
+struct CaptureState {
+    /// Number of frames to capture.
+    count: usize,
+    /// Captured frames.
+    frames: Vec<Buffer>,
+}
+
+thread_local! {
+    /// Captures that are still recording.
+    static CAPTURES: RefCell<Vec<Rc<RefCell<CaptureState>>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records the given frame in all active captures.
+///
+/// Captures that are complete are removed from the active list.
+pub(crate) fn record(buffer: &Buffer) {
+    CAPTURES.with(|captures| {
+        captures.borrow_mut().retain(|capture| {
+            let mut capture = capture.borrow_mut();
+            capture.frames.push(buffer.clone());
+            capture.frames.len() < capture.count
+        })
+    });
+}
+
+/// Captures a number of consecutive frames.
+#[derive(Debug, Clone)]
+pub struct FrameCapture {
+    /// Shared capture state.
+    state: Rc<RefCell<CaptureState>>,
+}
+
+impl FrameCapture {
+    /// Starts capturing the next `count` frames.
+    pub fn new(count: usize) -> Self {
+        let state = Rc::new(RefCell::new(CaptureState {
+            count,
+            frames: Vec::with_capacity(count),
+        }));
+        if count > 0 {
+            CAPTURES.with(|captures| captures.borrow_mut().push(state.clone()));
+        }
+        Self { state }
+    }
+
+    /// Returns `true` if all frames have been captured.
+    pub fn is_complete(&self) -> bool {
+        let state = self.state.borrow();
+        state.frames.len() >= state.count
+    }
+
+    /// Returns the frames captured so far.
+    pub fn frames(&self) -> Vec<Buffer> {
+        self.state.borrow().frames.clone()
+    }
+
+    /// Returns the diffs between each pair of consecutive captured frames.
+    pub fn diffs(&self) -> Vec<FrameDiff> {
+        self.state
+            .borrow()
+            .frames
+            .windows(2)
+            .map(|pair| FrameDiff::new(&pair[0], &pair[1]))
+            .collect()
+    }
+
+    /// Exports the captured frames and their diffs as JSON.
+    pub fn to_json(&self) -> String {
+        let frames = self
+            .state
+            .borrow()
+            .frames
+            .iter()
+            .map(buffer_to_json)
+            .collect::<Vec<_>>()
+            .join(",");
+        let diffs = self
+            .diffs()
+            .iter()
+            .map(FrameDiff::to_json)
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(r#"{{"frames":[{frames}],"diffs":[{diffs}]}}"#)
+    }
+}
+
+/// A change of a single cell between two frames.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CellChange {
+    /// Position of the cell.
+    pub position: Position,
+    /// Symbol before and after the change, if it changed.
+    pub symbol: Option<(String, String)>,
+    /// Style before and after the change, if it changed.
+    pub style: Option<(Style, Style)>,
+}
+
+/// Structured diff between two frames.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FrameDiff {
+    /// Bounding rectangles of the changed regions.
+    pub changed_rects: Vec<Rect>,
+    /// Changes of the individual cells.
+    pub changes: Vec<CellChange>,
+}
+
+impl FrameDiff {
+    /// Computes the diff between two frames.
+    ///
+    /// Cells that are outside of either frame are compared against the
+    /// default cell.
+    pub fn new(before: &Buffer, after: &Buffer) -> Self {
+        let area = before.area.union(after.area);
+        let default = Cell::default();
+        let cell_at = |buffer: &Buffer, x, y| buffer.cell(Position::new(x, y)).cloned();
+        let mut changes = Vec::new();
+        let mut row_runs: Vec<Rect> = Vec::new();
+        for y in area.top()..area.bottom() {
+            let mut run: Option<Rect> = None;
+            for x in area.left()..area.right() {
+                let old = cell_at(before, x, y).unwrap_or_else(|| default.clone());
+                let new = cell_at(after, x, y).unwrap_or_else(|| default.clone());
+                if old == new {
+                    row_runs.extend(run.take());
+                    continue;
+                }
+                let symbol = (old.symbol() != new.symbol())
+                    .then(|| (old.symbol().to_string(), new.symbol().to_string()));
+                let style = (old.style() != new.style()).then(|| (old.style(), new.style()));
+                changes.push(CellChange {
+                    position: Position::new(x, y),
+                    symbol,
+                    style,
+                });
+                match run.as_mut() {
+                    Some(rect) => rect.width += 1,
+                    None => run = Some(Rect::new(x, y, 1, 1)),
+                }
+            }
+            row_runs.extend(run);
+        }
+        Self {
+            changed_rects: merge_rows(row_runs),
+            changes,
+        }
+    }
+
+    /// Returns `true` if the frames are identical.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Exports the diff as JSON.
+    pub fn to_json(&self) -> String {
+        let rects = self
+            .changed_rects
+            .iter()
+            .map(|r| {
+                format!(
+                    r#"{{"x":{},"y":{},"width":{},"height":{}}}"#,
+                    r.x, r.y, r.width, r.height
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let changes = self
+            .changes
+            .iter()
+            .map(|change| {
+                let mut json = format!(r#"{{"x":{},"y":{}"#, change.position.x, change.position.y);
+                if let Some((old, new)) = &change.symbol {
+                    let _ = write!(
+                        json,
+                        r#","symbol":{{"before":"{}","after":"{}"}}"#,
+                        escape_json(old),
+                        escape_json(new)
+                    );
+                }
+                if let Some((old, new)) = &change.style {
+                    let _ = write!(
+                        json,
+                        r#","style":{{"before":{},"after":{}}}"#,
+                        style_to_json(old),
+                        style_to_json(new)
+                    );
+                }
+                json.push('}');
+                json
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(r#"{{"changed_rects":[{rects}],"changes":[{changes}]}}"#)
+    }
+}
+
+/// Merges single-row rectangles that are vertically adjacent and span the same
+/// columns.
+pub(crate) fn merge_rows(rows: Vec<Rect>) -> Vec<Rect> {
+    let mut merged: Vec<Rect> = Vec::new();
+    for row in rows {
+        let adjacent = merged
+            .iter_mut()
+            .find(|rect| rect.x == row.x && rect.width == row.width && rect.bottom() == row.y);
+        match adjacent {
+            Some(rect) => rect.height += 1,
+            None => merged.push(row),
+        }
+    }
+    merged
+}
+
+/// Serializes a buffer as JSON.
+fn buffer_to_json(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let lines = (area.top()..area.bottom())
+        .map(|y| {
+            let cells = (area.left()..area.right())
+                .filter_map(|x| buffer.cell(Position::new(x, y)))
+                .map(|cell| {
+                    format!(
+                        r#"{{"symbol":"{}","style":{}}}"#,
+                        escape_json(cell.symbol()),
+                        style_to_json(&cell.style())
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            format!("[{cells}]")
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        r#"{{"width":{},"height":{},"lines":[{lines}]}}"#,
+        area.width, area.height
+    )
+}
+
+/// Serializes a style as JSON.
+fn style_to_json(style: &Style) -> String {
+    let color = |color: Option<ratatui::style::Color>| match color {
+        Some(color) => format!(r#""{color}""#),
+        None => "null".to_string(),
+    };
+    format!(
+        r#"{{"fg":{},"bg":{},"modifier":"{:?}"}}"#,
+        color(style.fg),
+        color(style.bg),
+        style.add_modifier
+    )
+}
+
+/// Escapes a string for use inside a JSON string literal.
+pub(crate) fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::{Color, Modifier};
+
+    use super::*;
+
+    #[test]
+    fn identical_frames() {
+        let buffer = Buffer::with_lines(["ab", "cd"]);
+        let diff = FrameDiff::new(&buffer, &buffer);
+        assert!(diff.is_empty());
+        assert_eq!(diff, FrameDiff::default());
+        assert_eq!(diff.to_json(), r#"{"changed_rects":[],"changes":[]}"#);
+    }
+
+    #[test]
+    fn different_sizes() {
+        let before = Buffer::with_lines(["ab"]);
+        let after = Buffer::with_lines(["ab", "c "]);
+        let diff = FrameDiff::new(&before, &after);
+        assert_eq!(diff.changed_rects, [Rect::new(0, 1, 1, 1)]);
+        assert_eq!(
+            diff.changes,
+            [CellChange {
+                position: Position::new(0, 1),
+                symbol: Some((" ".to_string(), "c".to_string())),
+                style: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn style_change() {
+        let before = Buffer::with_lines(["ab"]);
+        let mut after = before.clone();
+        let red = Style::new().fg(Color::Red).add_modifier(Modifier::BOLD);
+        after.set_style(Rect::new(1, 0, 1, 1), red);
+        let diff = FrameDiff::new(&before, &after);
+        assert_eq!(
+            diff.changes,
+            [CellChange {
+                position: Position::new(1, 0),
+                symbol: None,
+                style: Some((
+                    Style::new().fg(Color::Reset).bg(Color::Reset),
+                    red.bg(Color::Reset),
+                )),
+            }]
+        );
+        assert_eq!(
+            diff.to_json(),
+            r#"{"changed_rects":[{"x":1,"y":0,"width":1,"height":1}],"changes":[{"x":1,"y":0,"style":{"before":{"fg":"Reset","bg":"Reset","modifier":"NONE"},"after":{"fg":"Red","bg":"Reset","modifier":"BOLD"}}}]}"#
+        );
+    }
+
+    #[test]
+    fn merges_adjacent_rows() {
+        let rows = vec![
+            Rect::new(1, 0, 2, 1),
+            Rect::new(1, 1, 2, 1),
+            Rect::new(4, 1, 1, 1),
+            Rect::new(1, 2, 3, 1),
+            Rect::new(1, 4, 2, 1),
+        ];
+        assert_eq!(
+            merge_rows(rows),
+            [
+                Rect::new(1, 0, 2, 2),
+                Rect::new(4, 1, 1, 1),
+                Rect::new(1, 2, 3, 1),
+                Rect::new(1, 4, 2, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes_json() {
+        assert_eq!(escape_json("a\"b\\c"), r#"a\"b\\c"#);
+        assert_eq!(escape_json("\n\r\t"), r#"\n\r\t"#);
+        assert_eq!(escape_json("\u{1}\u{7f}日"), r#"\u0001\u007f日"#);
+    }
+
+    #[test]
+    fn capture_to_json() {
+        let capture = FrameCapture::new(2);
+        record(&Buffer::with_lines(["\""]));
+        assert!(!capture.is_complete());
+        record(&Buffer::with_lines(["x"]));
+        assert!(capture.is_complete());
+        // Complete captures do not record more frames.
+        record(&Buffer::with_lines(["y"]));
+        assert_eq!(capture.frames().len(), 2);
+        assert_eq!(
+            capture.to_json(),
+            r#"{"frames":[{"width":1,"height":1,"lines":[[{"symbol":"\"","style":{"fg":"Reset","bg":"Reset","modifier":"NONE"}}]]},{"width":1,"height":1,"lines":[[{"symbol":"x","style":{"fg":"Reset","bg":"Reset","modifier":"NONE"}}]]}],"diffs":[{"changed_rects":[{"x":0,"y":0,"width":1,"height":1}],"changes":[{"x":0,"y":0,"symbol":{"before":"\"","after":"x"}}]}]}"#
+        );
+    }
+}
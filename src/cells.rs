@@ -0,0 +1,351 @@
+//! ## Cell symbols
+//!
+//! Every cell of the buffer holds a single grapheme (a user-perceived
+//! character) that is one or two columns wide. Writing a whole word, a
+//! combined emoji sequence split in half, or a wide character without room
+//! for it into a cell garbles the line in the browser, since the grid cannot
+//! reflow it like a terminal would.
+//!
+//! [`cell_symbols`] splits a string into the symbols of consecutive cells and
+//! [`validate_buffer`] finds the cells that break the rules. In debug builds,
+//! the renderer validates every frame and logs the offending cells to the
+//! console.
+//!
+//! Tabs are expanded to the next tab stop (every [`tab_width`] columns) when
+//! a frame is drawn, instead of leaving them to the browser: a cell holding
+//! `"\t"` is replaced with blanks up to the next tab stop and the rest of the
+//! row is shifted to the right, like the text after a tab in a terminal.
+//! [`expand_tabs`] does the same for a string before it is rendered.
+
+#[cfg(debug_assertions)]
+use std::cell::RefCell;
+use std::{cell::Cell as StdCell, ops::Range};
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::Position,
+    style::Style,
+    text::Span,
+};
+
+/// Maximum number of issues listed in a console warning.
+#[cfg(debug_assertions)]
+const MAX_REPORTED_ISSUES: usize = 10;
+
+/// Default number of columns between two tab stops.
+pub const DEFAULT_TAB_WIDTH: u16 = 8;
+
+thread_local! {
+    /// Number of columns between two tab stops.
+    static TAB_WIDTH: StdCell<u16> = const { StdCell::new(DEFAULT_TAB_WIDTH) };
+}
+
+#[cfg(debug_assertions)]
+thread_local! {
+    /// Issues reported in the last warning, to avoid repeating it every frame.
+    static REPORTED: RefCell<Vec<CellIssue>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Kind of a [`CellIssue`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum CellIssueKind {
+    /// The cell holds more than one grapheme, e.g. a whole word.
+    MultipleGraphemes,
+    /// The cell holds a wide grapheme but the next cell is not free, or it is
+    /// in the last column.
+    NoRoomForWideGrapheme,
+}
+
+/// A cell that holds an invalid symbol.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CellIssue {
+    /// Position of the cell.
+    pub position: Position,
+    /// Symbol of the cell.
+    pub symbol: String,
+    /// Kind of the issue.
+    pub kind: CellIssueKind,
+}
+
+/// Returns the graphemes of a string.
+fn graphemes(text: &str) -> Vec<String> {
+    // The spans of ratatui know how to split graphemes, which avoids adding
+    // dependencies for the same tables.
+    Span::raw(text)
+        .styled_graphemes(Style::default())
+        .map(|grapheme| grapheme.symbol.to_string())
+        .collect()
+}
+
+/// Returns the number of columns taken by a symbol.
+pub fn symbol_width(symbol: &str) -> usize {
+    Span::raw(symbol).width()
+}
+
+/// Returns the cells of a row along with their index in the row, without the
+/// cells covered by the wide symbol before them.
+///
+/// Writing a wide symbol to the buffer resets the cells that it covers to a
+/// blank, which is not part of the text of the row.
+pub(crate) fn visible_cells<'a, I>(row: I) -> impl Iterator<Item = (usize, &'a Cell)>
+where
+    I: IntoIterator<Item = &'a Cell>,
+{
+    let mut covered = 0;
+    row.into_iter().enumerate().filter(move |(_, cell)| {
+        if covered > 0 {
+            covered -= 1;
+            return false;
+        }
+        covered = symbol_width(cell.symbol()).saturating_sub(1);
+        true
+    })
+}
+
+/// Returns the text of a range of columns of a row of the buffer.
+///
+/// The cells covered by a wide symbol are left out, see [`visible_cells`],
+/// and a wide symbol that starts in the range is included whole.
+pub(crate) fn row_text(buffer: &Buffer, y: u16, columns: Range<u16>) -> String {
+    let area = buffer.area;
+    let row = (area.left()..area.right()).filter_map(|x| buffer.cell((x, y)));
+    visible_cells(row)
+        .filter(|(index, _)| columns.contains(&(area.left() + *index as u16)))
+        .map(|(_, cell)| cell.symbol())
+        .collect()
+}
+
+/// Splits a string into the symbols of consecutive cells.
+///
+/// Every grapheme takes one cell and wide graphemes (e.g. most emoji and CJK
+/// characters) are followed by an empty symbol for each extra column they
+/// cover. Zero-width graphemes such as control characters are dropped.
+pub fn cell_symbols(text: &str) -> Vec<String> {
+    let mut symbols = Vec::new();
+    for grapheme in graphemes(text) {
+        let width = symbol_width(&grapheme);
+        if width == 0 {
+            continue;
+        }
+        symbols.push(grapheme);
+        symbols.extend(std::iter::repeat_n(String::new(), width - 1));
+    }
+    symbols
+}
+
+/// Sets the number of columns between two tab stops.
+///
+/// A width of 0 draws every tab as a single blank. The default is
+/// [`DEFAULT_TAB_WIDTH`].
+pub fn set_tab_width(width: u16) {
+    TAB_WIDTH.set(width);
+}
+
+/// Returns the number of columns between two tab stops.
+pub fn tab_width() -> u16 {
+    TAB_WIDTH.get()
+}
+
+/// Returns the column of the tab stop after the given column.
+fn next_tab_stop(column: usize, tab_width: u16) -> usize {
+    match usize::from(tab_width) {
+        0 => column + 1,
+        width => (column / width + 1) * width,
+    }
+}
+
+/// Expands the tabs of a string to spaces up to the next tab stop.
+///
+/// The columns are counted from the start of every line, with wide
+/// graphemes taking two columns.
+pub fn expand_tabs(text: &str, tab_width: u16) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let (line, newline) = match line.strip_suffix('\n') {
+            Some(line) => (line, "\n"),
+            None => (line, ""),
+        };
+        let mut column = 0;
+        for grapheme in graphemes(line) {
+            if grapheme == "\t" {
+                let stop = next_tab_stop(column, tab_width);
+                expanded.extend(std::iter::repeat_n(' ', stop - column));
+                column = stop;
+            } else {
+                column += symbol_width(&grapheme);
+                expanded.push_str(&grapheme);
+            }
+        }
+        expanded.push_str(newline);
+    }
+    expanded
+}
+
+/// Expands the tabs of the cells of a buffer with the given tab width.
+fn expand_buffer_tabs_with(buffer: &mut Buffer, tab_width: u16) {
+    let width = usize::from(buffer.area.width);
+    if width == 0 {
+        return;
+    }
+    for row in buffer.content.chunks_mut(width) {
+        if !row.iter().any(|cell| cell.symbol().contains('\t')) {
+            continue;
+        }
+        let mut expanded: Vec<Cell> = Vec::with_capacity(width);
+        for cell in row.iter() {
+            if expanded.len() >= width {
+                break;
+            }
+            let mut cell = cell.clone();
+            if cell.symbol() == "\t" {
+                cell.set_symbol(" ");
+                let stop = next_tab_stop(expanded.len(), tab_width).min(width);
+                expanded.resize(stop, cell);
+            } else {
+                if cell.symbol().contains('\t') {
+                    let symbol = cell.symbol().replace('\t', " ");
+                    cell.set_symbol(&symbol);
+                }
+                expanded.push(cell);
+            }
+        }
+        expanded.truncate(width);
+        row[..expanded.len()].clone_from_slice(&expanded);
+    }
+}
+
+/// Expands the tabs of the cells of a buffer with the current tab width.
+///
+/// This is called by the renderer for every frame.
+pub(crate) fn expand_buffer_tabs(buffer: &mut Buffer) {
+    expand_buffer_tabs_with(buffer, TAB_WIDTH.get());
+}
+
+/// Returns the cells of the buffer that hold invalid symbols.
+pub fn validate_buffer(buffer: &Buffer) -> Vec<CellIssue> {
+    let area = buffer.area;
+    let mut issues = Vec::new();
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let Some(cell) = buffer.cell((x, y)) else {
+                continue;
+            };
+            let symbol = cell.symbol();
+            if symbol.len() <= 1 {
+                // A single ASCII character, which is the common case.
+                continue;
+            }
+            let kind = if graphemes(symbol).len() > 1 {
+                Some(CellIssueKind::MultipleGraphemes)
+            } else {
+                let width = symbol_width(symbol) as u16;
+                let covered = x.saturating_add(1)..x.saturating_add(width);
+                let is_free = |x: u16| {
+                    x < area.right()
+                        && buffer
+                            .cell((x, y))
+                            .is_some_and(|cell| matches!(cell.symbol(), "" | " "))
+                };
+                (width > 1 && !covered.into_iter().all(is_free))
+                    .then_some(CellIssueKind::NoRoomForWideGrapheme)
+            };
+            if let Some(kind) = kind {
+                issues.push(CellIssue {
+                    position: Position::new(x, y),
+                    symbol: symbol.to_string(),
+                    kind,
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// Validates the buffer and logs a warning if the issues changed since the
+/// last warning.
+///
+/// This is called by the renderer in debug builds.
+#[cfg(debug_assertions)]
+pub(crate) fn warn_invalid_cells(buffer: &Buffer) {
+    let issues = validate_buffer(buffer);
+    if REPORTED.with(|reported| *reported.borrow() == issues) {
+        return;
+    }
+    if !issues.is_empty() {
+        let list = issues
+            .iter()
+            .take(MAX_REPORTED_ISSUES)
+            .map(|issue| {
+                format!(
+                    "({}, {}) {:?}: {:?}",
+                    issue.position.x, issue.position.y, issue.symbol, issue.kind
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let more = issues.len().saturating_sub(MAX_REPORTED_ISSUES);
+        let more = if more > 0 {
+            format!("\n... and {more} more")
+        } else {
+            String::new()
+        };
+        crate::web_warn!(
+            "ratzilla: {} cell(s) hold invalid symbols, see `ratzilla::cells`:\n{list}{more}",
+            issues.len()
+        );
+    }
+    REPORTED.with(|reported| reported.replace(issues));
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+
+    use super::*;
+
+    /// Returns a buffer with the given symbols in its only row.
+    fn row(symbols: &[&str]) -> Buffer {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, symbols.len() as u16, 1));
+        for (x, symbol) in symbols.iter().enumerate() {
+            buffer[(x as u16, 0)].set_symbol(symbol);
+        }
+        buffer
+    }
+
+    #[test]
+    fn expands_tabs_in_strings() {
+        assert_eq!(expand_tabs("a\tb", 4), "a   b");
+        assert_eq!(expand_tabs("abcd\te", 4), "abcd    e");
+        assert_eq!(expand_tabs("ab\tc\nd\te", 4), "ab  c\nd   e");
+        assert_eq!(expand_tabs("日\tx", 4), "日  x");
+        assert_eq!(expand_tabs("a\tb", 0), "a b");
+    }
+
+    #[test]
+    fn aligns_cells_to_tab_stops() {
+        let mut buffer = row(&["a", "\t", "b", "c", " ", " ", " ", " "]);
+        expand_buffer_tabs_with(&mut buffer, 4);
+        assert_eq!(buffer, row(&["a", " ", " ", " ", "b", "c", " ", " "]));
+
+        let mut buffer = row(&["a", "b", "c", "d", "\t", "e", " ", " ", " ", " "]);
+        expand_buffer_tabs_with(&mut buffer, 4);
+        assert_eq!(
+            buffer,
+            row(&["a", "b", "c", "d", " ", " ", " ", " ", "e", " "])
+        );
+    }
+
+    #[test]
+    fn truncates_expanded_rows() {
+        let mut buffer = row(&["\t", "a", "b"]);
+        expand_buffer_tabs_with(&mut buffer, 8);
+        assert_eq!(buffer, row(&[" ", " ", " "]));
+    }
+
+    #[test]
+    fn tab_width_zero_draws_blanks() {
+        let mut buffer = row(&["a", "\t", "b"]);
+        expand_buffer_tabs_with(&mut buffer, 0);
+        assert_eq!(buffer, row(&["a", " ", "b"]));
+    }
+}
@@ -0,0 +1,222 @@
+//! ## Clipboard
+//!
+//! Helpers for writing text to the clipboard.
+//!
+//! The text of the screen can be copied with [`copy_region`] and
+//! [`copy_screen`], or with a shortcut set with [`set_copy_screen_key`]. It
+//! is reconstructed from the cells of the next frame, so the copied text has
+//! one line per row instead of the fragments that the native selection of the
+//! browser yields. Pasted text is received as
+//! [`Event::Paste`] with [`WebRenderer::on_event`].
+//!
+//! The asynchronous [Clipboard API] is used when it is available and the
+//! legacy `document.execCommand("copy")` is used as a fallback (e.g. in
+//! insecure contexts or older browsers).
+//!
+//! [Clipboard API]: https://developer.mozilla.org/en-US/docs/Web/API/Clipboard_API
+//! [`WebRenderer::on_event`]: crate::WebRenderer::on_event
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use ratatui::{buffer::Buffer, layout::Rect};
+
+use web_sys::{
+    js_sys::Reflect,
+    wasm_bindgen::{prelude::Closure, JsCast, JsValue},
+    window, HtmlDocument, HtmlTextAreaElement,
+};
+
+use crate::{
+    cells,
+    error::Error,
+    event::{self, CopyEvent, Event, KeyBinding},
+    render,
+};
+
+thread_local! {
+    /// Areas to copy from the next frame, `None` for the whole screen.
+    static PENDING_COPIES: RefCell<Vec<Option<Rect>>> = const { RefCell::new(Vec::new()) };
+    /// Shortcut that copies the screen.
+    static COPY_SCREEN_KEY: RefCell<Option<KeyBinding>> = const { RefCell::new(None) };
+    /// Whether the event filter of the shortcut is added.
+    static FILTER_ADDED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Copies the text of an area of the screen to the clipboard.
+///
+/// The text is taken from the next frame. Rows are separated with newlines
+/// and their trailing whitespace is removed. The result is reported to the
+/// listeners registered with [`WebRenderer::on_copy_event`].
+///
+/// [`WebRenderer::on_copy_event`]: crate::WebRenderer::on_copy_event
+pub fn copy_region(area: Rect) {
+    PENDING_COPIES.with(|copies| copies.borrow_mut().push(Some(area)));
+    render::request_redraw();
+}
+
+/// Copies the text of the whole screen to the clipboard.
+///
+/// The trailing blank rows are removed as well. See [`copy_region`].
+pub fn copy_screen() {
+    PENDING_COPIES.with(|copies| copies.borrow_mut().push(None));
+    render::request_redraw();
+}
+
+/// Sets the shortcut that copies the whole screen to the clipboard.
+///
+/// The key events of the shortcut are not passed to the handlers. This is
+/// disabled (`None`) by default.
+///
+/// ```rust no_run
+/// use ratzilla::{
+///     clipboard,
+///     event::{KeyBinding, KeyCode},
+/// };
+///
+/// clipboard::set_copy_screen_key(Some(KeyBinding::new(KeyCode::Char('c')).ctrl().alt()))
+///     .unwrap();
+/// ```
+pub fn set_copy_screen_key(key: Option<KeyBinding>) -> Result<(), Error> {
+    COPY_SCREEN_KEY.with(|current| current.replace(key));
+    if !FILTER_ADDED.get() {
+        event::add_key_listener()?;
+        event::add_event_filter(|event| match &event {
+            Event::Key(key)
+                if COPY_SCREEN_KEY.with(|binding| {
+                    binding
+                        .borrow()
+                        .as_ref()
+                        .is_some_and(|binding| binding.matches(key))
+                }) =>
+            {
+                copy_screen();
+                None
+            }
+            _ => Some(event),
+        });
+        FILTER_ADDED.set(true);
+    }
+    Ok(())
+}
+
+/// Performs the copies requested since the previous frame.
+///
+/// This is called by the renderer before the frame is flushed.
+pub(crate) fn copy_pending(buffer: &Buffer) {
+    let copies = PENDING_COPIES.with(|copies| copies.take());
+    for area in copies {
+        let text = match area {
+            Some(area) => buffer_text(buffer, area.intersection(buffer.area)),
+            None => buffer_text(buffer, buffer.area).trim_end().to_string(),
+        };
+        copy_to_clipboard(&text, |_| {});
+    }
+}
+
+/// Returns the text of an area of the buffer.
+///
+/// Rows are separated with newlines and their trailing whitespace is removed.
+/// The cells covered by wide symbols are left out.
+pub(crate) fn buffer_text(buffer: &Buffer, area: Rect) -> String {
+    (area.top()..area.bottom())
+        .map(|y| {
+            let row = cells::row_text(buffer, y, area.left()..area.right());
+            row.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes the given text to the clipboard.
+///
+/// `on_done` is called with `true` if the text was copied successfully. The
+/// result is also reported to the listeners registered with
+/// [`WebRenderer::on_copy_event`].
+///
+/// [`WebRenderer::on_copy_event`]: crate::WebRenderer::on_copy_event
+pub fn copy_to_clipboard<F>(text: &str, on_done: F)
+where
+    F: FnOnce(bool) + 'static,
+{
+    let finish = {
+        let text = text.to_string();
+        move |success: bool| {
+            on_done(success);
+            event::dispatch(Event::Copy(CopyEvent { text, success }));
+        }
+    };
+    let Some(window) = window() else {
+        finish(false);
+        return;
+    };
+    let navigator = window.navigator();
+    let has_clipboard_api = Reflect::get(&navigator, &JsValue::from_str("clipboard"))
+        .map(|clipboard| !clipboard.is_undefined())
+        .unwrap_or(false);
+    if !has_clipboard_api {
+        finish(copy_with_exec_command(text).unwrap_or(false));
+        return;
+    }
+
+    // Both closures need to own `finish`, only one of them will be called.
+    let finish = Rc::new(RefCell::new(Some(finish)));
+    let on_success = Closure::once({
+        let finish = finish.clone();
+        move |_: JsValue| {
+            if let Some(finish) = finish.borrow_mut().take() {
+                finish(true);
+            }
+        }
+    });
+    let on_failure = Closure::once({
+        let text = text.to_string();
+        move |_: JsValue| {
+            if let Some(finish) = finish.borrow_mut().take() {
+                finish(copy_with_exec_command(&text).unwrap_or(false));
+            }
+        }
+    });
+    let _ = navigator
+        .clipboard()
+        .write_text(text)
+        .then2(&on_success, &on_failure);
+    on_success.forget();
+    on_failure.forget();
+}
+
+/// Writes the given text to the clipboard using `document.execCommand`.
+fn copy_with_exec_command(text: &str) -> Result<bool, Error> {
+    let document = window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .document()
+        .ok_or(Error::UnableToRetrieveDocument)?;
+    let body = document.body().ok_or(Error::UnableToRetrieveBody)?;
+    let textarea = document
+        .create_element("textarea")?
+        .unchecked_into::<HtmlTextAreaElement>();
+    textarea.set_value(text);
+    textarea.set_attribute("readonly", "")?;
+    textarea.set_attribute("style", "position: fixed; left: -9999px; opacity: 0;")?;
+    body.append_child(&textarea)?;
+    textarea.select();
+    let result = document
+        .unchecked_into::<HtmlDocument>()
+        .exec_command("copy");
+    textarea.remove();
+    Ok(result?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_text() {
+        let buffer = Buffer::with_lines(["日本 ab  ", "🦀x      "]);
+        assert_eq!(super::buffer_text(&buffer, buffer.area), "日本 ab\n🦀x");
+        assert_eq!(super::buffer_text(&buffer, Rect::new(2, 0, 3, 2)), "本\nx");
+    }
+}
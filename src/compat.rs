@@ -0,0 +1,234 @@
+//! ## Browser compatibility
+//!
+//! Old or restricted browsers (e.g. embedded web views or locked-down
+//! enterprise browsers) can lack APIs that the backends rely on. Instead of
+//! failing in the middle of the first frame, the application can check the
+//! browser first and show a fallback screen:
+//!
+//! ```rust no_run
+//! use ratzilla::compat::{check_compatibility, show_fallback};
+//!
+//! let report = check_compatibility();
+//! if !report.is_supported() {
+//!     show_fallback(&report).unwrap();
+//!     return;
+//! }
+//! // Build the backend and start the render loop...
+//! ```
+//!
+//! The optional capabilities (storage, cross-origin isolation, WebAssembly
+//! features) are reported as well, so features that need them can be turned
+//! off while the rest of the application keeps working.
+
+use web_sys::{
+    js_sys::{Reflect, Uint8Array, WebAssembly},
+    wasm_bindgen::JsValue,
+    window, Storage, Window,
+};
+
+use crate::error::Error;
+
+/// Global APIs that the backends and the render loop require.
+const REQUIRED_APIS: &[&str] = &[
+    "requestAnimationFrame",
+    "IntersectionObserver",
+    "TextEncoder",
+    "TextDecoder",
+    "performance",
+];
+
+/// Smallest module that uses a SIMD instruction.
+const SIMD_MODULE: &[u8] = &[
+    0, 97, 115, 109, 1, 0, 0, 0, 1, 5, 1, 96, 0, 1, 123, 3, 2, 1, 0, 10, 10, 1, 8, 0, 65, 0, 253,
+    15, 253, 98, 11,
+];
+
+/// Smallest module that uses a bulk memory instruction.
+const BULK_MEMORY_MODULE: &[u8] = &[
+    0, 97, 115, 109, 1, 0, 0, 0, 1, 4, 1, 96, 0, 0, 3, 2, 1, 0, 5, 3, 1, 0, 1, 10, 14, 1, 12, 0,
+    65, 0, 65, 0, 65, 0, 252, 10, 0, 0, 11,
+];
+
+/// Smallest module that uses a shared memory and an atomic instruction.
+const THREADS_MODULE: &[u8] = &[
+    0, 97, 115, 109, 1, 0, 0, 0, 1, 4, 1, 96, 0, 0, 3, 2, 1, 0, 5, 4, 1, 3, 1, 1, 10, 11, 1, 9, 0,
+    65, 0, 254, 16, 2, 0, 26, 11,
+];
+
+/// Id of the fallback screen element.
+const FALLBACK_ID: &str = "ratzilla-fallback";
+
+/// Style of the fallback screen element.
+const FALLBACK_STYLE: &str = "position: fixed; inset: 0; display: flex; \
+    flex-direction: column; align-items: center; justify-content: center; \
+    gap: 1em; padding: 1em; font-family: monospace; white-space: pre-wrap; \
+    z-index: 2147483647;";
+
+/// WebAssembly features supported by the browser.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct WasmFeatures {
+    /// Whether 128-bit SIMD instructions are supported, see the `simd`
+    /// feature of the crate.
+    pub simd: bool,
+    /// Whether bulk memory instructions are supported.
+    pub bulk_memory: bool,
+    /// Whether shared memories and atomic instructions are supported, see the
+    /// `threads` feature of the crate.
+    pub threads: bool,
+}
+
+/// Result of [`check_compatibility`].
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct CompatibilityReport {
+    /// Required APIs that are missing, e.g. `IntersectionObserver`.
+    pub missing_apis: Vec<&'static str>,
+    /// Supported WebAssembly features.
+    pub wasm_features: WasmFeatures,
+    /// Whether `localStorage` can be written to.
+    ///
+    /// This is `false` when storage is disabled, e.g. for third-party frames
+    /// or in some private browsing modes.
+    pub local_storage: bool,
+    /// Whether `sessionStorage` can be written to.
+    pub session_storage: bool,
+    /// Whether the page is cross-origin isolated, which is required for
+    /// `SharedArrayBuffer` and thus for the `threads` feature.
+    pub cross_origin_isolated: bool,
+}
+
+impl CompatibilityReport {
+    /// Returns `true` if all the required APIs are present.
+    ///
+    /// The optional capabilities are not taken into account.
+    pub fn is_supported(&self) -> bool {
+        self.missing_apis.is_empty()
+    }
+
+    /// Returns `true` if rows can be processed on worker threads, i.e. the
+    /// browser supports threads and the page is cross-origin isolated.
+    pub fn supports_threads(&self) -> bool {
+        self.wasm_features.threads && self.cross_origin_isolated
+    }
+
+    /// Returns a human-readable description of the missing APIs, or `None`
+    /// if the browser is supported.
+    pub fn message(&self) -> Option<String> {
+        if self.is_supported() {
+            return None;
+        }
+        Some(format!(
+            "This browser is not supported.\n\nMissing: {}\n\nPlease use a recent version of \
+             Firefox, Chrome, Edge or Safari.",
+            self.missing_apis.join(", ")
+        ))
+    }
+}
+
+/// Checks which of the APIs used by the crate the browser supports.
+///
+/// This does not require any of the checked APIs itself, so it can be run
+/// before the backend is built.
+pub fn check_compatibility() -> CompatibilityReport {
+    let Some(window) = window() else {
+        return CompatibilityReport {
+            missing_apis: vec!["window"],
+            ..Default::default()
+        };
+    };
+    let global = JsValue::from(&window);
+    let mut missing_apis = REQUIRED_APIS
+        .iter()
+        .copied()
+        .filter(|api| !Reflect::has(&global, &JsValue::from_str(api)).unwrap_or(false))
+        .collect::<Vec<_>>();
+    if window.document().is_none() {
+        missing_apis.push("document");
+    }
+    CompatibilityReport {
+        missing_apis,
+        wasm_features: WasmFeatures {
+            simd: validates(SIMD_MODULE),
+            bulk_memory: validates(BULK_MEMORY_MODULE),
+            threads: validates(THREADS_MODULE)
+                && Reflect::has(&global, &JsValue::from_str("SharedArrayBuffer")).unwrap_or(false),
+        },
+        local_storage: is_writable(window.local_storage()),
+        session_storage: is_writable(window.session_storage()),
+        cross_origin_isolated: is_cross_origin_isolated(&window),
+    }
+}
+
+/// Shows a fallback screen that explains why the application does not run.
+///
+/// The screen covers the page and lists the missing APIs of the report. An
+/// existing fallback screen is replaced.
+pub fn show_fallback(report: &CompatibilityReport) -> Result<(), Error> {
+    let document = window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .document()
+        .ok_or(Error::UnableToRetrieveDocument)?;
+    if let Some(fallback) = document.get_element_by_id(FALLBACK_ID) {
+        fallback.remove();
+    }
+    let fallback = document.create_element("div")?;
+    fallback.set_attribute("id", FALLBACK_ID)?;
+    fallback.set_attribute("role", "alert")?;
+    fallback.set_attribute("style", FALLBACK_STYLE)?;
+    fallback.set_text_content(Some(
+        &report
+            .message()
+            .unwrap_or_else(|| "This browser is supported.".to_string()),
+    ));
+    document
+        .body()
+        .ok_or(Error::UnableToRetrieveBody)?
+        .append_child(&fallback)?;
+    Ok(())
+}
+
+/// Returns `true` if the WebAssembly module is valid in this browser.
+fn validates(module: &[u8]) -> bool {
+    WebAssembly::validate(&Uint8Array::from(module)).unwrap_or(false)
+}
+
+/// Returns `true` if an item can be written to the storage.
+///
+/// Accessing the storage throws when it is disabled and writing to it throws
+/// when its quota is zero, e.g. in some private browsing modes.
+fn is_writable(storage: Result<Option<Storage>, JsValue>) -> bool {
+    const KEY: &str = "ratzilla-compat";
+    let Ok(Some(storage)) = storage else {
+        return false;
+    };
+    storage.set_item(KEY, KEY).is_ok() && storage.remove_item(KEY).is_ok()
+}
+
+/// Returns `true` if the page is cross-origin isolated.
+fn is_cross_origin_isolated(window: &Window) -> bool {
+    Reflect::get(window, &JsValue::from_str("crossOriginIsolated"))
+        .ok()
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let report = CompatibilityReport::default();
+        assert!(report.is_supported());
+        assert_eq!(report.message(), None);
+        assert!(!report.supports_threads());
+
+        let report = CompatibilityReport {
+            missing_apis: vec!["IntersectionObserver", "TextEncoder"],
+            ..Default::default()
+        };
+        assert!(!report.is_supported());
+        assert!(report
+            .message()
+            .is_some_and(|message| message.contains("Missing: IntersectionObserver, TextEncoder")));
+    }
+}
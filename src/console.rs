@@ -0,0 +1,307 @@
+//! ## Console logging
+//!
+//! Leveled logging to the console of the browser. The messages below the
+//! level set with [`set_level`] are skipped without being formatted, and every
+//! call site is rate limited, so a message logged on every frame does not
+//! flood the console.
+//!
+//! ```rust no_run
+//! use ratzilla::{
+//!     console::{self, Level},
+//!     web_debug, web_warn,
+//! };
+//!
+//! console::set_level(Some(Level::Debug));
+//!
+//! let _span = console::span("loading");
+//! web_debug!("fetching {} items", 42);
+//! web_warn!("the cache is stale");
+//! ```
+//!
+//! Messages logged while a [`Span`] is alive are nested in a collapsed
+//! `console.group`. The group is only opened once a message is logged, so
+//! spans without messages leave no trace in the console.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt::{self, Arguments},
+};
+
+use web_sys::console;
+
+use crate::utils;
+
+/// Length of the window of the rate limit in milliseconds.
+const RATE_LIMIT_WINDOW: f64 = 1000.0;
+
+thread_local! {
+    /// Most verbose level that is logged.
+    static LEVEL: Cell<Option<Level>> = const { Cell::new(Some(Level::DEFAULT)) };
+    /// Largest number of messages of a call site per second.
+    static RATE_LIMIT: Cell<u32> = const { Cell::new(10) };
+    /// Rate limits of the call sites.
+    static SITES: RefCell<HashMap<&'static str, RateLimit>> = RefCell::new(HashMap::new());
+    /// Labels of the spans along with whether their group is opened.
+    static SPANS: RefCell<Vec<(String, bool)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Level of a message.
+///
+/// The levels are ordered from the most severe to the most verbose.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Level {
+    /// Errors, logged with `console.error`.
+    Error,
+    /// Warnings, logged with `console.warn`.
+    Warn,
+    /// Information, logged with `console.info`.
+    Info,
+    /// Debugging information, logged with `console.debug`.
+    Debug,
+    /// Detailed tracing, logged with `console.debug`.
+    Trace,
+}
+
+impl Level {
+    /// Default level: [`Level::Info`] in debug builds and [`Level::Warn`] in
+    /// release builds.
+    const DEFAULT: Self = if cfg!(debug_assertions) {
+        Self::Info
+    } else {
+        Self::Warn
+    };
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        })
+    }
+}
+
+/// Sets the most verbose level that is logged, or `None` to disable the
+/// logging.
+///
+/// The default is [`Level::Info`] in debug builds and [`Level::Warn`] in
+/// release builds.
+pub fn set_level(level: Option<Level>) {
+    LEVEL.set(level);
+}
+
+/// Returns the most verbose level that is logged.
+pub fn level() -> Option<Level> {
+    LEVEL.get()
+}
+
+/// Returns `true` if the messages of the given level are logged.
+pub fn enabled(level: Level) -> bool {
+    LEVEL.get().is_some_and(|max| level <= max)
+}
+
+/// Sets the largest number of messages that a call site logs per second.
+///
+/// The messages over the limit are counted and the count is added to the next
+/// message of the call site that is logged. The default is 10, and 0 disables
+/// the rate limit.
+pub fn set_rate_limit(messages_per_second: u32) {
+    RATE_LIMIT.set(messages_per_second);
+}
+
+/// Logs a message with the given level.
+///
+/// ```rust no_run
+/// use ratzilla::{console::Level, web_log};
+///
+/// web_log!(Level::Info, "resized to {}x{}", 80, 24);
+/// ```
+#[macro_export]
+macro_rules! web_log {
+    ($level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        if $crate::console::enabled(level) {
+            $crate::console::log(
+                level,
+                concat!(file!(), ":", line!(), ":", column!()),
+                format_args!($($arg)+),
+            );
+        }
+    }};
+}
+
+/// Logs a message with [`Level::Error`](crate::console::Level::Error).
+#[macro_export]
+macro_rules! web_error {
+    ($($arg:tt)+) => {
+        $crate::web_log!($crate::console::Level::Error, $($arg)+)
+    };
+}
+
+/// Logs a message with [`Level::Warn`](crate::console::Level::Warn).
+#[macro_export]
+macro_rules! web_warn {
+    ($($arg:tt)+) => {
+        $crate::web_log!($crate::console::Level::Warn, $($arg)+)
+    };
+}
+
+/// Logs a message with [`Level::Info`](crate::console::Level::Info).
+#[macro_export]
+macro_rules! web_info {
+    ($($arg:tt)+) => {
+        $crate::web_log!($crate::console::Level::Info, $($arg)+)
+    };
+}
+
+/// Logs a message with [`Level::Debug`](crate::console::Level::Debug).
+#[macro_export]
+macro_rules! web_debug {
+    ($($arg:tt)+) => {
+        $crate::web_log!($crate::console::Level::Debug, $($arg)+)
+    };
+}
+
+/// Logs a message with [`Level::Trace`](crate::console::Level::Trace).
+#[macro_export]
+macro_rules! web_trace {
+    ($($arg:tt)+) => {
+        $crate::web_log!($crate::console::Level::Trace, $($arg)+)
+    };
+}
+
+/// Logs a message from the given call site, if its rate limit allows it.
+///
+/// This is called by the logging macros.
+#[doc(hidden)]
+pub fn log(level: Level, site: &'static str, message: Arguments<'_>) {
+    let limit = RATE_LIMIT.get();
+    let suppressed = if limit == 0 {
+        Some(0)
+    } else {
+        SITES.with(|sites| {
+            sites
+                .borrow_mut()
+                .entry(site)
+                .or_default()
+                .allow(utils::performance_now(), limit)
+        })
+    };
+    let Some(suppressed) = suppressed else {
+        return;
+    };
+    let mut message = format!("[{level}] {message}");
+    if suppressed > 0 {
+        message.push_str(&format!(" ({suppressed} similar message(s) suppressed)"));
+    }
+    open_groups();
+    let message = message.into();
+    match level {
+        Level::Error => console::error_1(&message),
+        Level::Warn => console::warn_1(&message),
+        Level::Info => console::info_1(&message),
+        Level::Debug | Level::Trace => console::debug_1(&message),
+    }
+}
+
+/// Opens the groups of the spans that are not opened yet.
+fn open_groups() {
+    SPANS.with(|spans| {
+        for (label, opened) in spans.borrow_mut().iter_mut().filter(|(_, opened)| !opened) {
+            console::group_collapsed_1(&label.as_str().into());
+            *opened = true;
+        }
+    });
+}
+
+/// Starts a span that nests the following messages in a console group.
+///
+/// The span ends when the returned guard is dropped.
+pub fn span<S: Into<String>>(label: S) -> Span {
+    SPANS.with(|spans| spans.borrow_mut().push((label.into(), false)));
+    Span { _private: () }
+}
+
+/// Guard of a span started with [`span`].
+///
+/// Dropping it closes the console group of the span, if any message was
+/// logged in it.
+#[derive(Debug)]
+#[must_use = "the span ends when the guard is dropped"]
+pub struct Span {
+    /// Prevents constructing the guard outside of [`span`].
+    _private: (),
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some((_, true)) = SPANS.with(|spans| spans.borrow_mut().pop()) {
+            console::group_end();
+        }
+    }
+}
+
+/// Rate limit of a call site.
+#[derive(Debug, Default)]
+struct RateLimit {
+    /// Start of the current window.
+    window_start: f64,
+    /// Number of messages logged in the current window.
+    logged: u32,
+    /// Number of messages suppressed since the last logged message.
+    suppressed: u32,
+}
+
+impl RateLimit {
+    /// Returns the number of the messages suppressed since the last one if a
+    /// message is allowed at the given time, `None` otherwise.
+    fn allow(&mut self, now: f64, limit: u32) -> Option<u32> {
+        if self.logged == 0 || now - self.window_start >= RATE_LIMIT_WINDOW {
+            self.window_start = now;
+            self.logged = 0;
+        }
+        if self.logged >= limit {
+            self.suppressed += 1;
+            return None;
+        }
+        self.logged += 1;
+        Some(std::mem::take(&mut self.suppressed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels() {
+        set_level(Some(Level::Warn));
+        assert!(enabled(Level::Error));
+        assert!(enabled(Level::Warn));
+        assert!(!enabled(Level::Info));
+        set_level(None);
+        assert!(!enabled(Level::Error));
+    }
+
+    #[test]
+    fn rate_limit() {
+        let mut rate_limit = RateLimit::default();
+        assert_eq!(rate_limit.allow(0.0, 2), Some(0));
+        assert_eq!(rate_limit.allow(10.0, 2), Some(0));
+        assert_eq!(rate_limit.allow(20.0, 2), None);
+        assert_eq!(rate_limit.allow(30.0, 2), None);
+        assert_eq!(rate_limit.allow(1000.0, 2), Some(2));
+        assert_eq!(rate_limit.allow(1010.0, 2), Some(0));
+        assert_eq!(rate_limit.allow(1020.0, 2), None);
+    }
+}
@@ -0,0 +1,220 @@
+//! ## Step debugger
+//!
+//! Pauses the render loop and renders one frame per step, logging the cells
+//! that changed in every step to the console. This helps with finding the
+//! frames that cause flicker or that are not diffed as expected.
+//!
+//! ```rust no_run
+//! use ratzilla::debugger;
+//!
+//! debugger::pause().unwrap();
+//! ```
+//!
+//! While paused, a step is taken with the step key (<kbd>F10</kbd> by
+//! default), by calling [`step`], or from the developer tools of the browser:
+//!
+//! ```js
+//! ratzillaStep();   // Renders the next frame.
+//! ratzillaResume(); // Resumes the render loop.
+//! ```
+
+use std::cell::{Cell, RefCell};
+
+use ratatui::buffer::Buffer;
+use web_sys::{
+    console,
+    js_sys::Reflect,
+    wasm_bindgen::{prelude::Closure, JsValue},
+    window,
+};
+
+use crate::{
+    error::Error,
+    event::{self, Event, KeyBinding, KeyCode},
+};
+
+/// Largest number of changed cells listed in a step.
+const MAX_LISTED_CELLS: usize = 100;
+
+thread_local! {
+    /// Whether the render loop is paused.
+    static PAUSED: Cell<bool> = const { Cell::new(false) };
+    /// Number of steps requested but not rendered yet.
+    static PENDING_STEPS: Cell<u32> = const { Cell::new(0) };
+    /// Schedules the next frame of the paused render loop.
+    static PARKED: RefCell<Option<Box<dyn FnOnce()>>> = const { RefCell::new(None) };
+    /// Key that takes a step while paused.
+    static STEP_KEY: RefCell<Option<KeyBinding>> = RefCell::new(Some(KeyBinding::new(KeyCode::F(10))));
+    /// Buffer of the last step.
+    static LAST_BUFFER: RefCell<Option<Buffer>> = const { RefCell::new(None) };
+    /// Number of steps taken since the render loop was paused.
+    static STEPS: Cell<u64> = const { Cell::new(0) };
+    /// Whether the key filter and the global functions are added.
+    static INSTALLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Pauses the render loop after the current frame.
+pub fn pause() -> Result<(), Error> {
+    install()?;
+    if !PAUSED.replace(true) {
+        STEPS.set(0);
+        LAST_BUFFER.with(|last| last.replace(None));
+        console::info_1(
+            &"ratzilla: rendering paused, press the step key or call `ratzillaStep()`".into(),
+        );
+    }
+    Ok(())
+}
+
+/// Resumes the render loop.
+pub fn resume() {
+    if PAUSED.replace(false) {
+        PENDING_STEPS.set(0);
+        LAST_BUFFER.with(|last| last.replace(None));
+        wake();
+    }
+}
+
+/// Renders the next frame of the paused render loop.
+pub fn step() {
+    if PAUSED.get() {
+        PENDING_STEPS.set(PENDING_STEPS.get() + 1);
+        wake();
+    }
+}
+
+/// Returns `true` if the render loop is paused.
+pub fn is_paused() -> bool {
+    PAUSED.get()
+}
+
+/// Sets the key that takes a step while the render loop is paused, or `None`
+/// to only take steps with [`step`].
+///
+/// The key events of the step key are not passed to the handlers while
+/// paused. The default is <kbd>F10</kbd>.
+pub fn set_step_key(key: Option<KeyBinding>) {
+    STEP_KEY.with(|current| current.replace(key));
+}
+
+/// Adds the key filter and the global functions, unless they are already
+/// added.
+fn install() -> Result<(), Error> {
+    if INSTALLED.get() {
+        return Ok(());
+    }
+    event::add_key_listener()?;
+    event::add_event_filter(|event| match &event {
+        Event::Key(key)
+            if PAUSED.get()
+                && STEP_KEY.with(|binding| {
+                    binding
+                        .borrow()
+                        .as_ref()
+                        .is_some_and(|binding| binding.matches(key))
+                }) =>
+        {
+            step();
+            None
+        }
+        _ => Some(event),
+    });
+    let window = window().ok_or(Error::UnableToRetrieveWindow)?;
+    let step = Closure::<dyn FnMut()>::new(step);
+    Reflect::set(&window, &JsValue::from_str("ratzillaStep"), step.as_ref())?;
+    step.forget();
+    let resume = Closure::<dyn FnMut()>::new(resume);
+    Reflect::set(
+        &window,
+        &JsValue::from_str("ratzillaResume"),
+        resume.as_ref(),
+    )?;
+    resume.forget();
+    INSTALLED.set(true);
+    Ok(())
+}
+
+/// Runs the parked schedule of the render loop, if any.
+fn wake() {
+    if let Some(schedule) = PARKED.with(|parked| parked.borrow_mut().take()) {
+        schedule();
+    }
+}
+
+/// Returns `true` if the render loop must skip the frame, i.e. it is paused
+/// and no step is pending.
+///
+/// The schedule of the next frame is then parked until the next step.
+pub(crate) fn park<F>(schedule: &F) -> bool
+where
+    F: FnOnce() + Clone + 'static,
+{
+    if !PAUSED.get() {
+        return false;
+    }
+    match PENDING_STEPS.get() {
+        0 => {
+            PARKED.with(|parked| *parked.borrow_mut() = Some(Box::new(schedule.clone())));
+            true
+        }
+        steps => {
+            PENDING_STEPS.set(steps - 1);
+            false
+        }
+    }
+}
+
+/// Logs the cells of a stepped frame that changed since the last step.
+///
+/// This is called with the final buffer of every frame, right before it is
+/// flushed.
+pub(crate) fn record(buffer: &Buffer) {
+    if !PAUSED.get() {
+        return;
+    }
+    let step = STEPS.get() + 1;
+    STEPS.set(step);
+    let previous = LAST_BUFFER.with(|last| last.replace(Some(buffer.clone())));
+    let changes = match previous.as_ref().filter(|last| last.area == buffer.area) {
+        Some(previous) => previous
+            .diff(buffer)
+            .into_iter()
+            .map(|(x, y, cell)| (x, y, previous.cell((x, y)).cloned(), cell.clone()))
+            .collect::<Vec<_>>(),
+        None => buffer
+            .content
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let (x, y) = buffer.pos_of(i);
+                (x, y, None, cell.clone())
+            })
+            .collect(),
+    };
+    let title = if previous.is_some() {
+        format!("ratzilla: step {step}, {} cell(s) changed", changes.len())
+    } else {
+        format!(
+            "ratzilla: step {step}, full frame of {} cell(s)",
+            changes.len()
+        )
+    };
+    console::group_collapsed_1(&title.into());
+    for (x, y, old, new) in changes.iter().take(MAX_LISTED_CELLS) {
+        let line = match old {
+            Some(old) => format!(
+                "({x}, {y}) {:?} {:?} -> {:?} {:?}",
+                old.symbol(),
+                old.style(),
+                new.symbol(),
+                new.style()
+            ),
+            None => format!("({x}, {y}) {:?} {:?}", new.symbol(), new.style()),
+        };
+        console::log_1(&line.into());
+    }
+    if changes.len() > MAX_LISTED_CELLS {
+        console::log_1(&format!("... and {} more", changes.len() - MAX_LISTED_CELLS).into());
+    }
+    console::group_end();
+}
@@ -0,0 +1,374 @@
+//! ## Effects
+//!
+//! Purely visual effects that are applied on top of the rendered grid.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::Color,
+};
+
+use crate::{
+    backend::utils::{ansi_to_rgb, blend_colors},
+    event::{self, EffectEvent, Event},
+    metadata::{self, RegionKind},
+    utils::now,
+};
+
+thread_local! {
+    /// Global color filter.
+    static COLOR_FILTER: Cell<Option<ColorFilter>> = const { Cell::new(None) };
+    /// Start times of the timed effects by name and whether they are finished.
+    static TIMERS: RefCell<HashMap<String, (f64, bool)>> = RefCell::new(HashMap::new());
+}
+
+/// Direction of a sweep effect.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Direction {
+    /// From the left edge to the right edge.
+    LeftToRight,
+    /// From the right edge to the left edge.
+    RightToLeft,
+    /// From the top edge to the bottom edge.
+    TopToBottom,
+    /// From the bottom edge to the top edge.
+    BottomToTop,
+}
+
+/// Kind of a [`CellEffect`].
+///
+/// [`CellEffect`]: crate::widgets::CellEffect
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EffectKind {
+    /// Fades the colors in from the given color.
+    FadeIn(Color),
+    /// Fades the colors out to the given color.
+    FadeOut(Color),
+    /// Reveals the cells with a sweep in the given direction.
+    SweepIn(Direction),
+    /// Hides the cells with a sweep in the given direction.
+    SweepOut(Direction),
+    /// Hides the cells in a random order.
+    Dissolve,
+    /// Reveals the cells in a random order.
+    Coalesce,
+}
+
+impl EffectKind {
+    /// Applies the effect to the cells of the area at the given progress,
+    /// between `0.0` and `1.0`.
+    fn apply(self, buffer: &mut Buffer, area: Rect, progress: f64) {
+        for position in area.positions() {
+            let Some(cell) = buffer.cell_mut(position) else {
+                continue;
+            };
+            match self {
+                Self::FadeIn(from) => {
+                    cell.fg = blend_colors(from, cell.fg, progress);
+                    cell.bg = blend_colors(from, cell.bg, progress);
+                }
+                Self::FadeOut(to) => {
+                    cell.fg = blend_colors(cell.fg, to, progress);
+                    cell.bg = blend_colors(cell.bg, to, progress);
+                }
+                Self::SweepIn(direction) => {
+                    if sweep_threshold(direction, area, position) >= progress {
+                        cell.set_symbol(" ");
+                    }
+                }
+                Self::SweepOut(direction) => {
+                    if sweep_threshold(direction, area, position) < progress {
+                        cell.set_symbol(" ");
+                    }
+                }
+                Self::Dissolve => {
+                    if noise(position) < progress {
+                        cell.set_symbol(" ");
+                    }
+                }
+                Self::Coalesce => {
+                    if noise(position) >= progress {
+                        cell.set_symbol(" ");
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the progress at which a sweep reaches the given position.
+fn sweep_threshold(direction: Direction, area: Rect, position: Position) -> f64 {
+    let (offset, len) = match direction {
+        Direction::LeftToRight => (position.x - area.x, area.width),
+        Direction::RightToLeft => (area.right() - 1 - position.x, area.width),
+        Direction::TopToBottom => (position.y - area.y, area.height),
+        Direction::BottomToTop => (area.bottom() - 1 - position.y, area.height),
+    };
+    f64::from(offset) / f64::from(len.max(1))
+}
+
+/// Returns a stable pseudo-random value between `0.0` and `1.0` for the
+/// position.
+fn noise(position: Position) -> f64 {
+    let hash = (u32::from(position.x).wrapping_mul(73_856_093))
+        ^ (u32::from(position.y).wrapping_mul(19_349_663));
+    f64::from(hash % 1000) / 1000.0
+}
+
+/// Applies the timed effects (typewriters and cell effects) of the regions
+/// registered during the current frame.
+///
+/// An effect starts when its region first appears and is reset when its
+/// region disappears. An [`EffectEvent`] is emitted once it is finished.
+pub(crate) fn apply_timed_effects(buffer: &mut Buffer) {
+    let time = now();
+    let mut finished = Vec::new();
+    TIMERS.with(|timers| {
+        let mut timers = timers.borrow_mut();
+        let mut active = Vec::new();
+        for region in metadata::regions() {
+            let area = region.area.intersection(buffer.area);
+            let (name, done) = match region.kind {
+                RegionKind::Typewriter { name, speed } => {
+                    let (start, _) = timers.entry(name.clone()).or_insert((time, false));
+                    let revealed = ((time - *start) / 1000.0 * speed).max(0.0) as usize;
+                    for position in area.positions().skip(revealed) {
+                        if let Some(cell) = buffer.cell_mut(position) {
+                            cell.set_symbol(" ");
+                        }
+                    }
+                    (name, revealed >= area.area() as usize)
+                }
+                RegionKind::Effect {
+                    name,
+                    kind,
+                    duration,
+                } => {
+                    let (start, _) = timers.entry(name.clone()).or_insert((time, false));
+                    let progress = if duration > 0.0 {
+                        ((time - *start) / duration).clamp(0.0, 1.0)
+                    } else {
+                        1.0
+                    };
+                    kind.apply(buffer, area, progress);
+                    (name, progress >= 1.0)
+                }
+                _ => continue,
+            };
+            if let Some((_, finished_before)) = timers.get_mut(&name) {
+                if done && !*finished_before {
+                    *finished_before = true;
+                    finished.push(name.clone());
+                }
+            }
+            active.push(name);
+        }
+        timers.retain(|name, _| active.contains(name));
+    });
+    for name in finished {
+        event::dispatch(Event::Effect(EffectEvent { name }));
+    }
+}
+
+/// A global color filter.
+///
+/// Color filters transform the foreground and background colors of every
+/// cell right before the frame is flushed, so they work with all backends.
+/// The colorblindness simulations help with checking whether a palette is
+/// still distinguishable for people with color vision deficiencies.
+///
+/// Cells with the default ([`Color::Reset`]) colors are not affected.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ColorFilter {
+    /// Sepia tone.
+    Sepia,
+    /// Shades of gray.
+    Grayscale,
+    /// Inverted colors.
+    Invert,
+    /// Simulation of protanopia (red blindness).
+    Protanopia,
+    /// Simulation of deuteranopia (green blindness).
+    Deuteranopia,
+    /// Simulation of tritanopia (blue blindness).
+    Tritanopia,
+}
+
+impl ColorFilter {
+    /// Returns the color transformation matrix of the filter.
+    ///
+    /// The colorblindness matrices are from Machado et al. (2009) with a
+    /// severity of 1.0.
+    fn matrix(self) -> Option<[[f32; 3]; 3]> {
+        match self {
+            Self::Sepia => Some([
+                [0.393, 0.769, 0.189],
+                [0.349, 0.686, 0.168],
+                [0.272, 0.534, 0.131],
+            ]),
+            Self::Grayscale => Some([
+                [0.2126, 0.7152, 0.0722],
+                [0.2126, 0.7152, 0.0722],
+                [0.2126, 0.7152, 0.0722],
+            ]),
+            Self::Invert => None,
+            Self::Protanopia => Some([
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ]),
+            Self::Deuteranopia => Some([
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ]),
+            Self::Tritanopia => Some([
+                [1.255_528, -0.076_749, -0.178_779],
+                [-0.078_411, 0.930_809, 0.147_602],
+                [0.004_733, 0.691_367, 0.303_900],
+            ]),
+        }
+    }
+
+    /// Applies the filter to a color.
+    pub fn apply(self, color: Color) -> Color {
+        let Some((r, g, b)) = ansi_to_rgb(color) else {
+            return color;
+        };
+        let Some(matrix) = self.matrix() else {
+            return Color::Rgb(255 - r, 255 - g, 255 - b);
+        };
+        let input = [r as f32, g as f32, b as f32];
+        let [r, g, b] = matrix.map(|row| {
+            let value: f32 = row.iter().zip(input).map(|(m, c)| m * c).sum();
+            value.round().clamp(0.0, 255.0) as u8
+        });
+        Color::Rgb(r, g, b)
+    }
+}
+
+/// Sets the global color filter.
+///
+/// Pass `None` to remove the filter.
+pub fn set_color_filter(filter: Option<ColorFilter>) {
+    COLOR_FILTER.set(filter);
+}
+
+/// Applies the global color filter to the buffer.
+pub(crate) fn apply_color_filter(buffer: &mut Buffer) {
+    let Some(filter) = COLOR_FILTER.get() else {
+        return;
+    };
+    for cell in buffer.content.iter_mut() {
+        cell.fg = filter.apply(cell.fg);
+        cell.bg = filter.apply(cell.bg);
+    }
+}
+
+/// A retro CRT monitor effect.
+///
+/// It is implemented with a stylesheet that is scoped to the grid of a
+/// [`DomBackend`] instance, so it does not require any changes to the
+/// rendered content.
+///
+/// ```rust no_run
+/// use ratzilla::{effects::CrtEffect, DomBackend};
+///
+/// let mut backend = DomBackend::new().unwrap();
+/// backend.set_crt_effect(Some(CrtEffect::new().intensity(0.8).curvature(false)));
+/// ```
+///
+/// [`DomBackend`]: crate::DomBackend
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrtEffect {
+    /// Intensity of the effect, between `0.0` and `1.0`.
+    intensity: f32,
+    /// Whether to draw scanlines.
+    scanlines: bool,
+    /// Whether to make the text glow.
+    bloom: bool,
+    /// Whether to round the corners and darken the edges of the screen.
+    curvature: bool,
+}
+
+impl Default for CrtEffect {
+    fn default() -> Self {
+        Self {
+            intensity: 0.5,
+            scanlines: true,
+            bloom: true,
+            curvature: true,
+        }
+    }
+}
+
+impl CrtEffect {
+    /// Constructs a new [`CrtEffect`] with all the parts enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the intensity of the effect.
+    ///
+    /// The value is clamped between `0.0` and `1.0`.
+    pub fn intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets whether to draw scanlines.
+    pub fn scanlines(mut self, scanlines: bool) -> Self {
+        self.scanlines = scanlines;
+        self
+    }
+
+    /// Sets whether to make the text glow.
+    pub fn bloom(mut self, bloom: bool) -> Self {
+        self.bloom = bloom;
+        self
+    }
+
+    /// Sets whether to round the corners and darken the edges of the screen.
+    pub fn curvature(mut self, curvature: bool) -> Self {
+        self.curvature = curvature;
+        self
+    }
+
+    /// Returns the stylesheet that applies the effect to the element with the
+    /// given id.
+    pub(crate) fn to_css(self, id: &str) -> String {
+        let intensity = self.intensity;
+        let mut css = format!("#{id} {{ position: relative; }}\n");
+        if self.bloom {
+            css.push_str(&format!(
+                "#{id} > * {{ text-shadow: 0 0 {:.2}px currentColor; }}\n",
+                4.0 * intensity
+            ));
+        }
+        if self.curvature {
+            css.push_str(&format!(
+                "#{id} {{ border-radius: {:.2}em; overflow: hidden; \
+                box-shadow: inset 0 0 {:.2}em rgba(0, 0, 0, {:.2}); }}\n",
+                2.0 * intensity,
+                4.0 * intensity,
+                0.9 * intensity
+            ));
+        }
+        if self.scanlines {
+            css.push_str(&format!(
+                "#{id}::after {{ content: \"\"; position: absolute; inset: 0; \
+                pointer-events: none; background: repeating-linear-gradient(\
+                rgba(0, 0, 0, {:.2}) 0px, rgba(0, 0, 0, {:.2}) 1px, \
+                transparent 1px, transparent 3px); }}\n",
+                0.5 * intensity,
+                0.5 * intensity
+            ));
+        }
+        css
+    }
+}
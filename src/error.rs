@@ -1,4 +1,7 @@
-use web_sys::wasm_bindgen;
+use web_sys::{
+    wasm_bindgen::{self, JsCast},
+    DomException,
+};
 
 /// Custom error implementation.
 #[derive(thiserror::Error, Debug)]
@@ -28,11 +31,105 @@ pub enum Error {
     #[error("Unable to retrieve canvas context")]
     UnableToRetrieveCanvasContext,
 
+    /// Element not found.
+    ///
+    /// This error occurs when no element of the document matches a selector.
+    #[error("No element matches the selector `{0}`")]
+    ElementNotFound(String),
+
+    /// Container is detached.
+    ///
+    /// This error occurs when the element that the backend renders into is
+    /// removed from the document.
+    #[error("Container is detached from the document")]
+    DetachedContainer,
+
     /// JS value error.
     #[error("JS value error: {0:?}")]
     JsValue(wasm_bindgen::JsValue),
 }
 
+impl Error {
+    /// Returns the kind of this error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::DetachedContainer => ErrorKind::DetachedContainer,
+            Self::JsValue(value) => match value.dyn_ref::<DomException>() {
+                Some(exception) if exception.name() == "QuotaExceededError" => {
+                    ErrorKind::QuotaExceeded
+                }
+                Some(_) => ErrorKind::DomException,
+                None => ErrorKind::Other,
+            },
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// Kind of an [`Error`].
+///
+/// This is used for deciding how to recover from a failure.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ErrorKind {
+    /// A DOM operation threw an exception.
+    DomException,
+    /// A storage or memory quota was exceeded.
+    QuotaExceeded,
+    /// The container element was removed from the document.
+    DetachedContainer,
+    /// Any other error.
+    Other,
+}
+
+/// A thread-safe summary of an [`Error`].
+///
+/// [`Error`] cannot be sent across threads since it may contain a
+/// [`wasm_bindgen::JsValue`]. This report is what ends up inside the
+/// [`std::io::Error`] returned by the backends, so that the kind of the error
+/// can still be recovered with [`ErrorReport::from_io_error`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ErrorReport {
+    /// Kind of the error.
+    pub kind: ErrorKind,
+    /// Error message.
+    pub message: String,
+}
+
+impl ErrorReport {
+    /// Extracts the report from an [`std::io::Error`].
+    ///
+    /// Errors that did not originate from an [`Error`] are reported as
+    /// [`ErrorKind::Other`].
+    pub fn from_io_error(error: &std::io::Error) -> Self {
+        error
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<Self>())
+            .cloned()
+            .unwrap_or_else(|| Self {
+                kind: ErrorKind::Other,
+                message: error.to_string(),
+            })
+    }
+}
+
+impl std::fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ErrorReport {}
+
+/// Convert [`Error`] to [`ErrorReport`].
+impl From<&Error> for ErrorReport {
+    fn from(error: &Error) -> Self {
+        Self {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
 /// Convert [`wasm_bindgen::JsValue`] to [`Error`].
 impl From<wasm_bindgen::JsValue> for Error {
     fn from(value: wasm_bindgen::JsValue) -> Self {
@@ -43,6 +140,6 @@ impl From<wasm_bindgen::JsValue> for Error {
 /// Convert [`Error`] to [`std::io::Error`].
 impl From<Error> for std::io::Error {
     fn from(error: Error) -> Self {
-        std::io::Error::new(std::io::ErrorKind::Other, error.to_string())
+        std::io::Error::other(ErrorReport::from(&error))
     }
 }
@@ -0,0 +1,246 @@
+//! ## Error overlay
+//!
+//! When rendering fails with a recoverable error (e.g. a DOM exception or a
+//! detached container), the render loop stops and an error panel is shown on
+//! top of the page instead of leaving a half-updated grid behind.
+//!
+//! A panic in the render callback does not stop the render loop: the frame is
+//! discarded, the last good frame stays on the screen and an error banner is
+//! shown until a frame renders successfully again. This requires panics to
+//! unwind, which is not the case for the default `panic = "abort"` strategy
+//! of WebAssembly targets.
+//!
+//! The panel can be customized with [`set_error_view`]:
+//!
+//! ```rust no_run
+//! use ratzilla::{
+//!     error::ErrorKind,
+//!     error_overlay::{set_error_view, ErrorView},
+//! };
+//!
+//! set_error_view(|report| {
+//!     let view = ErrorView::new(report).title("Something went wrong");
+//!     match report.kind {
+//!         ErrorKind::QuotaExceeded => view.retry(false),
+//!         _ => view,
+//!     }
+//! });
+//! ```
+
+use std::cell::{Cell, RefCell};
+
+use web_sys::{
+    wasm_bindgen::{prelude::Closure, JsCast},
+    window, Document, Element,
+};
+
+use crate::error::{Error, ErrorReport};
+
+/// Id of the error overlay element.
+const OVERLAY_ID: &str = "ratzilla-error";
+
+/// Style of the error overlay element.
+const OVERLAY_STYLE: &str = "position: fixed; inset: 0; display: flex; \
+    flex-direction: column; align-items: center; justify-content: center; \
+    gap: 1em; background-color: rgba(0, 0, 0, 0.85); color: rgb(255, 255, 255); \
+    font-family: monospace; z-index: 2147483647;";
+
+/// Id of the error banner element.
+const BANNER_ID: &str = "ratzilla-error-banner";
+
+/// Style of the error banner element.
+const BANNER_STYLE: &str = "position: fixed; top: 0; left: 0; right: 0; \
+    padding: 0.25em 0.5em; background-color: rgb(170, 0, 0); color: rgb(255, 255, 255); \
+    font-family: monospace; white-space: pre-wrap; z-index: 2147483647;";
+
+/// Type of the error view hook.
+type ErrorViewHook = Box<dyn Fn(&ErrorReport) -> ErrorView>;
+
+thread_local! {
+    /// Hook for building the error view.
+    static ERROR_VIEW_HOOK: RefCell<Option<ErrorViewHook>> = const { RefCell::new(None) };
+    /// Whether the error banner is shown.
+    static BANNER_SHOWN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Contents of the error overlay.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ErrorView {
+    /// Title of the panel.
+    title: String,
+    /// Message of the panel.
+    message: String,
+    /// Whether to show the retry action.
+    retry: bool,
+    /// Whether to show the reload action.
+    reload: bool,
+}
+
+impl ErrorView {
+    /// Constructs the default [`ErrorView`] for the given report.
+    pub fn new(report: &ErrorReport) -> Self {
+        Self {
+            title: "Rendering failed".to_string(),
+            message: report.message.clone(),
+            retry: true,
+            reload: true,
+        }
+    }
+
+    /// Sets the title of the panel.
+    pub fn title<T: Into<String>>(mut self, title: T) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the message of the panel.
+    pub fn message<T: Into<String>>(mut self, message: T) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Sets whether the retry action is shown.
+    ///
+    /// Retrying resumes the render loop and redraws the whole screen.
+    pub fn retry(mut self, retry: bool) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets whether the reload action is shown.
+    ///
+    /// Reloading reloads the current page.
+    pub fn reload(mut self, reload: bool) -> Self {
+        self.reload = reload;
+        self
+    }
+}
+
+/// Sets the hook that builds the error view for a failure.
+///
+/// By default, [`ErrorView::new`] is used.
+pub fn set_error_view<F>(hook: F)
+where
+    F: Fn(&ErrorReport) -> ErrorView + 'static,
+{
+    ERROR_VIEW_HOOK.with(|h| *h.borrow_mut() = Some(Box::new(hook)));
+}
+
+/// Shows the error overlay for the given report.
+///
+/// `on_retry` is called after the overlay is dismissed via the retry action.
+pub(crate) fn show<F>(report: &ErrorReport, on_retry: F) -> Result<(), Error>
+where
+    F: FnMut() + 'static,
+{
+    let view = ERROR_VIEW_HOOK.with(|hook| match hook.borrow().as_ref() {
+        Some(hook) => hook(report),
+        None => ErrorView::new(report),
+    });
+    let document = window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .document()
+        .ok_or(Error::UnableToRetrieveDocument)?;
+    hide(&document);
+
+    let overlay = document.create_element("div")?;
+    overlay.set_attribute("id", OVERLAY_ID)?;
+    overlay.set_attribute("role", "alertdialog")?;
+    overlay.set_attribute("style", OVERLAY_STYLE)?;
+
+    let title = document.create_element("h2")?;
+    title.set_text_content(Some(&view.title));
+    overlay.append_child(&title)?;
+
+    let message = document.create_element("pre")?;
+    message.set_text_content(Some(&view.message));
+    overlay.append_child(&message)?;
+
+    let actions = document.create_element("div")?;
+    if view.retry {
+        let mut on_retry = on_retry;
+        let button = create_button(&document, "Retry", move || {
+            if let Some(document) = window().and_then(|w| w.document()) {
+                hide(&document);
+            }
+            on_retry();
+        })?;
+        actions.append_child(&button)?;
+    }
+    if view.reload {
+        let button = create_button(&document, "Reload", || {
+            if let Some(window) = window() {
+                let _ = window.location().reload();
+            }
+        })?;
+        actions.append_child(&button)?;
+    }
+    overlay.append_child(&actions)?;
+
+    document
+        .body()
+        .ok_or(Error::UnableToRetrieveBody)?
+        .append_child(&overlay)?;
+    Ok(())
+}
+
+/// Removes the error overlay if it is shown.
+fn hide(document: &Document) {
+    if let Some(overlay) = document.get_element_by_id(OVERLAY_ID) {
+        overlay.remove();
+    }
+}
+
+/// Shows the error banner with the given message.
+///
+/// Unlike the overlay, the banner leaves the last good frame visible and the
+/// render loop running. An existing banner is replaced.
+pub(crate) fn show_banner(message: &str) -> Result<(), Error> {
+    let document = window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .document()
+        .ok_or(Error::UnableToRetrieveDocument)?;
+    let banner = match document.get_element_by_id(BANNER_ID) {
+        Some(banner) => banner,
+        None => {
+            let banner = document.create_element("div")?;
+            banner.set_attribute("id", BANNER_ID)?;
+            banner.set_attribute("role", "alert")?;
+            banner.set_attribute("style", BANNER_STYLE)?;
+            document
+                .body()
+                .ok_or(Error::UnableToRetrieveBody)?
+                .append_child(&banner)?;
+            banner
+        }
+    };
+    banner.set_text_content(Some(message));
+    BANNER_SHOWN.with(|shown| shown.set(true));
+    Ok(())
+}
+
+/// Removes the error banner if it is shown.
+pub(crate) fn hide_banner() {
+    if !BANNER_SHOWN.with(|shown| shown.replace(false)) {
+        return;
+    }
+    if let Some(banner) = window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id(BANNER_ID))
+    {
+        banner.remove();
+    }
+}
+
+/// Creates a `<button>` element that calls the given closure when clicked.
+fn create_button<F>(document: &Document, label: &str, on_click: F) -> Result<Element, Error>
+where
+    F: FnMut() + 'static,
+{
+    let button = document.create_element("button")?;
+    button.set_text_content(Some(label));
+    let closure = Closure::<dyn FnMut()>::new(on_click);
+    button.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    Ok(button)
+}
@@ -1,8 +1,1576 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    rc::Rc,
+    thread::LocalKey,
+};
+
+use ratatui::layout::{Position, Size};
+use web_sys::{
+    wasm_bindgen::{prelude::Closure, JsCast},
+    window, ClipboardEvent, CompositionEvent, Element, EventTarget, PointerEvent, WheelEvent,
+};
+
+use crate::{
+    backend::font::FontMetrics, error::Error, keyboard, permalink, render, utils, web_warn,
+};
+
+/// An event.
+///
+/// All events go through the filters added with
+/// [`WebRenderer::add_event_filter`] before they reach the handlers.
+///
+/// [`WebRenderer::add_event_filter`]: crate::WebRenderer::add_event_filter
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Event {
+    /// A key event.
+    Key(KeyEvent),
+    /// A mouse event.
+    Mouse(MouseEvent),
+    /// The page gained the focus.
+    FocusGained,
+    /// The page lost the focus.
+    FocusLost,
+    /// Text was pasted.
+    Paste(String),
+    /// The grid was resized to the given number of columns and rows.
+    Resize(Size),
+    /// A clipboard copy event.
+    Copy(CopyEvent),
+    /// An effect event.
+    Effect(EffectEvent),
+    /// A multi-touch event, see [`WebRenderer::set_multi_touch`].
+    ///
+    /// [`WebRenderer::set_multi_touch`]: crate::WebRenderer::set_multi_touch
+    Touch(TouchEvent),
+    /// A touch gesture, see [`WebRenderer::set_multi_touch`].
+    ///
+    /// [`WebRenderer::set_multi_touch`]: crate::WebRenderer::set_multi_touch
+    Gesture(GestureEvent),
+    /// The page was opened with a permalink, or the URL changed to another
+    /// one. See the [`permalink`] module.
+    ///
+    /// [`permalink`]: crate::permalink
+    StateFromUrl(String),
+}
+
+/// Type of the event filters.
+type EventFilter = Box<dyn FnMut(Event) -> Option<Event>>;
+
+/// Type of the event handlers.
+type EventHandler = Box<dyn FnMut(&Event)>;
+
+thread_local! {
+    /// Event filters, in the order they are applied.
+    static FILTERS: RefCell<Vec<EventFilter>> = const { RefCell::new(Vec::new()) };
+    /// Event handlers.
+    static HANDLERS: RefCell<Vec<EventHandler>> = const { RefCell::new(Vec::new()) };
+    /// Whether an event is being dispatched.
+    static DISPATCHING: Cell<bool> = const { Cell::new(false) };
+    /// Events raised while an event is dispatched, e.g. by a handler.
+    static DEFERRED: RefCell<VecDeque<Event>> = const { RefCell::new(VecDeque::new()) };
+    /// How the code of the key events is determined.
+    static KEY_POLICY: Cell<KeyPolicy> = const { Cell::new(KeyPolicy::Layout) };
+    /// How the auto-repeat of the keys is handled.
+    static KEY_REPEAT: Cell<KeyRepeat> = const { Cell::new(KeyRepeat::new()) };
+    /// Key repeated at a custom rate, `None` if no key is held down.
+    static REPEATER: RefCell<Option<Repeater>> = const { RefCell::new(None) };
+    /// Whether the `keydown` listener is added to the document.
+    static KEY_LISTENER_ADDED: Cell<bool> = const { Cell::new(false) };
+    /// Whether the terminal has the keyboard focus.
+    static KEYBOARD_FOCUS: Cell<bool> = const { Cell::new(true) };
+    /// Buffering of the input events, `None` if they are dispatched right
+    /// away.
+    static BUFFERING: Cell<Option<InputBuffering>> = const { Cell::new(None) };
+    /// Input events waiting for the next frame, along with their timestamps.
+    static QUEUE: RefCell<VecDeque<(Event, f64)>> = const { RefCell::new(VecDeque::new()) };
+    /// Last mouse movement waiting for the next frame while the quality is
+    /// reduced, along with its timestamp.
+    static PENDING_MOVE: RefCell<Option<(Event, f64)>> = const { RefCell::new(None) };
+    /// Timing of the input event being dispatched.
+    static TIMING: Cell<Option<EventTiming>> = const { Cell::new(None) };
+    /// Timestamp of the oldest input event dispatched since the last flush.
+    static PENDING_INPUT: Cell<Option<f64>> = const { Cell::new(None) };
+    /// Latency of the last flushed input in milliseconds.
+    static LATENCY: Cell<Option<f64>> = const { Cell::new(None) };
+    /// Distance in pixels that the pointer moves before a press becomes a
+    /// drag.
+    static DRAG_THRESHOLD: Cell<f64> = const { Cell::new(4.0) };
+    /// Shortest touch in milliseconds that is a long press rather than a tap.
+    static LONG_PRESS: Cell<f64> = const { Cell::new(500.0) };
+    /// Client coordinates where the mouse button was last pressed.
+    static PRESSED_AT: Cell<Option<(f64, f64)>> = const { Cell::new(None) };
+    /// Whether the pointer moved further than the drag threshold since the
+    /// button was pressed.
+    static DRAGGED: Cell<bool> = const { Cell::new(false) };
+    /// Unit of the distance of the scroll events.
+    static WHEEL_MODE: Cell<WheelMode> = const { Cell::new(WheelMode::Lines) };
+    /// Horizontal and vertical distances in pixels scrolled since the last
+    /// scroll events.
+    static SCROLLED: Cell<(f64, f64)> = const { Cell::new((0.0, 0.0)) };
+    /// Whether the multi-touch and gesture events are dispatched.
+    static MULTI_TOUCH: Cell<bool> = const { Cell::new(false) };
+    /// Listeners of the multi-touch and gesture events of every target.
+    static MULTI_TOUCH_LISTENERS: RefCell<Vec<MultiTouchListeners>> = const { RefCell::new(Vec::new()) };
+    /// State of the current two-finger gesture.
+    static GESTURE: Cell<Option<GestureState>> = const { Cell::new(None) };
+    /// Whether the focus and paste listeners are added.
+    static PAGE_LISTENERS_ADDED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Adds an event filter.
+///
+/// Filters are applied in the order they are added. A filter added while an
+/// event is dispatched is applied from the next event on.
+pub(crate) fn add_event_filter<F>(filter: F)
+where
+    F: FnMut(Event) -> Option<Event> + 'static,
+{
+    FILTERS.with(|filters| filters.borrow_mut().push(Box::new(filter)));
+}
+
+/// Adds an event handler.
+///
+/// A handler added while an event is dispatched is called from the next
+/// event on.
+pub(crate) fn add_event_handler<F>(handler: F)
+where
+    F: FnMut(&Event) + 'static,
+{
+    HANDLERS.with(|handlers| handlers.borrow_mut().push(Box::new(handler)));
+}
+
+/// Passes the event through the filters and then to the handlers.
+///
+/// Events raised while an event is dispatched (e.g. by a handler) are
+/// dispatched after it, since the filters and the handlers are borrowed.
+pub(crate) fn dispatch(event: Event) {
+    if DISPATCHING.get() {
+        DEFERRED.with(|deferred| deferred.borrow_mut().push_back(event));
+        return;
+    }
+    /// Clears the dispatching flag, also if a handler panics.
+    struct Dispatching;
+    impl Drop for Dispatching {
+        fn drop(&mut self) {
+            DISPATCHING.set(false);
+        }
+    }
+    DISPATCHING.set(true);
+    let _dispatching = Dispatching;
+    let mut next = Some(event);
+    while let Some(event) = next {
+        dispatch_now(event);
+        next = DEFERRED.with(|deferred| deferred.borrow_mut().pop_front());
+    }
+}
+
+/// Passes the event through the filters and then to the handlers right away.
+fn dispatch_now(event: Event) {
+    let event = with_taken(&FILTERS, |filters| {
+        filters
+            .iter_mut()
+            .try_fold(event, |event, filter| filter(event))
+    });
+    let Some(event) = event else {
+        return;
+    };
+    // The handlers are likely to change the state of the application.
+    render::request_redraw();
+    with_taken(&HANDLERS, |handlers| {
+        for handler in handlers {
+            handler(&event);
+        }
+    });
+}
+
+/// Calls `f` with the items of a list.
+///
+/// The list is taken out while `f` is called, so that the items can add new
+/// items (e.g. a handler that adds a handler), which are appended afterwards.
+fn with_taken<T, R, F>(list: &'static LocalKey<RefCell<Vec<T>>>, f: F) -> R
+where
+    F: FnOnce(&mut [T]) -> R,
+{
+    /// Puts the items back into the list, also if an item panics.
+    struct Restore<T: 'static> {
+        list: &'static LocalKey<RefCell<Vec<T>>>,
+        items: Vec<T>,
+    }
+    impl<T> Drop for Restore<T> {
+        fn drop(&mut self) {
+            self.list.with(|list| {
+                let mut list = list.borrow_mut();
+                let added = std::mem::replace(&mut *list, std::mem::take(&mut self.items));
+                list.extend(added);
+            });
+        }
+    }
+    let mut restore = Restore {
+        list,
+        items: list.with(|list| std::mem::take(&mut *list.borrow_mut())),
+    };
+    f(&mut restore.items)
+}
+
+/// Timing of an input event.
+///
+/// The times are in milliseconds since the time origin of the page, like
+/// [`performance.now()`].
+///
+/// [`performance.now()`]: https://developer.mozilla.org/en-US/docs/Web/API/Performance/now
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventTiming {
+    /// Time the browser created the event, i.e. the `timeStamp` of the DOM
+    /// event.
+    pub timestamp: f64,
+    /// Time the event was dispatched to the handlers.
+    ///
+    /// This is later than the timestamp if the main thread was busy or the
+    /// input is buffered.
+    pub dispatched_at: f64,
+}
+
+impl EventTiming {
+    /// Returns the time the event waited before being dispatched in
+    /// milliseconds.
+    pub fn queue_delay(&self) -> f64 {
+        (self.dispatched_at - self.timestamp).max(0.0)
+    }
+}
+
+/// Returns the timing of the input event being handled.
+///
+/// This is `Some` while the event filters and handlers run for an input
+/// event (key, mouse, focus and paste events) and `None` otherwise.
+pub fn timing() -> Option<EventTiming> {
+    TIMING.get()
+}
+
+/// Returns the end-to-end latency of the last input in milliseconds.
+///
+/// This is the time from the oldest input event handled in a frame to the
+/// flush of the frame, i.e. until the result is visible in the DOM or on the
+/// canvas. Returns `None` until a frame with input is flushed.
+pub fn input_latency() -> Option<f64> {
+    LATENCY.get()
+}
+
+/// Records the latency of the input dispatched since the last flush.
+///
+/// This is called by the renderer after the frame is flushed.
+pub(crate) fn record_latency() {
+    if let Some(timestamp) = PENDING_INPUT.take() {
+        LATENCY.set(Some((utils::performance_now() - timestamp).max(0.0)));
+    }
+}
+
+/// Dispatches an input event with the given timestamp.
+fn dispatch_input(event: Event, timestamp: f64) {
+    let dispatched_at = utils::performance_now();
+    if PENDING_INPUT
+        .get()
+        .is_none_or(|pending| timestamp < pending)
+    {
+        PENDING_INPUT.set(Some(timestamp));
+    }
+    TIMING.set(Some(EventTiming {
+        timestamp,
+        dispatched_at,
+    }));
+    dispatch(event);
+    TIMING.set(None);
+}
+
+/// What to do with new input events when the queue is full.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum OverflowPolicy {
+    /// Drop the oldest queued event.
+    #[default]
+    DropOldest,
+    /// Drop the new event.
+    DropNewest,
+}
+
+/// Buffering of the input events.
+///
+/// When the render callback is slower than the input arrives (e.g. while a
+/// key is held down), handling every event as it comes builds up latency.
+/// With buffering, the input events (keys, mouse, focus and paste) are
+/// queued and dispatched at the start of the next frame instead, repeated
+/// events are coalesced and the size of the queue is capped.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct InputBuffering {
+    /// Maximum number of queued events.
+    capacity: usize,
+    /// Whether repeated events are coalesced.
+    coalesce: bool,
+    /// What to do with new events when the queue is full.
+    overflow: OverflowPolicy,
+}
+
+impl Default for InputBuffering {
+    fn default() -> Self {
+        Self {
+            capacity: 64,
+            coalesce: true,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+impl InputBuffering {
+    /// Constructs a new [`InputBuffering`].
+    ///
+    /// By default, up to 64 events are queued, repeated events are coalesced
+    /// and the oldest events are dropped when the queue is full.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of queued events.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Sets whether repeated events are coalesced.
+    ///
+    /// A repeated key event (from holding a key down) that is identical to
+    /// the last queued one is dropped, a scroll in the same direction at the
+    /// same cell is added to the last queued one and a mouse move replaces
+    /// the last queued move.
+    pub fn coalesce(mut self, coalesce: bool) -> Self {
+        self.coalesce = coalesce;
+        self
+    }
+
+    /// Sets what to do with new events when the queue is full.
+    pub fn overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+}
+
+/// Sets the buffering of the input events.
+///
+/// Pass `None` to dispatch the events right away, which is the default.
+pub(crate) fn set_input_buffering(buffering: Option<InputBuffering>) {
+    BUFFERING.set(buffering);
+    if buffering.is_none() {
+        dispatch_queued();
+    }
+}
+
+/// Dispatches an input event, or queues it for the next frame if the input
+/// is buffered.
+///
+/// `timestamp` is the `timeStamp` of the DOM event and `repeat` is `true` for
+/// the key events that are generated by holding a key down, only those are
+/// coalesced.
+fn enqueue(event: Event, timestamp: f64, repeat: bool) {
+    let Some(buffering) = BUFFERING.get() else {
+        // Hover effects are redrawn on every movement, keep at most one per
+        // frame while the render loop is overloaded.
+        if render::is_quality_reduced()
+            && matches!(&event, Event::Mouse(mouse) if mouse.kind == MouseEventKind::Moved)
+        {
+            PENDING_MOVE.with(|pending| pending.replace(Some((event, timestamp))));
+            render::request_redraw();
+            return;
+        }
+        dispatch_input(event, timestamp);
+        return;
+    };
+    let mut timestamp = timestamp;
+    QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+        if buffering.coalesce {
+            match (queue.back(), &event) {
+                (Some((Event::Mouse(last), last_timestamp)), Event::Mouse(mouse))
+                    if last.kind == MouseEventKind::Moved
+                        && mouse.kind == MouseEventKind::Moved =>
+                {
+                    // The latency is counted from the first movement.
+                    timestamp = timestamp.min(*last_timestamp);
+                    queue.pop_back();
+                }
+                (Some((Event::Mouse(last), _)), Event::Mouse(mouse))
+                    if matches!(
+                        mouse.kind,
+                        MouseEventKind::ScrollUp
+                            | MouseEventKind::ScrollDown
+                            | MouseEventKind::ScrollLeft
+                            | MouseEventKind::ScrollRight
+                    ) && last.kind == mouse.kind
+                        && last.position() == mouse.position() =>
+                {
+                    if let Some((Event::Mouse(last), _)) = queue.back_mut() {
+                        last.amount = last.amount.saturating_add(mouse.amount);
+                    }
+                    return;
+                }
+                (Some((Event::Key(last), _)), Event::Key(key)) if repeat && last == key => return,
+                _ => {}
+            }
+        }
+        if queue.len() >= buffering.capacity {
+            match buffering.overflow {
+                OverflowPolicy::DropOldest => {
+                    queue.pop_front();
+                }
+                OverflowPolicy::DropNewest => return,
+            }
+        }
+        queue.push_back((event, timestamp));
+    });
+    render::request_redraw();
+}
+
+/// Dispatches the queued input events.
+///
+/// This is called by the renderer at the start of every frame.
+pub(crate) fn dispatch_queued() {
+    if let Some((event, timestamp)) = PENDING_MOVE.with(|pending| pending.take()) {
+        dispatch_input(event, timestamp);
+    }
+    let events = QUEUE.with(|queue| std::mem::take(&mut *queue.borrow_mut()));
+    for (event, timestamp) in events {
+        dispatch_input(event, timestamp);
+    }
+}
+
+/// Adds the `keydown` listener that dispatches key events, unless it is
+/// already added.
+pub(crate) fn add_key_listener() -> Result<(), Error> {
+    if KEY_LISTENER_ADDED.get() {
+        return Ok(());
+    }
+    let document = window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .document()
+        .ok_or(Error::UnableToRetrieveDocument)?;
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::KeyboardEvent| {
+        // The keys typed into the virtual keyboard are always for the
+        // terminal.
+        let is_input = event
+            .target()
+            .and_then(|target| target.dyn_into::<Element>().ok())
+            .is_some_and(|target| keyboard::is_input(&target));
+        if !is_input && !KEYBOARD_FOCUS.get() {
+            return;
+        }
+        let key = event.key();
+        // Dead keys only start a composed character, which is reported by the
+        // next key event. Keys pressed during an IME composition are
+        // reported by the composition events.
+        // Virtual keyboards report the input with the events of the hidden
+        // input element instead.
+        if matches!(key.as_str(), "Dead" | "Process" | "Unidentified") || event.is_composing() {
+            return;
+        }
+        // The key is handled here, keep it from editing the hidden input.
+        if is_input {
+            event.prevent_default();
+        }
+        let (timestamp, repeat) = (event.time_stamp(), event.repeat());
+        let code = KeyCode::from(event.clone());
+        let mode = KEY_REPEAT.get().get(code.class());
+        if repeat && mode != RepeatMode::Browser {
+            return;
+        }
+        stop_repeat();
+        // Named keys (e.g. `CapsLock`) are alphanumeric, other multi-character
+        // values come from a dead key that did not compose with the key, e.g.
+        // `´x` on some platforms. Report the characters one by one.
+        if key.chars().count() > 1 && !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+            let physical_key = event.code();
+            for char in key.chars() {
+                let key_event = KeyEvent::text(char, physical_key.clone());
+                enqueue(Event::Key(key_event), timestamp, repeat);
+            }
+            return;
+        }
+        let key_event = KeyEvent::from(event);
+        if let RepeatMode::Custom { delay, interval } = mode {
+            start_repeat(key_event.clone(), delay, interval);
+        }
+        enqueue(Event::Key(key_event), timestamp, repeat);
+    });
+    document.add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::KeyboardEvent| {
+        let released = REPEATER.with(|repeater| {
+            repeater
+                .borrow()
+                .as_ref()
+                .is_some_and(|repeater| repeater.event.physical_key == event.code())
+        });
+        if released {
+            stop_repeat();
+        }
+    });
+    document.add_event_listener_with_callback("keyup", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    // The key up event is not received when the page loses the focus.
+    let closure = Closure::<dyn FnMut()>::new(stop_repeat);
+    window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .add_event_listener_with_callback("blur", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: CompositionEvent| {
+        if !KEYBOARD_FOCUS.get() {
+            return;
+        }
+        let timestamp = event.time_stamp();
+        for char in event.data().unwrap_or_default().chars() {
+            enqueue(
+                Event::Key(KeyEvent::text(char, String::new())),
+                timestamp,
+                false,
+            );
+        }
+    });
+    document
+        .add_event_listener_with_callback("compositionend", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    KEY_LISTENER_ADDED.set(true);
+    Ok(())
+}
+
+/// Sets whether the terminal has the keyboard focus.
+///
+/// Key events are only dispatched while it has the focus, the keys are left
+/// to the rest of the page otherwise. [`Event::FocusGained`] and
+/// [`Event::FocusLost`] are dispatched when it changes. The terminal has the
+/// focus by default, see [`FocusCapture`] for grabbing it on click instead.
+///
+/// [`FocusCapture`]: crate::backend::dom::FocusCapture
+pub fn set_keyboard_focus(focused: bool) {
+    if KEYBOARD_FOCUS.replace(focused) == focused {
+        return;
+    }
+    let event = if focused {
+        Event::FocusGained
+    } else {
+        Event::FocusLost
+    };
+    if DISPATCHING.get() {
+        // Called from a handler, the event is dispatched after the handlers.
+        dispatch(event);
+    } else {
+        enqueue(event, utils::performance_now(), false);
+    }
+}
+
+/// Returns `true` if the terminal has the keyboard focus, see
+/// [`set_keyboard_focus`].
+pub fn has_keyboard_focus() -> bool {
+    KEYBOARD_FOCUS.get()
+}
+
+/// Adds the listeners that dispatch the focus, paste and permalink events,
+/// unless they are already added.
+///
+/// The state of the permalink that the page was opened with is dispatched
+/// at the start of the next frame, after the handlers are registered.
+pub(crate) fn add_page_listeners() -> Result<(), Error> {
+    if PAGE_LISTENERS_ADDED.get() {
+        return Ok(());
+    }
+    let window = window().ok_or(Error::UnableToRetrieveWindow)?;
+    let document = window.document().ok_or(Error::UnableToRetrieveDocument)?;
+    for (name, event) in [("focus", Event::FocusGained), ("blur", Event::FocusLost)] {
+        let closure = Closure::<dyn FnMut()>::new(move || {
+            enqueue(event.clone(), utils::performance_now(), false)
+        });
+        window.add_event_listener_with_callback(name, closure.as_ref().unchecked_ref())?;
+        closure.forget();
+    }
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: ClipboardEvent| {
+        let Some(text) = event
+            .clipboard_data()
+            .and_then(|data| data.get_data("text/plain").ok())
+        else {
+            return;
+        };
+        enqueue(Event::Paste(text), event.time_stamp(), false);
+    });
+    document.add_event_listener_with_callback("paste", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::Event| {
+        if let Some(state) = permalink::state() {
+            enqueue(Event::StateFromUrl(state), event.time_stamp(), false);
+        }
+    });
+    window.add_event_listener_with_callback("hashchange", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    if let Some(state) = permalink::state() {
+        let timestamp = utils::performance_now();
+        QUEUE.with(|queue| {
+            queue
+                .borrow_mut()
+                .push_back((Event::StateFromUrl(state), timestamp))
+        });
+        render::request_redraw();
+    }
+    PAGE_LISTENERS_ADDED.set(true);
+    Ok(())
+}
+
+/// Adds the listeners that dispatch the mouse and touch events happening on
+/// the target.
+///
+/// `position` maps the element under the pointer and the client coordinates
+/// of the pointer to the position of the cell, events outside of the grid are
+/// ignored.
+///
+/// The mouse and pen input is handled with the pointer events. A tap is
+/// dispatched as a click of the left button and a swipe as scroll events.
+/// `metrics` returns the size of the cells, for converting the scrolled
+/// distance to lines, see [`WheelMode`].
+///
+/// The presses are tracked to tell clicks from drags, see [`is_drag`].
+pub(crate) fn add_mouse_listeners<F, G>(
+    target: &EventTarget,
+    position: F,
+    metrics: G,
+) -> Result<(), Error>
+where
+    F: Fn(&Element, f64, f64) -> Option<Position> + 'static,
+    G: Fn() -> FontMetrics + 'static,
+{
+    let metrics = Rc::new(metrics);
+    let position = Rc::new(position);
+    let mouse_position = {
+        let position = position.clone();
+        move |event: &web_sys::MouseEvent| {
+            let element = event.target()?.dyn_into::<Element>().ok()?;
+            position(
+                &element,
+                f64::from(event.client_x()),
+                f64::from(event.client_y()),
+            )
+        }
+    };
+    let mouse_position = Rc::new(mouse_position);
+    for name in ["pointerdown", "pointerup", "pointermove"] {
+        let position = mouse_position.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: PointerEvent| {
+            // Taps and swipes are handled by the touch listeners.
+            if event.pointer_type() == "touch" {
+                return;
+            }
+            let (x, y) = (f64::from(event.client_x()), f64::from(event.client_y()));
+            if event.type_() == "pointermove" {
+                track_drag(x, y);
+            }
+            let Some(cell) = position(&event) else {
+                return;
+            };
+            let kind = match event.type_().as_str() {
+                "pointerdown" => {
+                    track_press(x, y);
+                    MouseEventKind::Down(event.button().into())
+                }
+                "pointerup" => MouseEventKind::Up(event.button().into()),
+                // Pressing or releasing a button while another one is held
+                // down is reported as a move.
+                _ if event.button() >= 0 => {
+                    if event.buttons() & button_mask(event.button()) != 0 {
+                        MouseEventKind::Down(event.button().into())
+                    } else {
+                        MouseEventKind::Up(event.button().into())
+                    }
+                }
+                _ => MouseEventKind::Moved,
+            };
+            let mut mouse_event = MouseEvent::new(kind, cell, &event);
+            mouse_event.pointer = Pointer::from(&event);
+            enqueue(Event::Mouse(mouse_event), event.time_stamp(), false);
+        });
+        target.add_event_listener_with_callback(name, closure.as_ref().unchecked_ref())?;
+        closure.forget();
+    }
+    let closure = {
+        let metrics = metrics.clone();
+        Closure::<dyn FnMut(_)>::new(move |event: WheelEvent| {
+            let Some(cell) = mouse_position(&event) else {
+                return;
+            };
+            let metrics = metrics();
+            for (axis, delta) in [
+                (Axis::Horizontal, event.delta_x()),
+                (Axis::Vertical, event.delta_y()),
+            ] {
+                // Browsers report the distance in pixels, lines or pages.
+                let distance = match event.delta_mode() {
+                    WheelEvent::DOM_DELTA_LINE => delta * axis.cell_size(metrics),
+                    WheelEvent::DOM_DELTA_PAGE => delta * axis.page_size(metrics),
+                    _ => delta,
+                };
+                if let Some((kind, amount)) = scroll(axis, distance, metrics) {
+                    let mut mouse_event = MouseEvent::new(kind, cell, &event);
+                    mouse_event.amount = amount;
+                    enqueue(Event::Mouse(mouse_event), event.time_stamp(), false);
+                }
+            }
+        })
+    };
+    target.add_event_listener_with_callback("wheel", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    add_touch_listeners(target, position, metrics)
+}
+
+/// Returns the bit of a button in the `buttons` of a DOM mouse event.
+fn button_mask(button: i16) -> u16 {
+    match button {
+        // The middle and right buttons are swapped in the mask.
+        1 => 4,
+        2 => 2,
+        button => 1u16.checked_shl(button as u32).unwrap_or(0),
+    }
+}
+
+/// Unit of the distance of the scroll events, see [`MouseEvent::amount`].
+///
+/// Browsers report the distance of the wheel events in pixels, lines or
+/// pages depending on the browser and the device, e.g. 100 pixels or 3 lines
+/// per notch of a mouse wheel and a few pixels at a time on a touchpad. The
+/// distance is converted to the chosen unit with the size of the cells, and
+/// the distance smaller than the unit is carried over to the next events, so
+/// a touchpad scrolls as smoothly as a wheel.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum WheelMode {
+    /// Rows of the grid, or columns when scrolling horizontally.
+    #[default]
+    Lines,
+    /// Pages, i.e. the height of the grid, or its width when scrolling
+    /// horizontally.
+    Pages,
+    /// Pixels.
+    Pixels,
+}
+
+/// Sets the unit of the distance of the scroll events.
+pub(crate) fn set_wheel_mode(mode: WheelMode) {
+    WHEEL_MODE.set(mode);
+    SCROLLED.set((0.0, 0.0));
+}
+
+/// Axis of a scroll.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Axis {
+    /// Left and right.
+    Horizontal,
+    /// Up and down.
+    Vertical,
+}
+
+impl Axis {
+    /// Returns the size of a cell along the axis in pixels.
+    fn cell_size(self, metrics: FontMetrics) -> f64 {
+        match self {
+            Axis::Horizontal => metrics.cell_width,
+            Axis::Vertical => metrics.cell_height,
+        }
+    }
+
+    /// Returns the size of a page along the axis in pixels.
+    fn page_size(self, metrics: FontMetrics) -> f64 {
+        let cell_size = self.cell_size(metrics);
+        let cells = render::frame_size().map(|size| match self {
+            Axis::Horizontal => size.width,
+            Axis::Vertical => size.height,
+        });
+        match cells {
+            Some(cells) if cells > 0 => f64::from(cells) * cell_size,
+            _ => window()
+                .and_then(|window| match self {
+                    Axis::Horizontal => window.inner_width().ok(),
+                    Axis::Vertical => window.inner_height().ok(),
+                })
+                .and_then(|size| size.as_f64())
+                .unwrap_or(cell_size),
+        }
+    }
+}
+
+/// Adds a scrolled distance in pixels along an axis, positive for scrolling
+/// right or down.
+///
+/// Returns the kind and the amount of the scroll event to dispatch, if the
+/// distance scrolled since the last event reached the unit of the
+/// [`WheelMode`].
+fn scroll(axis: Axis, distance: f64, metrics: FontMetrics) -> Option<(MouseEventKind, u32)> {
+    let (scrolled_x, scrolled_y) = SCROLLED.get();
+    let mut scrolled = match axis {
+        Axis::Horizontal => scrolled_x,
+        Axis::Vertical => scrolled_y,
+    };
+    // Start over when the direction changes.
+    if scrolled * distance < 0.0 {
+        scrolled = 0.0;
+    }
+    scrolled += distance;
+    let unit = match WHEEL_MODE.get() {
+        WheelMode::Lines => axis.cell_size(metrics),
+        WheelMode::Pages => axis.page_size(metrics),
+        WheelMode::Pixels => 1.0,
+    }
+    .max(1.0);
+    let amount = (scrolled.abs() / unit).floor();
+    let remaining = if amount < 1.0 {
+        scrolled
+    } else {
+        scrolled % unit
+    };
+    SCROLLED.set(match axis {
+        Axis::Horizontal => (remaining, scrolled_y),
+        Axis::Vertical => (scrolled_x, remaining),
+    });
+    if amount < 1.0 {
+        return None;
+    }
+    let kind = match (axis, scrolled < 0.0) {
+        (Axis::Horizontal, true) => MouseEventKind::ScrollLeft,
+        (Axis::Horizontal, false) => MouseEventKind::ScrollRight,
+        (Axis::Vertical, true) => MouseEventKind::ScrollUp,
+        (Axis::Vertical, false) => MouseEventKind::ScrollDown,
+    };
+    Some((kind, amount.min(f64::from(u32::MAX)) as u32))
+}
+
+/// Sets the distance in pixels that the pointer moves before a press becomes
+/// a drag.
+pub(crate) fn set_drag_threshold(distance: f64) {
+    DRAG_THRESHOLD.set(distance.max(0.0));
+}
+
+/// Sets the shortest touch in milliseconds that is a long press rather than
+/// a tap.
+pub(crate) fn set_long_press(duration: f64) {
+    LONG_PRESS.set(duration.max(0.0));
+}
+
+/// Records a press of a mouse button at the given client coordinates.
+fn track_press(x: f64, y: f64) {
+    PRESSED_AT.set(Some((x, y)));
+    DRAGGED.set(false);
+}
+
+/// Marks the press as a drag if the pointer moved further than the drag
+/// threshold.
+fn track_drag(x: f64, y: f64) {
+    if let Some((start_x, start_y)) = PRESSED_AT.get() {
+        if (x - start_x).hypot(y - start_y) > DRAG_THRESHOLD.get() {
+            DRAGGED.set(true);
+        }
+    }
+}
+
+/// Returns `true` if the pointer moved further than the drag threshold since
+/// the last press, i.e. the press is a drag rather than a click.
+///
+/// This stays `true` until the next press, so it can be checked when the
+/// button is released. The actions of the clickable regions (e.g.
+/// [`CopyButton`]s) and the hyperlinks are skipped after a drag, e.g. when
+/// selecting text.
+///
+/// The threshold is set with [`DomBackendBuilder::drag_threshold`].
+///
+/// [`CopyButton`]: crate::widgets::CopyButton
+/// [`DomBackendBuilder::drag_threshold`]: crate::backend::dom::DomBackendBuilder::drag_threshold
+pub fn is_drag() -> bool {
+    DRAGGED.get()
+}
+
+/// State of the current touch.
+#[derive(Debug, Clone, Copy)]
+struct TouchState {
+    /// Cell where the touch started.
+    cell: Position,
+    /// Time the touch started.
+    started_at: f64,
+    /// Client coordinates where the touch started.
+    start: (f64, f64),
+    /// Last client coordinates of the finger.
+    last: (f64, f64),
+    /// Whether the touch moved far enough to be a swipe.
+    swiping: bool,
+}
+
+/// Adds the listeners that dispatch taps and swipes on the target.
+fn add_touch_listeners<F, G>(
+    target: &EventTarget,
+    position: Rc<F>,
+    metrics: Rc<G>,
+) -> Result<(), Error>
+where
+    F: Fn(&Element, f64, f64) -> Option<Position> + 'static,
+    G: Fn() -> FontMetrics + 'static,
+{
+    let document = window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .document()
+        .ok_or(Error::UnableToRetrieveDocument)?;
+    let state = Rc::new(Cell::new(None::<TouchState>));
+    let closure = {
+        let state = state.clone();
+        let position = position.clone();
+        Closure::<dyn FnMut(_)>::new(move |event: web_sys::TouchEvent| {
+            let touches = event.touches();
+            let touch = match (touches.length(), touches.get(0)) {
+                (1, Some(touch)) => touch,
+                // Gestures with more fingers are left to the browser.
+                _ => {
+                    state.set(None);
+                    return;
+                }
+            };
+            let (x, y) = (f64::from(touch.client_x()), f64::from(touch.client_y()));
+            let cell = document
+                .element_from_point(x as f32, y as f32)
+                .and_then(|element| position(&element, x, y));
+            state.set(cell.map(|cell| TouchState {
+                cell,
+                started_at: event.time_stamp(),
+                start: (x, y),
+                last: (x, y),
+                swiping: false,
+            }));
+        })
+    };
+    target.add_event_listener_with_callback("touchstart", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    let closure = {
+        let state = state.clone();
+        let metrics = metrics.clone();
+        Closure::<dyn FnMut(_)>::new(move |event: web_sys::TouchEvent| {
+            let (Some(mut touch_state), Some(touch)) = (state.get(), event.touches().get(0)) else {
+                return;
+            };
+            let (x, y) = (f64::from(touch.client_x()), f64::from(touch.client_y()));
+            let (start_x, start_y) = touch_state.start;
+            if (x - start_x).hypot(y - start_y) > DRAG_THRESHOLD.get() {
+                // Not a tap anymore.
+                touch_state.swiping = true;
+            }
+            if !touch_state.swiping {
+                return;
+            }
+            // Moving the finger up scrolls the content down.
+            let (last_x, last_y) = touch_state.last;
+            touch_state.last = (x, y);
+            state.set(Some(touch_state));
+            let metrics = metrics();
+            for (axis, distance) in [(Axis::Horizontal, last_x - x), (Axis::Vertical, last_y - y)] {
+                if let Some((kind, amount)) = scroll(axis, distance, metrics) {
+                    let mut mouse_event = MouseEvent::from_touch(kind, touch_state.cell, &event);
+                    mouse_event.amount = amount;
+                    enqueue(Event::Mouse(mouse_event), event.time_stamp(), false);
+                }
+            }
+        })
+    };
+    target.add_event_listener_with_callback("touchmove", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::TouchEvent| {
+        let Some(touch_state) = state.take() else {
+            return;
+        };
+        // A long press is left to the browser, e.g. for the context menu.
+        if touch_state.swiping || event.time_stamp() - touch_state.started_at >= LONG_PRESS.get() {
+            return;
+        }
+        // Prevent the emulated mouse events, the tap is dispatched below.
+        event.prevent_default();
+        for kind in [
+            MouseEventKind::Down(MouseButton::Left),
+            MouseEventKind::Up(MouseButton::Left),
+        ] {
+            let mouse_event = MouseEvent::from_touch(kind, touch_state.cell, &event);
+            enqueue(Event::Mouse(mouse_event), event.time_stamp(), false);
+        }
+        keyboard::tapped();
+    });
+    target.add_event_listener_with_callback("touchend", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    Ok(())
+}
+
+/// Distance between the fingers, relative to the start of the gesture, that
+/// is recognized as a pinch.
+const PINCH_THRESHOLD: f64 = 0.1;
+
+/// Angle in degrees that is recognized as a rotation.
+const ROTATE_THRESHOLD: f64 = 10.0;
+
+/// State of a two-finger gesture.
+#[derive(Debug, Clone, Copy)]
+struct GestureState {
+    /// Center between the fingers at the start of the gesture.
+    start_center: (f64, f64),
+    /// Distance between the fingers at the start of the gesture.
+    start_distance: f64,
+    /// Angle between the fingers at the start of the gesture.
+    start_angle: f64,
+    /// Center between the fingers at the last move.
+    center: (f64, f64),
+    /// Distance between the fingers at the last move.
+    distance: f64,
+    /// Angle between the fingers at the last move.
+    angle: f64,
+    /// Whether a scroll, a pinch and a rotation were recognized.
+    recognized: [bool; 3],
+}
+
+/// Returns the center, the distance and the angle in degrees between two
+/// fingers.
+fn two_fingers(event: &web_sys::TouchEvent) -> Option<((f64, f64), f64, f64)> {
+    let touches = event.touches();
+    if touches.length() != 2 {
+        return None;
+    }
+    let (first, second) = (touches.get(0)?, touches.get(1)?);
+    let (x1, y1) = (f64::from(first.client_x()), f64::from(first.client_y()));
+    let (x2, y2) = (f64::from(second.client_x()), f64::from(second.client_y()));
+    let center = ((x1 + x2) / 2.0, (y1 + y2) / 2.0);
+    Some((
+        center,
+        (x2 - x1).hypot(y2 - y1),
+        (y2 - y1).atan2(x2 - x1).to_degrees(),
+    ))
+}
+
+/// Returns an angle in degrees between -180 and 180.
+fn normalize_angle(angle: f64) -> f64 {
+    (angle + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// Listener of a touch event.
+type TouchListener = Closure<dyn FnMut(web_sys::TouchEvent)>;
+
+/// Listeners that dispatch the multi-touch and gesture events on a target.
+///
+/// They are only added to the target while multi-touch is enabled, since
+/// they keep the browser from scrolling the page.
+struct MultiTouchListeners {
+    /// Element that the listeners are added to, e.g. the grid.
+    target: EventTarget,
+    /// Names of the events and their listeners.
+    listeners: Vec<(&'static str, TouchListener)>,
+}
+
+impl MultiTouchListeners {
+    /// Adds the listeners to the target.
+    fn attach(&self) -> Result<(), Error> {
+        let options = web_sys::AddEventListenerOptions::new();
+        // The browser must not zoom or scroll the page during a gesture.
+        options.set_passive(false);
+        for (name, closure) in &self.listeners {
+            self.target
+                .add_event_listener_with_callback_and_add_event_listener_options(
+                    name,
+                    closure.as_ref().unchecked_ref(),
+                    &options,
+                )?;
+        }
+        Ok(())
+    }
+
+    /// Removes the listeners from the target.
+    fn detach(&self) -> Result<(), Error> {
+        for (name, closure) in &self.listeners {
+            self.target
+                .remove_event_listener_with_callback(name, closure.as_ref().unchecked_ref())?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds the listeners that dispatch the multi-touch and gesture events on the
+/// target, see [`set_multi_touch`].
+///
+/// `position` and `metrics` are the same as for [`add_mouse_listeners`].
+pub(crate) fn add_multi_touch_listeners<F, G>(
+    target: &EventTarget,
+    position: F,
+    metrics: G,
+) -> Result<(), Error>
+where
+    F: Fn(&Element, f64, f64) -> Option<Position> + 'static,
+    G: Fn() -> FontMetrics + 'static,
+{
+    let document = window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .document()
+        .ok_or(Error::UnableToRetrieveDocument)?;
+    let cell_at = Rc::new(move |x: f64, y: f64| {
+        document
+            .element_from_point(x as f32, y as f32)
+            .and_then(|element| position(&element, x, y))
+    });
+    let metrics = Rc::new(metrics);
+    let mut listeners = Vec::new();
+    for (name, kind) in [
+        ("touchstart", TouchEventKind::Start),
+        ("touchmove", TouchEventKind::Move),
+        ("touchend", TouchEventKind::End),
+        ("touchcancel", TouchEventKind::Cancel),
+    ] {
+        let cell_at = cell_at.clone();
+        let metrics = metrics.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::TouchEvent| {
+            let changed = event.changed_touches();
+            let changed_ids = (0..changed.length())
+                .filter_map(|i| changed.get(i))
+                .map(|touch| touch.identifier())
+                .collect::<Vec<_>>();
+            // The lifted fingers are only in the changed touches.
+            let touches = match kind {
+                TouchEventKind::End | TouchEventKind::Cancel => changed,
+                _ => event.touches(),
+            };
+            let points = (0..touches.length())
+                .filter_map(|i| touches.get(i))
+                .filter_map(|touch| {
+                    let cell = cell_at(f64::from(touch.client_x()), f64::from(touch.client_y()))?;
+                    Some(TouchPoint {
+                        id: touch.identifier(),
+                        column: cell.x,
+                        row: cell.y,
+                        changed: changed_ids.contains(&touch.identifier()),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let timestamp = event.time_stamp();
+            if !points.is_empty() {
+                let touch_event = TouchEvent {
+                    kind,
+                    touches: points,
+                };
+                enqueue(Event::Touch(touch_event), timestamp, false);
+            }
+            let Some((center, distance, angle)) = two_fingers(&event) else {
+                GESTURE.set(None);
+                return;
+            };
+            event.prevent_default();
+            let Some(mut gesture) = GESTURE.get() else {
+                GESTURE.set(Some(GestureState {
+                    start_center: center,
+                    start_distance: distance,
+                    start_angle: angle,
+                    center,
+                    distance,
+                    angle,
+                    recognized: [false; 3],
+                }));
+                return;
+            };
+            let (start_x, start_y) = gesture.start_center;
+            let recognized = [
+                (center.0 - start_x).hypot(center.1 - start_y) > DRAG_THRESHOLD.get(),
+                gesture.start_distance > 0.0
+                    && (distance / gesture.start_distance - 1.0).abs() > PINCH_THRESHOLD,
+                normalize_angle(angle - gesture.start_angle).abs() > ROTATE_THRESHOLD,
+            ];
+            for (recognized, new) in gesture.recognized.iter_mut().zip(recognized) {
+                *recognized |= new;
+            }
+            let metrics = metrics();
+            let kinds = [
+                GestureKind::Scroll {
+                    // Moving the fingers up scrolls the content down.
+                    columns: (gesture.center.0 - center.0) / metrics.cell_width,
+                    rows: (gesture.center.1 - center.1) / metrics.cell_height,
+                },
+                GestureKind::Pinch {
+                    scale: if gesture.distance > 0.0 {
+                        distance / gesture.distance
+                    } else {
+                        1.0
+                    },
+                },
+                GestureKind::Rotate {
+                    degrees: normalize_angle(angle - gesture.angle),
+                },
+            ];
+            gesture.center = center;
+            gesture.distance = distance;
+            gesture.angle = angle;
+            GESTURE.set(Some(gesture));
+            let Some(cell) = cell_at(center.0, center.1) else {
+                return;
+            };
+            for (kind, recognized) in kinds.into_iter().zip(gesture.recognized) {
+                if recognized {
+                    let gesture_event = GestureEvent {
+                        kind,
+                        column: cell.x,
+                        row: cell.y,
+                    };
+                    enqueue(Event::Gesture(gesture_event), timestamp, false);
+                }
+            }
+        });
+        listeners.push((name, closure));
+    }
+    let listeners = MultiTouchListeners {
+        target: target.clone(),
+        listeners,
+    };
+    if MULTI_TOUCH.get() {
+        listeners.attach()?;
+    }
+    MULTI_TOUCH_LISTENERS.with(|all| all.borrow_mut().push(listeners));
+    Ok(())
+}
+
+/// Moves the multi-touch listeners of a target to another target, e.g. when
+/// the grid element is recreated.
+pub(crate) fn move_multi_touch_listeners(
+    from: &EventTarget,
+    to: &EventTarget,
+) -> Result<(), Error> {
+    MULTI_TOUCH_LISTENERS.with(|all| {
+        for listeners in all.borrow_mut().iter_mut() {
+            if listeners.target != *from {
+                continue;
+            }
+            if MULTI_TOUCH.get() {
+                listeners.detach()?;
+                to.clone_into(&mut listeners.target);
+                listeners.attach()?;
+            } else {
+                to.clone_into(&mut listeners.target);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Sets whether the multi-touch and gesture events are dispatched.
+///
+/// The listeners are added to their targets when enabled and removed when
+/// disabled.
+pub(crate) fn set_multi_touch(enabled: bool) {
+    GESTURE.set(None);
+    if MULTI_TOUCH.replace(enabled) == enabled {
+        return;
+    }
+    let result = MULTI_TOUCH_LISTENERS.with(|all| {
+        all.borrow().iter().try_for_each(|listeners| {
+            if enabled {
+                listeners.attach()
+            } else {
+                listeners.detach()
+            }
+        })
+    });
+    if let Err(error) = result {
+        web_warn!("unable to update the multi-touch listeners: {error}");
+    }
+}
+
+/// A mouse event.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MouseEvent {
+    /// Kind of the event.
+    pub kind: MouseEventKind,
+    /// Column of the cell that the event happened in.
+    pub column: u16,
+    /// Row of the cell that the event happened in.
+    pub row: u16,
+    /// Whether the control key is pressed.
+    pub ctrl: bool,
+    /// Whether the alt key is pressed.
+    pub alt: bool,
+    /// Whether the shift key is pressed.
+    pub shift: bool,
+    /// Distance scrolled for the scroll events, in the unit set with
+    /// [`WebRenderer::set_wheel_mode`] (lines by default). This is 0 for the
+    /// other events.
+    ///
+    /// [`WebRenderer::set_wheel_mode`]: crate::WebRenderer::set_wheel_mode
+    pub amount: u32,
+    /// The pointer device, along with the pressure and the tilt of a pen.
+    pub pointer: Pointer,
+}
+
+impl MouseEvent {
+    /// Constructs a new [`MouseEvent`] from a DOM mouse event.
+    fn new(kind: MouseEventKind, position: Position, event: &web_sys::MouseEvent) -> Self {
+        Self {
+            kind,
+            column: position.x,
+            row: position.y,
+            ctrl: event.ctrl_key(),
+            alt: event.alt_key(),
+            shift: event.shift_key(),
+            amount: 0,
+            pointer: Pointer::default(),
+        }
+    }
+
+    /// Constructs a new [`MouseEvent`] from a DOM touch event.
+    fn from_touch(kind: MouseEventKind, position: Position, event: &web_sys::TouchEvent) -> Self {
+        let pressure = event
+            .changed_touches()
+            .get(0)
+            .map_or(0.0, |touch| touch.force());
+        Self {
+            kind,
+            column: position.x,
+            row: position.y,
+            ctrl: event.ctrl_key(),
+            alt: event.alt_key(),
+            shift: event.shift_key(),
+            amount: 0,
+            pointer: Pointer {
+                kind: PointerKind::Touch,
+                pressure,
+                ..Pointer::default()
+            },
+        }
+    }
+
+    /// Returns the position of the cell that the event happened in.
+    pub fn position(&self) -> Position {
+        Position::new(self.column, self.row)
+    }
+}
+
+/// A multi-touch event.
+///
+/// This lists the fingers on the grid, e.g. for dragging several objects at
+/// once. The fingers outside of the grid are left out.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TouchEvent {
+    /// Kind of the event.
+    pub kind: TouchEventKind,
+    /// The fingers on the grid, and the lifted ones for
+    /// [`TouchEventKind::End`] and [`TouchEventKind::Cancel`].
+    pub touches: Vec<TouchPoint>,
+}
+
+/// Kind of a [`TouchEvent`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum TouchEventKind {
+    /// Fingers touched the screen.
+    Start,
+    /// Fingers moved.
+    Move,
+    /// Fingers were lifted.
+    End,
+    /// The touches were interrupted, e.g. by a system gesture.
+    Cancel,
+}
+
+/// A finger on the grid.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct TouchPoint {
+    /// Identifier of the finger, which stays the same while it touches the
+    /// screen.
+    pub id: i32,
+    /// Column of the cell under the finger.
+    pub column: u16,
+    /// Row of the cell under the finger.
+    pub row: u16,
+    /// Whether the finger changed in this event, e.g. moved or was lifted.
+    pub changed: bool,
+}
+
+impl TouchPoint {
+    /// Returns the position of the cell under the finger.
+    pub fn position(&self) -> Position {
+        Position::new(self.column, self.row)
+    }
+}
+
+/// A two-finger gesture.
+///
+/// A gesture is recognized once it goes past a threshold (e.g. the fingers
+/// move apart by 10% for a pinch), from then on an event is dispatched for
+/// every move with the change since the previous event. Several gestures can
+/// be recognized at once, e.g. a pinch and a scroll when zooming into a map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureEvent {
+    /// Kind of the gesture.
+    pub kind: GestureKind,
+    /// Column of the cell between the fingers.
+    pub column: u16,
+    /// Row of the cell between the fingers.
+    pub row: u16,
+}
+
+// The values of the gestures are never NaN.
+impl Eq for GestureEvent {}
+
+impl GestureEvent {
+    /// Returns the position of the cell between the fingers.
+    pub fn position(&self) -> Position {
+        Position::new(self.column, self.row)
+    }
+}
+
+/// Kind of a [`GestureEvent`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureKind {
+    /// Two fingers moved together.
+    Scroll {
+        /// Columns scrolled, positive for scrolling right (i.e. the fingers
+        /// moving left).
+        columns: f64,
+        /// Rows scrolled, positive for scrolling down (i.e. the fingers
+        /// moving up).
+        rows: f64,
+    },
+    /// The fingers moved apart or together.
+    Pinch {
+        /// Ratio of the distance between the fingers to the previous one,
+        /// greater than 1 when zooming in.
+        scale: f64,
+    },
+    /// The fingers rotated around each other.
+    Rotate {
+        /// Angle of the rotation in degrees, positive for clockwise.
+        degrees: f64,
+    },
+}
+
+/// Kind of a pointer device.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum PointerKind {
+    /// A mouse or a touchpad.
+    #[default]
+    Mouse,
+    /// A pen or a stylus.
+    Pen,
+    /// A finger on a touch screen.
+    Touch,
+}
+
+/// The pointer device of a [`MouseEvent`].
+///
+/// Pens report how hard they are pressed and how they are tilted, e.g. for
+/// varying the strokes of a drawing on a [`Canvas`] widget. Other devices
+/// report a pressure of 0.5 while a button is pressed (or the force of the
+/// touch, if the device supports it) and no tilt.
+///
+/// [`Canvas`]: ratatui::widgets::canvas::Canvas
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pointer {
+    /// Kind of the device.
+    pub kind: PointerKind,
+    /// Pressure from 0 to 1.
+    pub pressure: f32,
+    /// Angle between the pen and the screen along the X axis in degrees, from
+    /// -90 (tilted to the left) to 90 (tilted to the right).
+    pub tilt_x: i32,
+    /// Angle between the pen and the screen along the Y axis in degrees, from
+    /// -90 (tilted away from the user) to 90 (tilted towards the user).
+    pub tilt_y: i32,
+}
+
+// The pressure is never NaN, browsers clamp it between 0 and 1.
+impl Eq for Pointer {}
+
+/// Convert a [`PointerEvent`] to a [`Pointer`].
+impl From<&PointerEvent> for Pointer {
+    fn from(event: &PointerEvent) -> Self {
+        let kind = match event.pointer_type().as_str() {
+            "pen" => PointerKind::Pen,
+            "touch" => PointerKind::Touch,
+            _ => PointerKind::Mouse,
+        };
+        let pressure = event.pressure();
+        Self {
+            kind,
+            pressure: if pressure.is_nan() {
+                0.0
+            } else {
+                pressure.clamp(0.0, 1.0)
+            },
+            tilt_x: event.tilt_x(),
+            tilt_y: event.tilt_y(),
+        }
+    }
+}
+
+/// Kind of a [`MouseEvent`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MouseEventKind {
+    /// A button was pressed.
+    Down(MouseButton),
+    /// A button was released.
+    Up(MouseButton),
+    /// The mouse was moved.
+    Moved,
+    /// The wheel was scrolled up.
+    ScrollUp,
+    /// The wheel was scrolled down.
+    ScrollDown,
+    /// The wheel or the touchpad was scrolled left.
+    ScrollLeft,
+    /// The wheel or the touchpad was scrolled right.
+    ScrollRight,
+}
+
+/// A mouse button.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MouseButton {
+    /// Left (primary) button.
+    Left,
+    /// Right (secondary) button.
+    Right,
+    /// Middle (auxiliary) button.
+    Middle,
+    /// Any other button, e.g. the back and forward buttons.
+    Other(i16),
+}
+
+/// Convert a DOM button number to a [`MouseButton`].
+impl From<i16> for MouseButton {
+    fn from(button: i16) -> Self {
+        match button {
+            0 => MouseButton::Left,
+            1 => MouseButton::Middle,
+            2 => MouseButton::Right,
+            button => MouseButton::Other(button),
+        }
+    }
+}
+
+/// How the [`KeyEvent::code`] of the key events is determined.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum KeyPolicy {
+    /// The code is the character produced with the keyboard layout of the
+    /// user, e.g. `z` for the key left of `x` on a German keyboard.
+    ///
+    /// This is what editors and text inputs need.
+    #[default]
+    Layout,
+    /// The code is the key at the same position on a US QWERTY keyboard,
+    /// regardless of the keyboard layout, e.g. `y` for the key left of `x` on
+    /// a German keyboard.
+    ///
+    /// This is what games need for e.g. WASD movement.
+    Physical,
+}
+
 /// A key event.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct KeyEvent {
-    /// The key code.
+    /// The key code, see [`KeyPolicy`].
     pub code: KeyCode,
+    /// The key value produced with the keyboard layout, i.e. the `key` of the
+    /// DOM event (e.g. `"z"` or `"ArrowUp"`).
+    pub key: String,
+    /// The physical key, i.e. the `code` of the DOM event (e.g. `"KeyY"` or
+    /// `"ArrowUp"`).
+    pub physical_key: String,
     /// Whether the control key is pressed.
     pub ctrl: bool,
     /// Whether the alt key is pressed.
@@ -12,13 +1580,36 @@ pub struct KeyEvent {
 }
 
 /// Convert a [`web_sys::KeyboardEvent`] to a [`KeyEvent`].
+///
+/// The code is determined with the policy set with
+/// [`WebRenderer::set_key_policy`].
+///
+/// [`WebRenderer::set_key_policy`]: crate::WebRenderer::set_key_policy
 impl From<web_sys::KeyboardEvent> for KeyEvent {
     fn from(event: web_sys::KeyboardEvent) -> Self {
-        let ctrl = event.ctrl_key();
-        let alt = event.alt_key();
+        let mut ctrl = event.ctrl_key();
+        let mut alt = event.alt_key();
         let shift = event.shift_key();
+        let key = event.key();
+        let physical_key = event.code();
+        let code = match KEY_POLICY.get() {
+            KeyPolicy::Layout => KeyCode::from_key(&key),
+            KeyPolicy::Physical => match KeyCode::from_physical_key(&physical_key, shift) {
+                // Fall back to the layout for keys without a US equivalent.
+                KeyCode::Unidentified => KeyCode::from_key(&key),
+                code => code,
+            },
+        };
+        // AltGr is reported as Ctrl+Alt on some platforms, the characters
+        // typed with it (e.g. `€` or `@` on European layouts) are plain text.
+        if matches!(code, KeyCode::Char(_)) && event.get_modifier_state("AltGraph") {
+            ctrl = false;
+            alt = false;
+        }
         KeyEvent {
-            code: event.into(),
+            code,
+            key,
+            physical_key,
             ctrl,
             alt,
             shift,
@@ -26,6 +1617,370 @@ impl From<web_sys::KeyboardEvent> for KeyEvent {
     }
 }
 
+impl KeyEvent {
+    /// Constructs a new [`KeyEvent`] without modifiers, e.g. for a composed
+    /// character.
+    fn unmodified(code: KeyCode, key: String, physical_key: String) -> Self {
+        Self {
+            code,
+            key,
+            physical_key,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        }
+    }
+
+    /// Constructs a new [`KeyEvent`] for a typed character without
+    /// modifiers.
+    fn text(char: char, physical_key: String) -> Self {
+        Self::unmodified(KeyCode::Char(char), char.to_string(), physical_key)
+    }
+
+    /// Returns `true` if the key is on the numpad.
+    ///
+    /// The numpad keys produce the same codes as the other keys, e.g.
+    /// [`KeyCode::Char`] with NumLock on and [`KeyCode::Up`] with NumLock
+    /// off for the 8.
+    pub fn is_numpad(&self) -> bool {
+        self.physical_key.starts_with("Numpad")
+    }
+}
+
+/// A key along with the modifiers that must be pressed, e.g. for a shortcut.
+///
+/// ```rust
+/// use ratzilla::event::{KeyBinding, KeyCode};
+///
+/// // Ctrl+Alt+C
+/// let binding = KeyBinding::new(KeyCode::Char('c')).ctrl().alt();
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct KeyBinding {
+    /// The key code.
+    pub code: KeyCode,
+    /// Whether the control key must be pressed.
+    pub ctrl: bool,
+    /// Whether the alt key must be pressed.
+    pub alt: bool,
+    /// Whether the shift key must be pressed.
+    pub shift: bool,
+}
+
+impl KeyBinding {
+    /// Constructs a new [`KeyBinding`] for the key without modifiers.
+    pub fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        }
+    }
+
+    /// Requires the control key.
+    pub fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    /// Requires the alt key.
+    pub fn alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Requires the shift key.
+    pub fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    /// Returns `true` if the key event matches the binding.
+    ///
+    /// Letters match regardless of their case, which depends on the shift
+    /// key.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        let same_code = match (&self.code, &event.code) {
+            (KeyCode::Char(expected), KeyCode::Char(actual)) => {
+                expected.to_lowercase().eq(actual.to_lowercase())
+            }
+            (expected, actual) => expected == actual,
+        };
+        same_code && self.ctrl == event.ctrl && self.alt == event.alt && self.shift == event.shift
+    }
+}
+
+/// Dispatches a key typed on a virtual keyboard.
+pub(crate) fn dispatch_virtual_key(code: KeyCode, key: String, timestamp: f64) {
+    enqueue(
+        Event::Key(KeyEvent::unmodified(code, key, String::new())),
+        timestamp,
+        false,
+    );
+}
+
+/// Sets how the code of the key events is determined.
+pub(crate) fn set_key_policy(policy: KeyPolicy) {
+    KEY_POLICY.set(policy);
+}
+
+/// Input capabilities.
+///
+/// Applications made for terminals work around the limits of the terminal
+/// input, e.g. by waiting after an Esc byte to tell the Esc key from the
+/// start of an escape sequence. The browser reports the keys as separate
+/// events, so these workarounds only add latency here. An application that
+/// also runs in a terminal can check the capabilities to skip them.
+///
+/// ```rust no_run
+/// use std::time::Duration;
+///
+/// use ratzilla::{ratatui::Terminal, DomBackend, WebRenderer};
+///
+/// let terminal = Terminal::new(DomBackend::new().unwrap()).unwrap();
+/// let esc_delay = if terminal.capabilities().immediate_escape {
+///     Duration::ZERO
+/// } else {
+///     Duration::from_millis(25)
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Whether [`KeyCode::Esc`] is dispatched as soon as the key is pressed.
+    ///
+    /// Esc is never part of an escape sequence, so there is no delay to tell
+    /// it apart and Alt+key is reported as a key event with [`KeyEvent::alt`]
+    /// rather than as Esc followed by the key.
+    pub immediate_escape: bool,
+    /// Whether keys that share a byte in a terminal are told apart, e.g. Tab
+    /// and Ctrl+I, Enter and Ctrl+M, or Backspace and Ctrl+H.
+    pub disambiguated_keys: bool,
+    /// Whether [`Event::FocusGained`] and [`Event::FocusLost`] are dispatched.
+    pub focus_events: bool,
+    /// Whether pasted text is dispatched as a single [`Event::Paste`], like
+    /// with bracketed paste.
+    pub paste_events: bool,
+}
+
+/// Returns the input capabilities.
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        immediate_escape: true,
+        disambiguated_keys: true,
+        focus_events: true,
+        paste_events: true,
+    }
+}
+
+/// Class of a key, see [`KeyRepeat`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum KeyClass {
+    /// Keys that type a character.
+    Character,
+    /// Arrow keys, Home, End, Page up and Page down.
+    Navigation,
+    /// Backspace, Delete, Enter, Tab, Insert and Escape.
+    Editing,
+    /// F keys.
+    Function,
+    /// Any other key, e.g. lock and media keys.
+    Other,
+}
+
+impl KeyClass {
+    /// Returns the index of the class in [`KeyRepeat`].
+    const fn index(self) -> usize {
+        match self {
+            KeyClass::Character => 0,
+            KeyClass::Navigation => 1,
+            KeyClass::Editing => 2,
+            KeyClass::Function => 3,
+            KeyClass::Other => 4,
+        }
+    }
+}
+
+/// How the auto-repeat of a key is handled.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum RepeatMode {
+    /// The repeated key events of the browser are dispatched.
+    ///
+    /// The rate is set by the operating system of the user.
+    #[default]
+    Browser,
+    /// The key is repeated at a custom rate while it is held down, the
+    /// repeated key events of the browser are dropped.
+    Custom {
+        /// Time from pressing the key to the first repeat in milliseconds.
+        delay: u32,
+        /// Time between repeats in milliseconds.
+        interval: u32,
+    },
+    /// The key is not repeated.
+    Suppress,
+}
+
+/// Auto-repeat handling of the key events.
+///
+/// The rate at which browsers repeat a held down key differs between
+/// platforms and from what applications made for terminals expect. The
+/// repeats can be passed through, repeated at a custom rate or suppressed
+/// for each [`KeyClass`].
+///
+/// ```rust no_run
+/// use ratzilla::{
+///     event::{KeyClass, KeyRepeat, RepeatMode},
+///     ratatui::Terminal,
+///     DomBackend, WebRenderer,
+/// };
+///
+/// let terminal = Terminal::new(DomBackend::new().unwrap()).unwrap();
+/// // Move smoothly with the arrow keys, never repeat Enter.
+/// terminal.set_key_repeat(
+///     KeyRepeat::new()
+///         .mode(
+///             KeyClass::Navigation,
+///             RepeatMode::Custom {
+///                 delay: 200,
+///                 interval: 30,
+///             },
+///         )
+///         .mode(KeyClass::Editing, RepeatMode::Suppress),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct KeyRepeat {
+    /// Modes of the key classes.
+    modes: [RepeatMode; 5],
+}
+
+impl KeyRepeat {
+    /// Constructs a new [`KeyRepeat`].
+    ///
+    /// By default, the repeats of the browser are dispatched for every key.
+    pub const fn new() -> Self {
+        Self {
+            modes: [RepeatMode::Browser; 5],
+        }
+    }
+
+    /// Sets the mode of a key class.
+    pub fn mode(mut self, class: KeyClass, mode: RepeatMode) -> Self {
+        self.modes[class.index()] = mode;
+        self
+    }
+
+    /// Sets the mode of every key class.
+    pub fn all(mut self, mode: RepeatMode) -> Self {
+        self.modes = [mode; 5];
+        self
+    }
+
+    /// Returns the mode of a key class.
+    pub fn get(&self, class: KeyClass) -> RepeatMode {
+        self.modes[class.index()]
+    }
+}
+
+/// A key repeated at a custom rate.
+struct Repeater {
+    /// The repeated event.
+    event: KeyEvent,
+    /// Time between repeats in milliseconds.
+    interval: u32,
+    /// Handle of the pending timeout.
+    handle: i32,
+    /// Callback of the timeout.
+    callback: Closure<dyn FnMut()>,
+}
+
+/// Sets how the auto-repeat of the keys is handled.
+pub(crate) fn set_key_repeat(repeat: KeyRepeat) {
+    KEY_REPEAT.set(repeat);
+    stop_repeat();
+}
+
+/// Starts repeating the key event until the key is released.
+fn start_repeat(event: KeyEvent, delay: u32, interval: u32) {
+    let callback = Closure::<dyn FnMut()>::new(|| {
+        let Some(event) = REPEATER.with(|repeater| {
+            repeater
+                .borrow()
+                .as_ref()
+                .map(|repeater| repeater.event.clone())
+        }) else {
+            return;
+        };
+        enqueue(Event::Key(event), utils::performance_now(), true);
+        schedule_repeat(None);
+    });
+    REPEATER.with(|repeater| {
+        repeater.replace(Some(Repeater {
+            event,
+            interval,
+            handle: 0,
+            callback,
+        }))
+    });
+    schedule_repeat(Some(delay));
+}
+
+/// Schedules the next repeat after the given delay, or after the interval of
+/// the repeater.
+fn schedule_repeat(delay: Option<u32>) {
+    let Some(window) = window() else {
+        return;
+    };
+    REPEATER.with(|repeater| {
+        if let Some(repeater) = repeater.borrow_mut().as_mut() {
+            let delay = delay.unwrap_or(repeater.interval);
+            if let Ok(handle) = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                repeater.callback.as_ref().unchecked_ref(),
+                delay.min(i32::MAX as u32) as i32,
+            ) {
+                repeater.handle = handle;
+            }
+        }
+    });
+}
+
+/// Stops repeating the held down key, if any.
+fn stop_repeat() {
+    let Some(repeater) = REPEATER.with(|repeater| repeater.take()) else {
+        return;
+    };
+    if let Some(window) = window() {
+        window.clear_timeout_with_handle(repeater.handle);
+    }
+}
+
+/// A clipboard copy event.
+///
+/// This is emitted after text is written to the clipboard, e.g. by clicking a
+/// [`CopyButton`].
+///
+/// [`CopyButton`]: crate::widgets::CopyButton
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CopyEvent {
+    /// The copied text.
+    pub text: String,
+    /// Whether the text was copied successfully.
+    pub success: bool,
+}
+
+/// An effect event.
+///
+/// This is emitted when an effect such as a [`Typewriter`] finishes.
+///
+/// [`Typewriter`]: crate::widgets::Typewriter
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EffectEvent {
+    /// Name of the effect.
+    pub name: String,
+}
+
 /// A key code.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum KeyCode {
@@ -58,36 +2013,107 @@ pub enum KeyCode {
     /// Page down key
     PageDown,
     /// Escape key
+    ///
+    /// Unlike in a terminal, this is dispatched as soon as the key is
+    /// pressed, see [`Capabilities::immediate_escape`].
     Esc,
+    /// Insert key
+    Insert,
+    /// The 5 of the numpad when NumLock is off
+    KeypadBegin,
+    /// Caps lock key
+    CapsLock,
+    /// Scroll lock key
+    ScrollLock,
+    /// Num lock key
+    NumLock,
+    /// Print screen key
+    PrintScreen,
+    /// Pause key
+    Pause,
+    /// Context menu key
+    Menu,
+    /// Media key
+    Media(MediaKeyCode),
     /// Unidentified.
     Unidentified,
 }
 
+/// A media key code.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MediaKeyCode {
+    /// Play media key
+    Play,
+    /// Pause media key
+    Pause,
+    /// Play/Pause media key
+    PlayPause,
+    /// Stop media key
+    Stop,
+    /// Fast-forward media key
+    FastForward,
+    /// Rewind media key
+    Rewind,
+    /// Next-track media key
+    TrackNext,
+    /// Previous-track media key
+    TrackPrevious,
+    /// Record media key
+    Record,
+    /// Lower-volume media key
+    LowerVolume,
+    /// Raise-volume media key
+    RaiseVolume,
+    /// Mute media key
+    MuteVolume,
+}
+
 /// Convert a [`web_sys::KeyboardEvent`] to a [`KeyCode`].
+///
+/// This uses the keyboard layout, see [`KeyPolicy::Layout`].
 impl From<web_sys::KeyboardEvent> for KeyCode {
     fn from(event: web_sys::KeyboardEvent) -> Self {
-        let key = event.key();
-        if key.len() == 1 {
-            let char = key.chars().next();
-            if let Some(char) = char {
-                return KeyCode::Char(char);
-            } else {
-                return KeyCode::Unidentified;
-            }
-        }
-        match key.as_str() {
-            "F1" => KeyCode::F(1),
-            "F2" => KeyCode::F(2),
-            "F3" => KeyCode::F(3),
-            "F4" => KeyCode::F(4),
-            "F5" => KeyCode::F(5),
-            "F6" => KeyCode::F(6),
-            "F7" => KeyCode::F(7),
-            "F8" => KeyCode::F(8),
-            "F9" => KeyCode::F(9),
-            "F10" => KeyCode::F(10),
-            "F11" => KeyCode::F(11),
-            "F12" => KeyCode::F(12),
+        KeyCode::from_key(&event.key())
+    }
+}
+
+impl KeyCode {
+    /// Returns the class of the key, see [`KeyRepeat`].
+    pub fn class(&self) -> KeyClass {
+        match self {
+            KeyCode::Char(_) => KeyClass::Character,
+            KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Up
+            | KeyCode::Down
+            | KeyCode::Home
+            | KeyCode::End
+            | KeyCode::PageUp
+            | KeyCode::PageDown
+            | KeyCode::KeypadBegin => KeyClass::Navigation,
+            KeyCode::Backspace
+            | KeyCode::Enter
+            | KeyCode::Tab
+            | KeyCode::Delete
+            | KeyCode::Insert
+            | KeyCode::Esc => KeyClass::Editing,
+            KeyCode::F(_) => KeyClass::Function,
+            _ => KeyClass::Other,
+        }
+    }
+
+    /// Returns the code of a key value, i.e. the `key` of a DOM event.
+    pub(crate) fn from_key(key: &str) -> Self {
+        let mut chars = key.chars();
+        if let (Some(char), None) = (chars.next(), chars.next()) {
+            return KeyCode::Char(char);
+        }
+        if let Some(number) = key.strip_prefix('F').and_then(|n| n.parse::<u8>().ok()) {
+            if (1..=24).contains(&number) {
+                return KeyCode::F(number);
+            }
+        }
+        match key {
             "Backspace" => KeyCode::Backspace,
             "Enter" => KeyCode::Enter,
             "ArrowLeft" => KeyCode::Left,
@@ -96,12 +2122,241 @@ impl From<web_sys::KeyboardEvent> for KeyCode {
             "ArrowDown" => KeyCode::Down,
             "Tab" => KeyCode::Tab,
             "Delete" => KeyCode::Delete,
+            "Insert" => KeyCode::Insert,
             "Home" => KeyCode::Home,
             "End" => KeyCode::End,
             "PageUp" => KeyCode::PageUp,
             "PageDown" => KeyCode::PageDown,
             "Escape" => KeyCode::Esc,
+            // The 5 of the numpad without NumLock.
+            "Clear" => KeyCode::KeypadBegin,
+            "CapsLock" => KeyCode::CapsLock,
+            "ScrollLock" => KeyCode::ScrollLock,
+            "NumLock" => KeyCode::NumLock,
+            "PrintScreen" => KeyCode::PrintScreen,
+            "Pause" => KeyCode::Pause,
+            "ContextMenu" => KeyCode::Menu,
+            "MediaPlay" => KeyCode::Media(MediaKeyCode::Play),
+            "MediaPause" => KeyCode::Media(MediaKeyCode::Pause),
+            "MediaPlayPause" => KeyCode::Media(MediaKeyCode::PlayPause),
+            "MediaStop" => KeyCode::Media(MediaKeyCode::Stop),
+            "MediaFastForward" => KeyCode::Media(MediaKeyCode::FastForward),
+            "MediaRewind" => KeyCode::Media(MediaKeyCode::Rewind),
+            "MediaTrackNext" => KeyCode::Media(MediaKeyCode::TrackNext),
+            "MediaTrackPrevious" => KeyCode::Media(MediaKeyCode::TrackPrevious),
+            "MediaRecord" => KeyCode::Media(MediaKeyCode::Record),
+            "AudioVolumeDown" => KeyCode::Media(MediaKeyCode::LowerVolume),
+            "AudioVolumeUp" => KeyCode::Media(MediaKeyCode::RaiseVolume),
+            "AudioVolumeMute" => KeyCode::Media(MediaKeyCode::MuteVolume),
             _ => KeyCode::Unidentified,
         }
     }
+
+    /// Returns the code of a physical key, i.e. the `code` of a DOM event, as
+    /// if it was typed on a US QWERTY keyboard.
+    pub(crate) fn from_physical_key(physical_key: &str, shift: bool) -> Self {
+        if let Some(letter) = physical_key.strip_prefix("Key") {
+            if let Some(char) = letter.chars().next().filter(|_| letter.len() == 1) {
+                return KeyCode::Char(if shift {
+                    char.to_ascii_uppercase()
+                } else {
+                    char.to_ascii_lowercase()
+                });
+            }
+        }
+        const SYMBOLS: [(&str, char, char); 21] = [
+            ("Digit1", '1', '!'),
+            ("Digit2", '2', '@'),
+            ("Digit3", '3', '#'),
+            ("Digit4", '4', '$'),
+            ("Digit5", '5', '%'),
+            ("Digit6", '6', '^'),
+            ("Digit7", '7', '&'),
+            ("Digit8", '8', '*'),
+            ("Digit9", '9', '('),
+            ("Digit0", '0', ')'),
+            ("Minus", '-', '_'),
+            ("Equal", '=', '+'),
+            ("BracketLeft", '[', '{'),
+            ("BracketRight", ']', '}'),
+            ("Backslash", '\\', '|'),
+            ("Semicolon", ';', ':'),
+            ("Quote", '\'', '"'),
+            ("Backquote", '`', '~'),
+            ("Comma", ',', '<'),
+            ("Period", '.', '>'),
+            ("Slash", '/', '?'),
+        ];
+        if let Some((_, char, shifted)) = SYMBOLS.iter().find(|(name, ..)| *name == physical_key) {
+            return KeyCode::Char(if shift { *shifted } else { *char });
+        }
+        match physical_key {
+            "Space" => KeyCode::Char(' '),
+            "NumpadEnter" => KeyCode::Enter,
+            // The other named keys have the same code and key values.
+            _ => KeyCode::from_key(physical_key).named(),
+        }
+    }
+
+    /// Returns the code if it is a named key, [`KeyCode::Unidentified`]
+    /// otherwise.
+    fn named(self) -> Self {
+        match self {
+            KeyCode::Char(_) => KeyCode::Unidentified,
+            code => code,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_keyboard_focus_from_handler() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        add_event_handler({
+            let events = Rc::clone(&events);
+            move |event| {
+                events.borrow_mut().push(event.clone());
+                if *event == Event::Paste("blur".to_string()) {
+                    set_keyboard_focus(false);
+                }
+            }
+        });
+        dispatch(Event::Paste("blur".to_string()));
+        assert_eq!(
+            *events.borrow(),
+            [Event::Paste("blur".to_string()), Event::FocusLost]
+        );
+        assert!(!has_keyboard_focus());
+        assert!(!DISPATCHING.get());
+    }
+
+    #[test]
+    fn add_event_handler_from_handler() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        add_event_filter(|event| {
+            if event == Event::Paste("filter".to_string()) {
+                add_event_filter(Some);
+            }
+            Some(event)
+        });
+        add_event_handler({
+            let events = Rc::clone(&events);
+            move |event| {
+                if *event == Event::Paste("handler".to_string()) {
+                    let events = Rc::clone(&events);
+                    add_event_handler(move |event| events.borrow_mut().push(event.clone()));
+                }
+            }
+        });
+        dispatch(Event::Paste("filter".to_string()));
+        dispatch(Event::Paste("handler".to_string()));
+        assert!(events.borrow().is_empty());
+        dispatch(Event::FocusGained);
+        assert_eq!(*events.borrow(), [Event::FocusGained]);
+        assert_eq!(FILTERS.with(|filters| filters.borrow().len()), 2);
+        assert_eq!(HANDLERS.with(|handlers| handlers.borrow().len()), 2);
+    }
+
+    #[test]
+    fn function_keys() {
+        assert_eq!(KeyCode::from_key("F1"), KeyCode::F(1));
+        assert_eq!(KeyCode::from_key("F12"), KeyCode::F(12));
+        assert_eq!(KeyCode::from_key("F13"), KeyCode::F(13));
+        assert_eq!(KeyCode::from_key("F24"), KeyCode::F(24));
+        assert_eq!(KeyCode::from_key("F25"), KeyCode::Unidentified);
+        assert_eq!(KeyCode::from_key("F0"), KeyCode::Unidentified);
+        assert_eq!(KeyCode::from_key("F"), KeyCode::Char('F'));
+    }
+
+    #[test]
+    fn numpad_keys() {
+        // NumLock on.
+        assert_eq!(KeyCode::from_key("7"), KeyCode::Char('7'));
+        assert_eq!(KeyCode::from_key("+"), KeyCode::Char('+'));
+        assert_eq!(KeyCode::from_key("Enter"), KeyCode::Enter);
+        // NumLock off.
+        assert_eq!(KeyCode::from_key("Home"), KeyCode::Home);
+        assert_eq!(KeyCode::from_key("ArrowUp"), KeyCode::Up);
+        assert_eq!(KeyCode::from_key("Clear"), KeyCode::KeypadBegin);
+        assert_eq!(KeyCode::from_key("Insert"), KeyCode::Insert);
+        assert_eq!(KeyCode::from_key("Delete"), KeyCode::Delete);
+
+        let event = KeyEvent::unmodified(KeyCode::Up, "ArrowUp".into(), "Numpad8".into());
+        assert!(event.is_numpad());
+        let event = KeyEvent::unmodified(KeyCode::Up, "ArrowUp".into(), "ArrowUp".into());
+        assert!(!event.is_numpad());
+    }
+
+    #[test]
+    fn lock_and_system_keys() {
+        assert_eq!(KeyCode::from_key("CapsLock"), KeyCode::CapsLock);
+        assert_eq!(KeyCode::from_key("NumLock"), KeyCode::NumLock);
+        assert_eq!(KeyCode::from_key("ScrollLock"), KeyCode::ScrollLock);
+        assert_eq!(KeyCode::from_key("PrintScreen"), KeyCode::PrintScreen);
+        assert_eq!(KeyCode::from_key("Pause"), KeyCode::Pause);
+        assert_eq!(KeyCode::from_key("ContextMenu"), KeyCode::Menu);
+        assert_eq!(KeyCode::from_key("Shift"), KeyCode::Unidentified);
+    }
+
+    #[test]
+    fn media_keys() {
+        assert_eq!(
+            KeyCode::from_key("MediaPlayPause"),
+            KeyCode::Media(MediaKeyCode::PlayPause)
+        );
+        assert_eq!(
+            KeyCode::from_key("MediaTrackNext"),
+            KeyCode::Media(MediaKeyCode::TrackNext)
+        );
+        assert_eq!(
+            KeyCode::from_key("AudioVolumeMute"),
+            KeyCode::Media(MediaKeyCode::MuteVolume)
+        );
+    }
+
+    #[test]
+    fn key_repeat() {
+        assert_eq!(KeyCode::Char('a').class(), KeyClass::Character);
+        assert_eq!(KeyCode::PageDown.class(), KeyClass::Navigation);
+        assert_eq!(KeyCode::Backspace.class(), KeyClass::Editing);
+        assert_eq!(KeyCode::F(5).class(), KeyClass::Function);
+        assert_eq!(KeyCode::CapsLock.class(), KeyClass::Other);
+
+        let custom = RepeatMode::Custom {
+            delay: 200,
+            interval: 30,
+        };
+        let repeat = KeyRepeat::new()
+            .all(RepeatMode::Suppress)
+            .mode(KeyClass::Navigation, custom);
+        assert_eq!(repeat.get(KeyClass::Navigation), custom);
+        assert_eq!(repeat.get(KeyClass::Character), RepeatMode::Suppress);
+        assert_eq!(
+            KeyRepeat::default().get(KeyClass::Other),
+            RepeatMode::Browser
+        );
+    }
+
+    #[test]
+    fn key_bindings() {
+        let binding = KeyBinding::new(KeyCode::Char('c')).ctrl().shift();
+        let mut event = KeyEvent::text('C', "KeyC".into());
+        assert!(!binding.matches(&event));
+        event.ctrl = true;
+        event.shift = true;
+        assert!(binding.matches(&event));
+        event.alt = true;
+        assert!(!binding.matches(&event));
+    }
+
+    #[test]
+    fn characters() {
+        assert_eq!(KeyCode::from_key("a"), KeyCode::Char('a'));
+        assert_eq!(KeyCode::from_key("é"), KeyCode::Char('é'));
+        assert_eq!(KeyCode::from_key("€"), KeyCode::Char('€'));
+        assert_eq!(KeyCode::from_key(" "), KeyCode::Char(' '));
+    }
 }
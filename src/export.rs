@@ -0,0 +1,171 @@
+//! ## Static export
+//!
+//! Renders a buffer as plain text ([`to_text`]), static HTML with inline
+//! styles and without any script ([`to_html`]) or a PNG image ([`to_png`]).
+//! [`DomBackend`] and [`CanvasBackend`] have the same methods for exporting
+//! their current content, e.g. for share or download buttons.
+//!
+//! This can be used for progressive enhancement: export a representative
+//! frame (e.g. captured with [`FrameCapture`] or rendered natively with
+//! ratatui's `TestBackend`) and put it in a `<noscript>` element or in the
+//! page itself, so search engines and users without JavaScript see the
+//! content while the WebAssembly module loads.
+//!
+//! ```rust no_run
+//! use ratzilla::{export::to_noscript, ratatui::buffer::Buffer};
+//!
+//! # let buffer = Buffer::empty(Default::default());
+//! let html = to_noscript(&buffer);
+//! // Write the HTML to the index page at build time...
+//! ```
+//!
+//! Note that the buffer is exported as is, the contents of [`Secret`]
+//! widgets are only masked by the [`DomBackend`].
+//!
+//! [`FrameCapture`]: crate::capture::FrameCapture
+//! [`Secret`]: crate::widgets::Secret
+//! [`DomBackend`]: crate::DomBackend
+//! [`CanvasBackend`]: crate::CanvasBackend
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier},
+};
+use web_sys::{wasm_bindgen::JsCast, CanvasRenderingContext2d, HtmlCanvasElement};
+
+use crate::{
+    backend::utils::{get_cell_color_for_canvas, get_cell_style_as_css, is_same_style, map_rows},
+    clipboard::buffer_text,
+    error::Error,
+};
+
+/// Style of the exported `<pre>` element.
+const PRE_STYLE: &str = "margin: 0; font-family: monospace; line-height: normal; white-space: pre;";
+
+/// Renders the buffer as a self-contained `<pre>` element.
+///
+/// Consecutive cells with the same style are merged into a single `<span>`.
+/// Hyperlinks are rendered as their text, since their URLs are not part of
+/// the buffer. The rows of large buffers are rendered in parallel with the
+/// `threads` feature.
+pub fn to_html(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let rows = map_rows(area.height as usize, area.width as usize, |row| {
+        let y = area.top() + row as u16;
+        let cells = (area.left()..area.right())
+            .filter_map(|x| buffer.cell((x, y)))
+            .collect::<Vec<_>>();
+        let mut html = String::new();
+        let mut start = 0;
+        while start < cells.len() {
+            let end = start
+                + cells[start..]
+                    .iter()
+                    .take_while(|cell| is_same_style(cells[start], cell))
+                    .count();
+            html.push_str(&run_to_html(&cells[start..end]));
+            start = end;
+        }
+        html
+    });
+    format!("<pre style=\"{PRE_STYLE}\">{}</pre>", rows.join("\n"))
+}
+
+/// Renders the buffer as plain text.
+///
+/// Rows are separated with newlines and their trailing whitespace is removed.
+pub fn to_text(buffer: &Buffer) -> String {
+    buffer_text(buffer, buffer.area)
+}
+
+/// Font of the exported images.
+const PNG_FONT: &str = "16px monospace";
+
+/// Height of a line of the exported images in pixels.
+const PNG_LINE_HEIGHT: f64 = 19.0;
+
+/// Renders the buffer as a PNG image and returns it as a data URL.
+///
+/// The buffer is drawn onto an offscreen canvas with a monospace font on a
+/// black background. The data URL can be used as the `href` of a download
+/// link or the `src` of an image.
+pub fn to_png(buffer: &Buffer) -> Result<String, Error> {
+    let document = web_sys::window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .document()
+        .ok_or(Error::UnableToRetrieveDocument)?;
+    let canvas = document
+        .create_element("canvas")?
+        .unchecked_into::<HtmlCanvasElement>();
+    let context = canvas
+        .get_context("2d")?
+        .ok_or(Error::UnableToRetrieveCanvasContext)?
+        .unchecked_into::<CanvasRenderingContext2d>();
+    context.set_font(PNG_FONT);
+    let cell_width = context.measure_text("M")?.width();
+    let area = buffer.area;
+    canvas.set_width((f64::from(area.width) * cell_width).ceil() as u32);
+    canvas.set_height((f64::from(area.height) * PNG_LINE_HEIGHT).ceil() as u32);
+    // Resizing the canvas resets the state of the context.
+    context.set_text_baseline("top");
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let Some(cell) = buffer.cell((x, y)) else {
+                continue;
+            };
+            let (fg, bg) = get_cell_color_for_canvas(cell, Color::Black);
+            let (fg, bg) = if cell.modifier.contains(Modifier::REVERSED) {
+                (bg, fg)
+            } else {
+                (fg, bg)
+            };
+            let (left, top) = (
+                f64::from(x - area.left()) * cell_width,
+                f64::from(y - area.top()) * PNG_LINE_HEIGHT,
+            );
+            context.set_fill_style_str(&bg);
+            context.fill_rect(left, top, cell_width, PNG_LINE_HEIGHT);
+            if cell.modifier.contains(Modifier::HIDDEN) {
+                continue;
+            }
+            context.set_font(if cell.modifier.contains(Modifier::BOLD) {
+                "bold 16px monospace"
+            } else {
+                PNG_FONT
+            });
+            context.set_fill_style_str(&fg);
+            context.fill_text(cell.symbol(), left, top)?;
+        }
+    }
+    Ok(canvas.to_data_url()?)
+}
+
+/// Renders the buffer as a `<noscript>` element.
+///
+/// See [`to_html`].
+pub fn to_noscript(buffer: &Buffer) -> String {
+    format!("<noscript>{}</noscript>", to_html(buffer))
+}
+
+/// Renders a run of cells that share the same style.
+fn run_to_html(cells: &[&Cell]) -> String {
+    let text = cells.iter().map(|cell| cell.symbol()).collect::<String>();
+    let style = get_cell_style_as_css(cells[0]);
+    format!("<span style=\"{style}\">{}</span>", escape_html(&text))
+}
+
+/// Escapes a string for use in HTML text and attribute values.
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,188 @@
+//! ## Glyph substitution
+//!
+//! Fonts do not cover every character that terminal applications use, e.g.
+//! the private-use icons of the patched "Nerd" fonts or some box-drawing
+//! characters. The [`GlyphTable`] replaces the symbols of the cells at render
+//! time, so these font-fallback workarounds live in one place instead of
+//! every widget.
+//!
+//! Every substitution belongs to a [`GlyphLevel`] and applies from that level
+//! of degradation on, so the application can switch to plainer glyphs when
+//! the font turns out to lack them:
+//!
+//! ```rust no_run
+//! use ratzilla::glyphs::{set_glyph_level, set_glyph_table, GlyphLevel, GlyphTable};
+//!
+//! set_glyph_table(
+//!     GlyphTable::new()
+//!         // Always replace an icon of a patched font with an emoji.
+//!         .substitute("\u{f07b}", "📁", GlyphLevel::Full)
+//!         // Only replace the box drawing in the ASCII level.
+//!         .with_ascii_box_drawing(),
+//! );
+//! set_glyph_level(GlyphLevel::Ascii);
+//! ```
+//!
+//! Substitutions should keep the width of the symbol: a wide emoji can only
+//! replace a wide symbol, otherwise the line is garbled (see [`cells`]).
+//!
+//! [`cells`]: crate::cells
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use ratatui::buffer::Buffer;
+
+use crate::render;
+
+thread_local! {
+    /// Global glyph table.
+    static GLYPH_TABLE: RefCell<GlyphTable> = RefCell::new(GlyphTable::new());
+    /// Current level of degradation.
+    static GLYPH_LEVEL: Cell<GlyphLevel> = const { Cell::new(GlyphLevel::Full) };
+}
+
+/// Symbols replaced by [`GlyphTable::with_ascii_box_drawing`].
+const ASCII_BOX_DRAWING: &[(&str, &str)] = &[
+    ("─", "-"),
+    ("━", "-"),
+    ("═", "="),
+    ("│", "|"),
+    ("┃", "|"),
+    ("║", "|"),
+    ("┌", "+"),
+    ("┐", "+"),
+    ("└", "+"),
+    ("┘", "+"),
+    ("╭", "+"),
+    ("╮", "+"),
+    ("╰", "+"),
+    ("╯", "+"),
+    ("├", "+"),
+    ("┤", "+"),
+    ("┬", "+"),
+    ("┴", "+"),
+    ("┼", "+"),
+    ("┏", "+"),
+    ("┓", "+"),
+    ("┗", "+"),
+    ("┛", "+"),
+    ("╔", "+"),
+    ("╗", "+"),
+    ("╚", "+"),
+    ("╝", "+"),
+    ("█", "#"),
+    ("▓", "#"),
+    ("▒", ":"),
+    ("░", "."),
+    ("•", "*"),
+    ("…", "."),
+];
+
+/// Level of degradation of the glyphs.
+///
+/// The levels are ordered from the richest to the plainest glyphs.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum GlyphLevel {
+    /// The font covers everything, only the substitutions of this level
+    /// apply.
+    #[default]
+    Full,
+    /// The font lacks some glyphs, e.g. the private-use icons.
+    Reduced,
+    /// Only ASCII is reliable.
+    Ascii,
+}
+
+/// Substitutions of the symbols of the cells.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct GlyphTable {
+    /// Replacements of the symbols along with the level they apply from.
+    substitutions: HashMap<String, (String, GlyphLevel)>,
+}
+
+impl GlyphTable {
+    /// Constructs a new empty [`GlyphTable`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces a symbol from the given level of degradation on.
+    ///
+    /// A later substitution of the same symbol replaces the earlier one.
+    pub fn substitute<F, T>(mut self, from: F, to: T, level: GlyphLevel) -> Self
+    where
+        F: Into<String>,
+        T: Into<String>,
+    {
+        self.substitutions.insert(from.into(), (to.into(), level));
+        self
+    }
+
+    /// Replaces the box-drawing and block characters with ASCII in the
+    /// [`GlyphLevel::Ascii`] level.
+    pub fn with_ascii_box_drawing(mut self) -> Self {
+        for (from, to) in ASCII_BOX_DRAWING {
+            self = self.substitute(*from, *to, GlyphLevel::Ascii);
+        }
+        self
+    }
+
+    /// Returns `true` if the table has no substitutions.
+    pub fn is_empty(&self) -> bool {
+        self.substitutions.is_empty()
+    }
+
+    /// Returns the replacement of a symbol in the given level, if any.
+    pub fn get(&self, symbol: &str, level: GlyphLevel) -> Option<&str> {
+        self.substitutions
+            .get(symbol)
+            .filter(|(_, from)| *from <= level)
+            .map(|(to, _)| to.as_str())
+    }
+
+    /// Replaces the symbols of the cells of a buffer in the given level.
+    pub fn apply(&self, buffer: &mut Buffer, level: GlyphLevel) {
+        if self.is_empty() {
+            return;
+        }
+        for cell in &mut buffer.content {
+            if let Some(to) = self.get(cell.symbol(), level) {
+                cell.set_symbol(to);
+            }
+        }
+    }
+}
+
+/// Sets the global glyph table.
+///
+/// The table applies to the frames rendered from now on.
+pub fn set_glyph_table(table: GlyphTable) {
+    GLYPH_TABLE.with(|current| current.replace(table));
+    render::request_redraw();
+}
+
+/// Returns the global glyph table.
+pub fn glyph_table() -> GlyphTable {
+    GLYPH_TABLE.with(|table| table.borrow().clone())
+}
+
+/// Sets the current level of degradation.
+///
+/// The default is [`GlyphLevel::Full`].
+pub fn set_glyph_level(level: GlyphLevel) {
+    GLYPH_LEVEL.set(level);
+    render::request_redraw();
+}
+
+/// Returns the current level of degradation.
+pub fn glyph_level() -> GlyphLevel {
+    GLYPH_LEVEL.get()
+}
+
+/// Applies the global glyph table to a frame.
+pub(crate) fn apply(buffer: &mut Buffer) {
+    GLYPH_TABLE.with(|table| table.borrow().apply(buffer, GLYPH_LEVEL.get()));
+}
@@ -0,0 +1,207 @@
+//! ## Rendering health
+//!
+//! Collects statistics about the rendering of the session (frame times,
+//! dropped frames, errors and resizes) that can be exported as JSON at any
+//! time, e.g. to collect real-user performance data from a deployed site:
+//!
+//! ```rust no_run
+//! use ratzilla::health;
+//!
+//! let report = health::report();
+//! if report.dropped_frames > 0 {
+//!     let json = report.to_json();
+//!     // Send the JSON to the monitoring service...
+//! }
+//! ```
+//!
+//! The statistics are collected in constant memory, so they can be kept for
+//! the whole session.
+
+use std::cell::{Cell, RefCell};
+
+use crate::{render, utils};
+
+/// Upper bounds of the buckets of the frame time histogram in milliseconds.
+///
+/// The last bucket holds the frames that took longer than the last bound.
+pub const FRAME_TIME_BUCKETS: [f64; 7] = [2.0, 4.0, 8.0, 16.7, 33.3, 50.0, 100.0];
+
+thread_local! {
+    /// Statistics collected so far.
+    static STATS: RefCell<HealthReport> = RefCell::new(HealthReport::default());
+    /// Start of the collection in milliseconds since the time origin of the
+    /// page.
+    static STARTED_AT: Cell<Option<f64>> = const { Cell::new(None) };
+    /// Number of frames skipped by the render loop before the last reset.
+    static SKIPPED_BEFORE_RESET: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Statistics about the rendering of the session.
+///
+/// See [`report`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HealthReport {
+    /// Duration of the collection in milliseconds.
+    pub duration: f64,
+    /// Number of rendered frames.
+    pub frames: u64,
+    /// Number of frames per bucket of [`FRAME_TIME_BUCKETS`], plus the
+    /// frames slower than the last bucket.
+    pub frame_time_histogram: [u64; FRAME_TIME_BUCKETS.len() + 1],
+    /// Mean frame time in milliseconds.
+    pub mean_frame_time: f64,
+    /// Longest frame time in milliseconds.
+    pub max_frame_time: f64,
+    /// Number of frames that exceeded the frame budget.
+    pub slow_frames: u64,
+    /// Number of animation frames skipped under heavy load, see
+    /// [`WebRenderer::set_frame_skipping`](crate::WebRenderer::set_frame_skipping).
+    pub dropped_frames: u64,
+    /// Number of errors returned by the render callback.
+    pub errors: u64,
+    /// Number of panics in the render callback.
+    pub panics: u64,
+    /// Number of errors that stopped the render loop.
+    pub fatal_errors: u64,
+    /// Number of times the grid was resized.
+    pub resizes: u64,
+}
+
+impl HealthReport {
+    /// Serializes the report as a JSON object.
+    ///
+    /// The histogram is an array of `{"le": bound, "count": n}` objects with
+    /// `null` as the bound of the last bucket.
+    pub fn to_json(&self) -> String {
+        let histogram = self
+            .frame_time_histogram
+            .iter()
+            .enumerate()
+            .map(|(i, count)| {
+                let bound = FRAME_TIME_BUCKETS
+                    .get(i)
+                    .map_or_else(|| "null".to_string(), |bound| bound.to_string());
+                format!(r#"{{"le":{bound},"count":{count}}}"#)
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            concat!(
+                r#"{{"duration":{:.1},"frames":{},"frameTimeHistogram":[{}],"#,
+                r#""meanFrameTime":{:.3},"maxFrameTime":{:.3},"slowFrames":{},"#,
+                r#""droppedFrames":{},"errors":{},"panics":{},"fatalErrors":{},"resizes":{}}}"#
+            ),
+            self.duration,
+            self.frames,
+            histogram,
+            self.mean_frame_time,
+            self.max_frame_time,
+            self.slow_frames,
+            self.dropped_frames,
+            self.errors,
+            self.panics,
+            self.fatal_errors,
+            self.resizes,
+        )
+    }
+
+    /// Adds a frame with the given duration and budget in milliseconds.
+    fn add_frame(&mut self, duration: f64, budget: f64) {
+        let bucket = FRAME_TIME_BUCKETS
+            .iter()
+            .position(|bound| duration <= *bound)
+            .unwrap_or(FRAME_TIME_BUCKETS.len());
+        self.frame_time_histogram[bucket] += 1;
+        self.frames += 1;
+        self.mean_frame_time += (duration - self.mean_frame_time) / self.frames as f64;
+        self.max_frame_time = self.max_frame_time.max(duration);
+        if duration > budget {
+            self.slow_frames += 1;
+        }
+    }
+}
+
+/// Returns the statistics collected so far.
+pub fn report() -> HealthReport {
+    let mut report = STATS.with(|stats| stats.borrow().clone());
+    report.duration = STARTED_AT
+        .get()
+        .map_or(0.0, |started_at| utils::performance_now() - started_at);
+    report.dropped_frames = render::skipped_frames() - SKIPPED_BEFORE_RESET.get();
+    report
+}
+
+/// Clears the statistics, e.g. after they were sent.
+pub fn reset() {
+    STATS.with(|stats| *stats.borrow_mut() = HealthReport::default());
+    SKIPPED_BEFORE_RESET.set(render::skipped_frames());
+    STARTED_AT.set(Some(utils::performance_now()));
+}
+
+/// Records a rendered frame with the given duration and budget in
+/// milliseconds.
+pub(crate) fn record_frame(duration: f64, budget: f64) {
+    if STARTED_AT.get().is_none() {
+        STARTED_AT.set(Some(utils::performance_now() - duration));
+    }
+    STATS.with(|stats| stats.borrow_mut().add_frame(duration, budget));
+}
+
+/// Records an error returned by the render callback.
+pub(crate) fn record_error() {
+    STATS.with(|stats| stats.borrow_mut().errors += 1);
+}
+
+/// Records a panic in the render callback.
+pub(crate) fn record_panic() {
+    STATS.with(|stats| stats.borrow_mut().panics += 1);
+}
+
+/// Records an error that stopped the render loop.
+pub(crate) fn record_fatal_error() {
+    STATS.with(|stats| stats.borrow_mut().fatal_errors += 1);
+}
+
+/// Records a resize of the grid.
+pub(crate) fn record_resize() {
+    STATS.with(|stats| stats.borrow_mut().resizes += 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames() {
+        let mut report = HealthReport::default();
+        for duration in [1.0, 10.0, 20.0, 500.0] {
+            report.add_frame(duration, 16.7);
+        }
+        assert_eq!(report.frames, 4);
+        assert_eq!(report.frame_time_histogram, [1, 0, 0, 1, 1, 0, 0, 1]);
+        assert_eq!(report.mean_frame_time, 132.75);
+        assert_eq!(report.max_frame_time, 500.0);
+        assert_eq!(report.slow_frames, 2);
+    }
+
+    #[test]
+    fn to_json() {
+        let mut report = HealthReport {
+            errors: 2,
+            resizes: 1,
+            ..Default::default()
+        };
+        report.add_frame(3.0, 16.7);
+        assert_eq!(
+            report.to_json(),
+            concat!(
+                r#"{"duration":0.0,"frames":1,"frameTimeHistogram":["#,
+                r#"{"le":2,"count":0},{"le":4,"count":1},{"le":8,"count":0},"#,
+                r#"{"le":16.7,"count":0},{"le":33.3,"count":0},{"le":50,"count":0},"#,
+                r#"{"le":100,"count":0},{"le":null,"count":0}],"#,
+                r#""meanFrameTime":3.000,"maxFrameTime":3.000,"slowFrames":0,"#,
+                r#""droppedFrames":0,"errors":2,"panics":0,"fatalErrors":0,"resizes":1}"#
+            )
+        );
+    }
+}
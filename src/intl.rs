@@ -0,0 +1,242 @@
+//! ## Number formatting
+//!
+//! Formats numbers for the locale of the user with the
+//! [`Intl.NumberFormat`] API of the browser, e.g. for the labels of gauges
+//! and the axes of charts:
+//!
+//! ```rust no_run
+//! use ratzilla::{
+//!     intl::{format_number, format_percent, NumberFormat},
+//!     ratatui::widgets::Gauge,
+//! };
+//!
+//! let ratio = 0.4219;
+//! // "42%" in English, "42 %" in French...
+//! let gauge = Gauge::default().label(format_percent(ratio)).ratio(ratio);
+//! // "1,234.5" in English, "1.234,5" in German...
+//! let total = format_number(1234.5);
+//! // "1.2K" in English...
+//! let compact = NumberFormat::new().compact(true).format(1234.5);
+//! ```
+//!
+//! The formatters of the browser are cached, so formatting every frame is
+//! cheap. If the browser rejects the options (e.g. an invalid locale), the
+//! numbers are formatted without localization.
+//!
+//! [`Intl.NumberFormat`]: https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Global_Objects/Intl/NumberFormat
+
+use std::{cell::RefCell, collections::HashMap};
+
+use web_sys::{
+    js_sys::{global, Array, Function, Intl, Object, Reflect},
+    wasm_bindgen::{JsCast, JsValue},
+    window,
+};
+
+/// Locale that is used when the browser does not report one.
+const DEFAULT_LOCALE: &str = "en-US";
+
+thread_local! {
+    /// Formatters of the browser by their options.
+    static FORMATTERS: RefCell<HashMap<NumberFormat, Option<Intl::NumberFormat>>> = RefCell::new(HashMap::new());
+}
+
+/// Style of a [`NumberFormat`].
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub enum NumberStyle {
+    /// Plain number, e.g. `1,234.5`.
+    #[default]
+    Decimal,
+    /// Ratio as a percentage, e.g. `0.42` as `42%`.
+    Percent,
+    /// Amount of money in the given ISO 4217 currency, e.g. `EUR`.
+    Currency(String),
+}
+
+/// Options for formatting numbers.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct NumberFormat {
+    /// BCP 47 language tag, the locale of the user if `None`.
+    locale: Option<String>,
+    /// Style of the number.
+    style: NumberStyle,
+    /// Minimum and maximum number of fraction digits.
+    fraction_digits: Option<(u8, u8)>,
+    /// Whether large numbers are abbreviated, e.g. `1.2K`.
+    compact: bool,
+    /// Whether the thousands are grouped.
+    grouping: bool,
+}
+
+impl NumberFormat {
+    /// Constructs a new [`NumberFormat`] for plain numbers in the locale of
+    /// the user.
+    pub fn new() -> Self {
+        Self {
+            grouping: true,
+            ..Default::default()
+        }
+    }
+
+    /// Constructs a new [`NumberFormat`] for percentages.
+    ///
+    /// The formatted values are ratios, i.e. `1.0` is `100%`.
+    pub fn percent() -> Self {
+        Self::new().style(NumberStyle::Percent)
+    }
+
+    /// Sets the locale as a BCP 47 language tag, e.g. `de-DE`.
+    ///
+    /// Defaults to the locale of the user, see [`locale`].
+    pub fn locale<T: Into<String>>(mut self, locale: T) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Sets the style of the number.
+    pub fn style(mut self, style: NumberStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the minimum and the maximum number of fraction digits.
+    ///
+    /// By default, the browser picks them based on the style.
+    pub fn fraction_digits(mut self, min: u8, max: u8) -> Self {
+        self.fraction_digits = Some((min.min(max), max));
+        self
+    }
+
+    /// Sets whether large numbers are abbreviated, e.g. `1.2K` or `3M`.
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Sets whether the thousands are grouped, e.g. `1,234`. Defaults to
+    /// `true`.
+    pub fn grouping(mut self, grouping: bool) -> Self {
+        self.grouping = grouping;
+        self
+    }
+
+    /// Formats the number.
+    pub fn format(&self, value: f64) -> String {
+        let formatter = FORMATTERS.with(|formatters| {
+            formatters
+                .borrow_mut()
+                .entry(self.clone())
+                .or_insert_with(|| self.to_js())
+                .clone()
+        });
+        formatter
+            .and_then(|formatter| {
+                formatter
+                    .format()
+                    .call1(&JsValue::NULL, &JsValue::from_f64(value))
+                    .ok()
+            })
+            .and_then(|formatted| formatted.as_string())
+            .unwrap_or_else(|| self.format_fallback(value))
+    }
+
+    /// Constructs the formatter of the browser, or `None` if the options are
+    /// rejected.
+    fn to_js(&self) -> Option<Intl::NumberFormat> {
+        let options = Object::new();
+        let set = |key: &str, value: JsValue| Reflect::set(&options, &key.into(), &value);
+        let style = match &self.style {
+            NumberStyle::Decimal => "decimal",
+            NumberStyle::Percent => "percent",
+            NumberStyle::Currency(currency) => {
+                set("currency", currency.into()).ok()?;
+                "currency"
+            }
+        };
+        set("style", style.into()).ok()?;
+        if let Some((min, max)) = self.fraction_digits {
+            set("minimumFractionDigits", min.into()).ok()?;
+            set("maximumFractionDigits", max.into()).ok()?;
+        }
+        if self.compact {
+            set("notation", "compact".into()).ok()?;
+        }
+        set("useGrouping", self.grouping.into()).ok()?;
+        let locale = self.locale.clone().unwrap_or_else(locale);
+        let constructor = Reflect::get(&global(), &"Intl".into())
+            .and_then(|intl| Reflect::get(&intl, &"NumberFormat".into()))
+            .ok()?
+            .dyn_into::<Function>()
+            .ok()?;
+        let arguments = Array::of2(&Array::of1(&locale.into()), &options);
+        Reflect::construct(&constructor, &arguments)
+            .ok()
+            .map(JsCast::unchecked_into)
+    }
+
+    /// Formats the number without localization.
+    fn format_fallback(&self, value: f64) -> String {
+        let (value, suffix) = match &self.style {
+            NumberStyle::Decimal => (value, String::new()),
+            NumberStyle::Percent => (value * 100.0, "%".to_string()),
+            NumberStyle::Currency(currency) => (value, format!(" {currency}")),
+        };
+        let (min, max) = self.fraction_digits.map_or(
+            match &self.style {
+                NumberStyle::Decimal => (0, 3),
+                NumberStyle::Percent => (0, 0),
+                NumberStyle::Currency(_) => (2, 2),
+            },
+            |(min, max)| (min as usize, max as usize),
+        );
+        let mut formatted = format!("{value:.max$}");
+        // Trim the trailing zeros down to the minimum number of fraction
+        // digits.
+        if max > min {
+            let trimmed = formatted.trim_end_matches('0').len();
+            formatted.truncate(trimmed.max(formatted.len() - (max - min)));
+            if formatted.ends_with('.') {
+                formatted.pop();
+            }
+        }
+        formatted + &suffix
+    }
+}
+
+/// Returns the locale of the user as a BCP 47 language tag, e.g. `en-US`.
+pub fn locale() -> String {
+    window()
+        .and_then(|window| window.navigator().language())
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// Formats a number in the locale of the user, e.g. `1,234.5`.
+pub fn format_number(value: f64) -> String {
+    NumberFormat::new().format(value)
+}
+
+/// Formats a ratio as a percentage in the locale of the user, e.g. `0.42` as
+/// `42%`.
+pub fn format_percent(ratio: f64) -> String {
+    NumberFormat::percent().format(ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_fallback() {
+        let format = NumberFormat::new();
+        assert_eq!(format.format_fallback(1234.5), "1234.5");
+        assert_eq!(format.format_fallback(2.0), "2");
+        assert_eq!(format.format_fallback(1.23456), "1.235");
+        let format = NumberFormat::new().fraction_digits(2, 2);
+        assert_eq!(format.format_fallback(2.0), "2.00");
+        assert_eq!(NumberFormat::percent().format_fallback(0.4219), "42%");
+        let format = NumberFormat::percent().fraction_digits(1, 2);
+        assert_eq!(format.format_fallback(0.5), "50.0%");
+        let format = NumberFormat::new().style(NumberStyle::Currency("EUR".to_string()));
+        assert_eq!(format.format_fallback(3.5), "3.50 EUR");
+    }
+}
@@ -0,0 +1,154 @@
+//! ## Virtual keyboard
+//!
+//! Touch devices only show their on-screen keyboard when an editable element
+//! is focused. [`show`] focuses a hidden `<input>` element whose input is
+//! turned into key events, including the text composed with an IME, so the
+//! application handles them like the input of a physical keyboard.
+//!
+//! ```rust no_run
+//! use ratzilla::keyboard;
+//!
+//! // Show the keyboard whenever the grid is tapped:
+//! keyboard::set_show_on_tap(true);
+//! ```
+//!
+//! Browsers only show the keyboard in response to a user action, so call
+//! [`show`] from an event handler (e.g. on a tap) rather than on start-up.
+
+use std::cell::{Cell, RefCell};
+
+use web_sys::{
+    wasm_bindgen::{prelude::Closure, JsCast},
+    window, Element, HtmlInputElement, InputEvent,
+};
+
+use crate::{
+    error::Error,
+    event::{self, KeyCode},
+};
+
+/// Id of the hidden input element.
+pub(crate) const INPUT_ID: &str = "ratzilla-keyboard";
+
+/// Style of the hidden input element.
+///
+/// The element must not be `display: none` or `visibility: hidden` to get the
+/// focus, so it is made transparent and moved out of the way instead.
+const INPUT_STYLE: &str = "position: fixed; left: 0; bottom: 0; width: 1px; height: 1px; \
+    opacity: 0; border: 0; padding: 0; font-size: 16px;";
+
+thread_local! {
+    /// Hidden input element, created on first use.
+    static INPUT: RefCell<Option<HtmlInputElement>> = const { RefCell::new(None) };
+    /// Whether the keyboard is shown when the grid is tapped.
+    static SHOW_ON_TAP: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Shows the virtual keyboard by focusing the hidden input element.
+pub fn show() -> Result<(), Error> {
+    let input = input()?;
+    input.focus()?;
+    Ok(())
+}
+
+/// Hides the virtual keyboard by removing the focus from the hidden input
+/// element.
+pub fn hide() -> Result<(), Error> {
+    if let Some(input) = INPUT.with(|input| input.borrow().clone()) {
+        input.blur()?;
+    }
+    Ok(())
+}
+
+/// Returns `true` if the hidden input element has the focus, i.e. the
+/// virtual keyboard is shown on touch devices.
+pub fn is_shown() -> bool {
+    let Some(input) = INPUT.with(|input| input.borrow().clone()) else {
+        return false;
+    };
+    window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.active_element())
+        .is_some_and(|active| &active == input.unchecked_ref::<Element>())
+}
+
+/// Sets whether the virtual keyboard is shown when the grid is tapped.
+///
+/// This is disabled by default.
+pub fn set_show_on_tap(show_on_tap: bool) {
+    SHOW_ON_TAP.set(show_on_tap);
+}
+
+/// Shows the keyboard after a tap on the grid if enabled.
+///
+/// This is called by the touch listeners, while the tap is being handled.
+pub(crate) fn tapped() {
+    if SHOW_ON_TAP.get() {
+        let _ = show();
+    }
+}
+
+/// Returns the hidden input element, creating it if needed.
+fn input() -> Result<HtmlInputElement, Error> {
+    if let Some(input) = INPUT.with(|input| input.borrow().clone()) {
+        return Ok(input);
+    }
+    let document = window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .document()
+        .ok_or(Error::UnableToRetrieveDocument)?;
+    let input = document
+        .create_element("input")?
+        .unchecked_into::<HtmlInputElement>();
+    input.set_id(INPUT_ID);
+    input.set_attribute("style", INPUT_STYLE)?;
+    input.set_attribute("autocomplete", "off")?;
+    input.set_attribute("autocorrect", "off")?;
+    input.set_attribute("autocapitalize", "off")?;
+    input.set_attribute("spellcheck", "false")?;
+    input.set_attribute("aria-hidden", "true")?;
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: InputEvent| {
+        // The text composed with an IME is reported by the composition
+        // events.
+        if event.is_composing() {
+            return;
+        }
+        let timestamp = event.time_stamp();
+        match event.input_type().as_str() {
+            "insertText" => {
+                for char in event.data().unwrap_or_default().chars() {
+                    event::dispatch_virtual_key(KeyCode::Char(char), char.to_string(), timestamp);
+                }
+            }
+            "insertLineBreak" | "insertParagraph" => {
+                event::dispatch_virtual_key(KeyCode::Enter, "Enter".into(), timestamp)
+            }
+            "deleteContentBackward" => {
+                event::dispatch_virtual_key(KeyCode::Backspace, "Backspace".into(), timestamp)
+            }
+            "deleteContentForward" => {
+                event::dispatch_virtual_key(KeyCode::Delete, "Delete".into(), timestamp)
+            }
+            _ => {}
+        }
+        if let Some(input) = event
+            .target()
+            .and_then(|target| target.dyn_into::<HtmlInputElement>().ok())
+        {
+            input.set_value("");
+        }
+    });
+    input.add_event_listener_with_callback("input", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    document
+        .body()
+        .ok_or(Error::UnableToRetrieveBody)?
+        .append_child(&input)?;
+    INPUT.with(|current| current.replace(Some(input.clone())));
+    Ok(input)
+}
+
+/// Returns `true` if the element is the hidden input element.
+pub(crate) fn is_input(element: &Element) -> bool {
+    element.id() == INPUT_ID
+}
@@ -1,12 +1,84 @@
 #![warn(missing_docs, clippy::unwrap_used)]
 #![doc = include_str!("../README.md")]
 
+/// Interaction analytics.
+pub mod analytics;
+
+/// Frame capture.
+pub mod capture;
+
+/// Cell symbol helpers.
+pub mod cells;
+
+/// Clipboard helpers.
+pub mod clipboard;
+
+/// Browser compatibility check.
+pub mod compat;
+
+/// Console logging.
+pub mod console;
+
+/// Frame step debugger.
+pub mod debugger;
+
+/// Visual effects.
+pub mod effects;
+
 /// Custom error type.
 pub mod error;
 
+/// Error overlay.
+pub mod error_overlay;
+
+/// Static export.
+pub mod export;
+
 /// Event/input handling.
 pub mod event;
 
+/// Glyph substitution.
+pub mod glyphs;
+
+/// Rendering health.
+pub mod health;
+
+/// Number formatting.
+pub mod intl;
+
+/// Virtual keyboard.
+pub mod keyboard;
+
+/// Color palette.
+pub mod palette;
+
+/// Shareable links.
+pub mod permalink;
+
+/// Native input prompts.
+pub mod prompt;
+
+/// Responsive helpers.
+pub mod responsive;
+
+/// Search in the screen.
+pub mod search;
+
+/// Text selection.
+pub mod selection;
+
+/// Splash screen.
+pub mod splash;
+
+/// Startup timings.
+pub mod startup;
+
+/// Time-travel debugging.
+pub mod time_travel;
+
+/// Toast notifications.
+pub mod toast;
+
 /// Web utility functions.
 pub mod utils;
 
@@ -16,11 +88,22 @@ pub mod widgets;
 /// Backend.
 pub mod backend;
 
+/// Region metadata.
+mod metadata;
+
 /// Rendering.
 mod render;
 
 // Re-export ratatui crate.
 pub use ratatui;
 
-pub use backend::{canvas::CanvasBackend, dom::DomBackend};
-pub use render::WebRenderer;
+pub use backend::{canvas::CanvasBackend, dom::DomBackend, headless::HeadlessBackend};
+pub use render::{RenderHandle, RenderMode, ResizeAnchor, WebRenderer};
+
+/// Starts the thread pool of the `threads` feature.
+///
+/// The returned promise must be awaited from JavaScript before the first
+/// frame, e.g. `await wasm.initThreadPool(navigator.hardwareConcurrency)`.
+/// Without a thread pool, the rows are processed on the main thread.
+#[cfg(all(feature = "threads", target_arch = "wasm32"))]
+pub use wasm_bindgen_rayon::init_thread_pool;
@@ -0,0 +1,130 @@
+//! ## Metadata
+//!
+//! Widgets can only write [`Cell`]s into the buffer, which leaves no room for
+//! web-specific information such as DOM attributes. This module provides a
+//! side channel: widgets register [`Region`]s while rendering and the backend
+//! reads them back when flushing the frame.
+//!
+//! [`Cell`]: ratatui::buffer::Cell
+
+use std::cell::RefCell;
+
+use ratatui::layout::Rect;
+
+use crate::effects::EffectKind;
+
+/// The kind of metadata attached to a region.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RegionKind {
+    /// `data-*` attributes to set on the cells of the region.
+    DataAttributes(Vec<(String, String)>),
+    /// Secret content that must never end up in the DOM.
+    ///
+    /// The non-blank cells of the region are replaced with the given mask
+    /// character.
+    Secret(char),
+    /// Text to copy to the clipboard when the region is clicked.
+    CopyButton(String),
+    /// Hyperlink that the region is rendered as.
+    Hyperlink {
+        /// URL of the hyperlink.
+        url: String,
+        /// Browsing context to open the URL in.
+        target: Option<String>,
+    },
+    /// Text that is revealed progressively.
+    Typewriter {
+        /// Name of the effect.
+        name: String,
+        /// Number of cells revealed per second.
+        speed: f64,
+    },
+    /// Text decorations drawn over the cells.
+    Decoration {
+        /// CSS color of the overline, if any.
+        overline: Option<String>,
+        /// CSS color of the strikethrough line, if any.
+        strikethrough: Option<String>,
+    },
+    /// Lines that are updated first when the update budget of a frame is
+    /// exceeded.
+    Priority,
+    /// Cell effect that runs over time.
+    Effect {
+        /// Name of the effect.
+        name: String,
+        /// Kind of the effect.
+        kind: EffectKind,
+        /// Duration of the effect in milliseconds.
+        duration: f64,
+    },
+}
+
+/// Attribute that holds the text to copy for the cells of a copy button.
+pub(crate) const COPY_ATTRIBUTE: &str = "data-ratzilla-copy";
+
+/// Attribute that holds the color of the overline of a decorated cell.
+pub(crate) const OVERLINE_ATTRIBUTE: &str = "data-ratzilla-overline";
+
+/// Attribute that holds the color of the strikethrough line of a decorated
+/// cell.
+pub(crate) const STRIKETHROUGH_ATTRIBUTE: &str = "data-ratzilla-strikethrough";
+
+impl RegionKind {
+    /// Returns the DOM attributes to set on the cells of the region.
+    pub(crate) fn attributes(&self) -> Vec<(&str, &str)> {
+        match self {
+            Self::DataAttributes(attributes) => attributes
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect(),
+            Self::Secret(_)
+            | Self::Hyperlink { .. }
+            | Self::Typewriter { .. }
+            | Self::Priority
+            | Self::Effect { .. } => Vec::new(),
+            Self::CopyButton(text) => vec![(COPY_ATTRIBUTE, text.as_str())],
+            Self::Decoration {
+                overline,
+                strikethrough,
+            } => [
+                (OVERLINE_ATTRIBUTE, overline),
+                (STRIKETHROUGH_ATTRIBUTE, strikethrough),
+            ]
+            .into_iter()
+            .filter_map(|(name, color)| color.as_deref().map(|color| (name, color)))
+            .collect(),
+        }
+    }
+}
+
+/// A region of the screen with attached metadata.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Region {
+    /// Area of the region.
+    pub(crate) area: Rect,
+    /// Metadata of the region.
+    pub(crate) kind: RegionKind,
+}
+
+thread_local! {
+    /// Regions registered during the current frame.
+    static REGIONS: RefCell<Vec<Region>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Clears the regions registered during the previous frame.
+///
+/// This is called before the render callback is invoked.
+pub(crate) fn begin_frame() {
+    REGIONS.with(|regions| regions.borrow_mut().clear());
+}
+
+/// Registers a region for the current frame.
+pub(crate) fn register(area: Rect, kind: RegionKind) {
+    REGIONS.with(|regions| regions.borrow_mut().push(Region { area, kind }));
+}
+
+/// Returns the regions registered during the current frame.
+pub(crate) fn regions() -> Vec<Region> {
+    REGIONS.with(|regions| regions.borrow().clone())
+}
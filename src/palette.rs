@@ -0,0 +1,258 @@
+//! ## Color palette
+//!
+//! Terminals let the user pick the 16 base ANSI colors, and so does Ratzilla:
+//! the [`Palette`] maps [`Color::Black`] to [`Color::White`] (and the first
+//! 16 [`Color::Indexed`] colors) to RGB values, so the colors can match the
+//! theme of the site.
+//!
+//! The rest of the 256-color palette follows the standard xterm table and
+//! [`Color::Rgb`] colors are used as is.
+//!
+//! With [`set_css_variables`], the DOM backend refers to the base colors with
+//! CSS variables such as `var(--ansi-red)` instead. The page can then retheme
+//! the grid live by changing the variables, without rendering the cells
+//! again:
+//!
+//! ```css
+//! :root { --ansi-red: #e06c75; --ansi-blue: #61afef; }
+//! ```
+//!
+//! The palette is the fallback of the variables that are not defined, and
+//! [`Palette::to_css`] defines all of them.
+//!
+//! ```rust no_run
+//! use ratzilla::{palette::{set_palette, Palette}, ratatui::style::Color};
+//!
+//! set_palette(
+//!     Palette::new()
+//!         .color(Color::Red, (224, 108, 117))
+//!         .color(Color::Blue, (97, 175, 239)),
+//! );
+//! ```
+
+use std::{cell::Cell, fmt::Write};
+
+use ratatui::style::Color;
+
+thread_local! {
+    /// Global color palette.
+    static PALETTE: Cell<Palette> = const { Cell::new(Palette::DEFAULT) };
+    /// Whether the base colors are output as CSS variables.
+    static CSS_VARIABLES: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Names of the CSS variables of the base colors, in ANSI order.
+const CSS_VARIABLE_NAMES: [&str; 16] = [
+    "--ansi-black",
+    "--ansi-red",
+    "--ansi-green",
+    "--ansi-yellow",
+    "--ansi-blue",
+    "--ansi-magenta",
+    "--ansi-cyan",
+    "--ansi-gray",
+    "--ansi-dark-gray",
+    "--ansi-light-red",
+    "--ansi-light-green",
+    "--ansi-light-yellow",
+    "--ansi-light-blue",
+    "--ansi-light-magenta",
+    "--ansi-light-cyan",
+    "--ansi-white",
+];
+
+/// Levels of the 6x6x6 color cube of the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// RGB values of the 16 base ANSI colors.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Palette {
+    /// Colors in ANSI order, from [`Color::Black`] to [`Color::White`].
+    colors: [(u8, u8, u8); 16],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Palette {
+    /// The default palette.
+    pub const DEFAULT: Self = Self {
+        colors: [
+            (0, 0, 0),
+            (128, 0, 0),
+            (0, 128, 0),
+            (128, 128, 0),
+            (0, 0, 128),
+            (128, 0, 128),
+            (0, 128, 128),
+            (192, 192, 192),
+            (128, 128, 128),
+            (255, 0, 0),
+            (0, 255, 0),
+            (255, 255, 0),
+            (0, 0, 255),
+            (255, 0, 255),
+            (0, 255, 255),
+            (255, 255, 255),
+        ],
+    };
+
+    /// Constructs a new [`Palette`] with the default colors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the RGB value of a base color.
+    ///
+    /// `color` is either one of the 16 named colors or an indexed color below
+    /// 16, other colors are ignored.
+    pub fn color(mut self, color: Color, rgb: (u8, u8, u8)) -> Self {
+        if let Some(index) = base_index(color) {
+            self.colors[index] = rgb;
+        }
+        self
+    }
+
+    /// Returns a stylesheet that defines the CSS variables of the base colors
+    /// on the root element, see [`set_css_variables`].
+    pub fn to_css(&self) -> String {
+        let mut css = String::from(":root {");
+        for (name, (r, g, b)) in CSS_VARIABLE_NAMES.iter().zip(self.colors) {
+            // Writing to a string never fails.
+            let _ = write!(css, " {name}: rgb({r}, {g}, {b});");
+        }
+        css.push_str(" }");
+        css
+    }
+
+    /// Converts a color to a CSS color.
+    ///
+    /// The base colors are CSS variables with the RGB value as fallback if
+    /// [`set_css_variables`] is enabled. Returns `None` for
+    /// [`Color::Reset`].
+    pub fn to_css_color(&self, color: Color) -> Option<String> {
+        let (r, g, b) = self.to_rgb(color)?;
+        match css_variable(color).filter(|_| CSS_VARIABLES.get()) {
+            Some(name) => Some(format!("var({name}, rgb({r}, {g}, {b}))")),
+            None => Some(format!("rgb({r}, {g}, {b})")),
+        }
+    }
+
+    /// Converts a color to an RGB tuple.
+    ///
+    /// Returns `None` for [`Color::Reset`].
+    pub fn to_rgb(&self, color: Color) -> Option<(u8, u8, u8)> {
+        if let Some(index) = base_index(color) {
+            return Some(self.colors[index]);
+        }
+        match color {
+            Color::Rgb(r, g, b) => Some((r, g, b)),
+            Color::Indexed(index @ 16..=231) => {
+                let index = usize::from(index - 16);
+                Some((
+                    CUBE_LEVELS[index / 36],
+                    CUBE_LEVELS[index / 6 % 6],
+                    CUBE_LEVELS[index % 6],
+                ))
+            }
+            Color::Indexed(index @ 232..) => {
+                let level = 8 + (index - 232) * 10;
+                Some((level, level, level))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the index of a base color in the palette.
+fn base_index(color: Color) -> Option<usize> {
+    let index = match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(index @ 0..=15) => usize::from(index),
+        _ => return None,
+    };
+    Some(index)
+}
+
+/// Returns the name of the CSS variable of a base color, e.g.
+/// `--ansi-red` for [`Color::Red`].
+///
+/// Returns `None` for the other colors.
+pub fn css_variable(color: Color) -> Option<&'static str> {
+    base_index(color).map(|index| CSS_VARIABLE_NAMES[index])
+}
+
+/// Sets whether the DOM backend outputs the base colors as CSS variables.
+///
+/// The cells drawn from now on refer to the variables, e.g.
+/// `var(--ansi-red, rgb(128, 0, 0))`, so the page can change the colors
+/// without rendering them again. The canvas backend always draws the palette
+/// colors. This is disabled by default.
+pub fn set_css_variables(enabled: bool) {
+    CSS_VARIABLES.set(enabled);
+}
+
+/// Returns `true` if the base colors are output as CSS variables.
+pub fn css_variables() -> bool {
+    CSS_VARIABLES.get()
+}
+
+/// Sets the global color palette.
+///
+/// The palette is used by both backends for the cells drawn from now on, so
+/// it is best set before the first frame.
+pub fn set_palette(palette: Palette) {
+    PALETTE.set(palette);
+}
+
+/// Returns the global color palette.
+pub fn palette() -> Palette {
+    PALETTE.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn css_colors() {
+        let palette = Palette::new().color(Color::Red, (224, 108, 117));
+        assert_eq!(css_variable(Color::Red), Some("--ansi-red"));
+        assert_eq!(css_variable(Color::Indexed(15)), Some("--ansi-white"));
+        assert_eq!(css_variable(Color::Rgb(1, 2, 3)), None);
+        assert!(palette.to_css().contains("--ansi-red: rgb(224, 108, 117);"));
+        assert_eq!(
+            palette.to_css_color(Color::Red).as_deref(),
+            Some("rgb(224, 108, 117)")
+        );
+        set_css_variables(true);
+        assert_eq!(
+            palette.to_css_color(Color::Red).as_deref(),
+            Some("var(--ansi-red, rgb(224, 108, 117))")
+        );
+        assert_eq!(
+            palette.to_css_color(Color::Rgb(1, 2, 3)).as_deref(),
+            Some("rgb(1, 2, 3)")
+        );
+        assert_eq!(palette.to_css_color(Color::Reset), None);
+        set_css_variables(false);
+    }
+}
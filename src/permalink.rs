@@ -0,0 +1,334 @@
+//! ## Permalinks
+//!
+//! Shareable links to a specific view of the application, e.g. the selected
+//! item and the filter settings. The application serializes its state to a
+//! string in any format (e.g. `"item=42&filter=open"` or JSON), which is
+//! compressed and stored in the fragment of the URL.
+//!
+//! ```rust no_run
+//! use ratzilla::permalink;
+//!
+//! // Keep the address bar up to date, so it can be copied at any time:
+//! permalink::set_state("item=42&filter=open").unwrap();
+//! // Or build a link to share:
+//! let link = permalink::permalink("item=42&filter=open").unwrap();
+//! ```
+//!
+//! When the page is opened with a permalink (or the fragment changes to
+//! another one), the state is dispatched as [`Event::StateFromUrl`] to the
+//! handlers registered with [`WebRenderer::on_event`], before the first
+//! frame is rendered.
+//!
+//! [`Event::StateFromUrl`]: crate::event::Event::StateFromUrl
+//! [`WebRenderer::on_event`]: crate::WebRenderer::on_event
+
+use std::collections::HashMap;
+
+use web_sys::{wasm_bindgen::JsValue, window};
+
+use crate::error::Error;
+
+/// Prefix of the fragments that hold a state.
+pub(crate) const FRAGMENT_PREFIX: &str = "#s=";
+
+/// Characters of the URL-safe base64 alphabet.
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Format of the encoded state, stored in its first byte.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[repr(u8)]
+enum Format {
+    /// The state as is, for short states that do not compress.
+    Raw = 0,
+    /// The state compressed with LZW.
+    Lzw = 1,
+}
+
+/// Largest number of LZW codes.
+const MAX_CODES: usize = 1 << 16;
+
+/// Encodes a state for a URL.
+///
+/// The state is compressed and encoded with the URL-safe base64 alphabet.
+pub fn encode(state: &str) -> String {
+    let compressed = compress(state.as_bytes());
+    let mut bytes = Vec::with_capacity(compressed.len().min(state.len()) + 1);
+    if compressed.len() < state.len() {
+        bytes.push(Format::Lzw as u8);
+        bytes.extend(compressed);
+    } else {
+        bytes.push(Format::Raw as u8);
+        bytes.extend(state.as_bytes());
+    }
+    base64_encode(&bytes)
+}
+
+/// Decodes a state encoded with [`encode`].
+///
+/// Returns `None` if the input is not a valid encoded state.
+pub fn decode(encoded: &str) -> Option<String> {
+    let bytes = base64_decode(encoded)?;
+    let (format, payload) = bytes.split_first()?;
+    let bytes = match *format {
+        format if format == Format::Raw as u8 => payload.to_vec(),
+        format if format == Format::Lzw as u8 => decompress(payload)?,
+        _ => return None,
+    };
+    String::from_utf8(bytes).ok()
+}
+
+/// Returns a link to the current page with the given state.
+pub fn permalink(state: &str) -> Result<String, Error> {
+    let location = window().ok_or(Error::UnableToRetrieveWindow)?.location();
+    let href = location.href()?;
+    let base = href.split('#').next().unwrap_or_default();
+    Ok(format!("{base}{FRAGMENT_PREFIX}{}", encode(state)))
+}
+
+/// Stores the state in the URL of the page.
+///
+/// The current entry of the history is replaced, so the back button is not
+/// affected and no [`Event::StateFromUrl`] is dispatched.
+///
+/// [`Event::StateFromUrl`]: crate::event::Event::StateFromUrl
+pub fn set_state(state: &str) -> Result<(), Error> {
+    let url = permalink(state)?;
+    window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .history()?
+        .replace_state_with_url(&JsValue::NULL, "", Some(&url))?;
+    Ok(())
+}
+
+/// Returns the state stored in the URL of the page, if any.
+pub fn state() -> Option<String> {
+    let hash = window()?.location().hash().ok()?;
+    decode(hash.strip_prefix(FRAGMENT_PREFIX)?)
+}
+
+/// Compresses bytes with LZW.
+///
+/// The codes start with 9 bits and grow up to 16 bits as the dictionary
+/// fills up, they are packed starting from the least significant bit.
+fn compress(input: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    let mut dictionary = HashMap::<(u32, u8), u32>::new();
+    let mut size = 256;
+    let mut bytes = input.iter();
+    let Some(&first) = bytes.next() else {
+        return Vec::new();
+    };
+    let mut current = u32::from(first);
+    for &byte in bytes {
+        if let Some(&code) = dictionary.get(&(current, byte)) {
+            current = code;
+            continue;
+        }
+        writer.write(current, code_width(size));
+        if size < MAX_CODES {
+            dictionary.insert((current, byte), size as u32);
+            size += 1;
+        }
+        current = u32::from(byte);
+    }
+    writer.write(current, code_width(size));
+    writer.finish()
+}
+
+/// Decompresses bytes compressed with [`compress`].
+fn decompress(input: &[u8]) -> Option<Vec<u8>> {
+    let mut reader = BitReader::new(input);
+    let mut dictionary = (0..=255).map(|byte| vec![byte]).collect::<Vec<_>>();
+    let mut output = Vec::new();
+    let Some(first) = reader.read(code_width(dictionary.len())) else {
+        return Some(output);
+    };
+    let mut previous = dictionary.get(first as usize)?.clone();
+    output.extend(&previous);
+    // The dictionary of the decoder is one entry behind the encoder.
+    while let Some(code) = reader.read(code_width(dictionary.len() + 1)) {
+        let entry = match dictionary.get(code as usize) {
+            Some(entry) => entry.clone(),
+            None if code as usize == dictionary.len() => {
+                let mut entry = previous.clone();
+                entry.push(previous[0]);
+                entry
+            }
+            None => return None,
+        };
+        output.extend(&entry);
+        if dictionary.len() < MAX_CODES {
+            let mut new_entry = previous;
+            new_entry.push(entry[0]);
+            dictionary.push(new_entry);
+        }
+        previous = entry;
+    }
+    Some(output)
+}
+
+/// Returns the number of bits of the codes for a dictionary of the given
+/// size.
+fn code_width(size: usize) -> u32 {
+    (usize::BITS - size.leading_zeros()).min(16)
+}
+
+/// Writes codes of varying widths to bytes.
+#[derive(Debug, Default)]
+struct BitWriter {
+    /// Written bytes.
+    bytes: Vec<u8>,
+    /// Bits that do not fill a byte yet.
+    pending: u32,
+    /// Number of pending bits.
+    pending_bits: u32,
+}
+
+impl BitWriter {
+    /// Writes the lowest `width` bits of the code.
+    fn write(&mut self, code: u32, width: u32) {
+        self.pending |= code << self.pending_bits;
+        self.pending_bits += width;
+        while self.pending_bits >= 8 {
+            self.bytes.push(self.pending as u8);
+            self.pending >>= 8;
+            self.pending_bits -= 8;
+        }
+    }
+
+    /// Returns the bytes, padding the last one with zeros.
+    fn finish(mut self) -> Vec<u8> {
+        if self.pending_bits > 0 {
+            self.bytes.push(self.pending as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Reads codes of varying widths from bytes.
+#[derive(Debug)]
+struct BitReader<'a> {
+    /// Bytes to read.
+    bytes: std::slice::Iter<'a, u8>,
+    /// Bits read from the bytes but not returned yet.
+    pending: u32,
+    /// Number of pending bits.
+    pending_bits: u32,
+}
+
+impl<'a> BitReader<'a> {
+    /// Constructs a new [`BitReader`].
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes: bytes.iter(),
+            pending: 0,
+            pending_bits: 0,
+        }
+    }
+
+    /// Reads a code of the given width.
+    ///
+    /// Returns `None` when fewer bits are left, i.e. only the padding.
+    fn read(&mut self, width: u32) -> Option<u32> {
+        while self.pending_bits < width {
+            let byte = self.bytes.next()?;
+            self.pending |= u32::from(*byte) << self.pending_bits;
+            self.pending_bits += 8;
+        }
+        let code = self.pending & ((1 << width) - 1);
+        self.pending >>= width;
+        self.pending_bits -= width;
+        Some(code)
+    }
+}
+
+/// Encodes bytes with the URL-safe base64 alphabet, without padding.
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | u32::from(*byte) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            let index = (group >> (18 - 6 * i)) & 0x3f;
+            encoded.push(BASE64_ALPHABET[index as usize] as char);
+        }
+    }
+    encoded
+}
+
+/// Decodes bytes encoded with [`base64_encode`].
+fn base64_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+    for chunk in encoded.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut group = 0u32;
+        for (i, char) in chunk.iter().enumerate() {
+            let index = BASE64_ALPHABET.iter().position(|c| c == char)?;
+            group |= (index as u32) << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            bytes.push((group >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for state in [
+            "",
+            "a",
+            "item=42&filter=open",
+            "ÄÖÜ → 日本語 🦀",
+            &"abcabcabcabc".repeat(100),
+        ] {
+            assert_eq!(decode(&encode(state)).as_deref(), Some(state));
+        }
+    }
+
+    #[test]
+    fn compresses_repetitive_states() {
+        let state = r#"{"filter":"open","sort":"name"}"#.repeat(50);
+        assert!(encode(&state).len() < state.len() / 2);
+    }
+
+    #[test]
+    fn large_dictionary() {
+        // Enough distinct sequences to fill the dictionary.
+        let state = (0..40_000u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) % 1000).to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        assert_eq!(decode(&encode(&state)), Some(state));
+    }
+
+    #[test]
+    fn base64() {
+        assert_eq!(base64_encode(b"Man"), "TWFu");
+        assert_eq!(base64_encode(b"Ma"), "TWE");
+        assert_eq!(base64_encode(b"M"), "TQ");
+        assert_eq!(base64_encode(&[0xfb, 0xff]), "-_8");
+        assert_eq!(base64_decode("TWFu").as_deref(), Some(&b"Man"[..]));
+        assert_eq!(base64_decode("TWE").as_deref(), Some(&b"Ma"[..]));
+        assert_eq!(base64_decode("T"), None);
+        assert_eq!(base64_decode("T$=A"), None);
+    }
+
+    #[test]
+    fn invalid_states() {
+        assert_eq!(decode(""), None);
+        assert_eq!(decode(&base64_encode(&[7, 1, 2])), None);
+        assert_eq!(
+            decode(&base64_encode(&[Format::Lzw as u8, 0xff, 0xff])),
+            None
+        );
+    }
+}
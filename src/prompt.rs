@@ -0,0 +1,242 @@
+//! ## Native input prompts
+//!
+//! Terminal UIs usually read text by handling key events one by one, which
+//! does not play well with mobile keyboards and password managers. The
+//! [`prompt_text`] helper temporarily focuses a hidden native `<input>` that is
+//! configured for the kind of value being asked for and resolves with the
+//! entered value.
+//!
+//! ```rust no_run
+//! use ratzilla::prompt::{prompt_text, InputMode, PromptOptions};
+//!
+//! async fn ask_pin() -> Option<String> {
+//!     prompt_text(PromptOptions::new(InputMode::Numeric).autocomplete("one-time-code")).await
+//! }
+//! ```
+//!
+//! The returned [`Prompt`] is a plain [`Future`], so it can be awaited with any
+//! executor such as `wasm_bindgen_futures::spawn_local`.
+
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use web_sys::{
+    wasm_bindgen::{prelude::Closure, JsCast},
+    window, HtmlInputElement, KeyboardEvent,
+};
+
+use crate::error::Error;
+
+/// Style of the hidden input element.
+///
+/// The element is kept inside the viewport since some mobile browsers refuse
+/// to show the keyboard for off-screen inputs.
+const INPUT_STYLE: &str = "position: fixed; left: 0; bottom: 0; width: 1px; \
+    height: 1px; opacity: 0; border: 0; padding: 0;";
+
+/// Kind of value a prompt asks for.
+///
+/// This decides which virtual keyboard is shown on mobile devices.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum InputMode {
+    /// Free-form text.
+    #[default]
+    Text,
+    /// Whole numbers.
+    Numeric,
+    /// Fractional numbers.
+    Decimal,
+    /// Email address.
+    Email,
+    /// Telephone number.
+    Tel,
+    /// URL.
+    Url,
+    /// Password, the value is never shown.
+    Password,
+}
+
+impl InputMode {
+    /// Returns the `type` and `inputmode` attributes of the input element.
+    fn attributes(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Text => ("text", "text"),
+            Self::Numeric => ("text", "numeric"),
+            Self::Decimal => ("text", "decimal"),
+            Self::Email => ("email", "email"),
+            Self::Tel => ("tel", "tel"),
+            Self::Url => ("url", "url"),
+            Self::Password => ("password", "text"),
+        }
+    }
+}
+
+/// Options of a prompt.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PromptOptions {
+    /// Kind of value to ask for.
+    mode: InputMode,
+    /// Initial value.
+    value: String,
+    /// Value of the `autocomplete` attribute.
+    autocomplete: Option<String>,
+    /// Maximum length of the value.
+    max_length: Option<u32>,
+}
+
+impl PromptOptions {
+    /// Constructs new [`PromptOptions`] with the given input mode.
+    pub fn new(mode: InputMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the initial value.
+    pub fn value<T: Into<String>>(mut self, value: T) -> Self {
+        self.value = value.into();
+        self
+    }
+
+    /// Sets the `autocomplete` attribute (e.g. `"current-password"`).
+    ///
+    /// This helps password managers and browsers to fill in the value.
+    pub fn autocomplete<T: Into<String>>(mut self, autocomplete: T) -> Self {
+        self.autocomplete = Some(autocomplete.into());
+        self
+    }
+
+    /// Sets the maximum length of the value.
+    pub fn max_length(mut self, max_length: u32) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+}
+
+/// State shared between a [`Prompt`] and its event listeners.
+#[derive(Debug, Default)]
+struct PromptState {
+    /// Result of the prompt, once it is resolved.
+    result: Option<Option<String>>,
+    /// Whether the prompt has been resolved.
+    done: bool,
+    /// Waker of the task awaiting the prompt.
+    waker: Option<Waker>,
+}
+
+impl PromptState {
+    /// Resolves the prompt with the given value.
+    fn resolve(&mut self, value: Option<String>) {
+        if self.done {
+            return;
+        }
+        self.done = true;
+        self.result = Some(value);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A pending prompt.
+///
+/// Resolves with the entered value once Enter is pressed, or with `None` if
+/// the prompt is cancelled with Escape or the input loses focus.
+#[derive(Debug)]
+pub struct Prompt {
+    /// Shared state.
+    state: Rc<RefCell<PromptState>>,
+}
+
+impl Future for Prompt {
+    type Output = Option<String>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.borrow_mut();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None if state.done => Poll::Ready(None),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Asks for a value using a hidden native input element.
+///
+/// Key events typed into the input are not propagated to the handlers
+/// registered with [`WebRenderer::on_key_event`].
+///
+/// [`WebRenderer::on_key_event`]: crate::WebRenderer::on_key_event
+pub fn prompt_text(options: PromptOptions) -> Prompt {
+    let state = Rc::new(RefCell::new(PromptState::default()));
+    if create_input(&options, state.clone()).is_err() {
+        state.borrow_mut().resolve(None);
+    }
+    Prompt { state }
+}
+
+/// Creates, attaches and focuses the input element of a prompt.
+fn create_input(options: &PromptOptions, state: Rc<RefCell<PromptState>>) -> Result<(), Error> {
+    let document = window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .document()
+        .ok_or(Error::UnableToRetrieveDocument)?;
+    let input = document
+        .create_element("input")?
+        .unchecked_into::<HtmlInputElement>();
+    let (input_type, input_mode) = options.mode.attributes();
+    input.set_attribute("type", input_type)?;
+    input.set_attribute("inputmode", input_mode)?;
+    input.set_attribute("style", INPUT_STYLE)?;
+    if let Some(autocomplete) = &options.autocomplete {
+        input.set_attribute("autocomplete", autocomplete)?;
+    }
+    if let Some(max_length) = options.max_length {
+        input.set_attribute("maxlength", &max_length.to_string())?;
+    }
+    input.set_value(&options.value);
+
+    let on_keydown = Closure::<dyn FnMut(_)>::new({
+        let input = input.clone();
+        let state = state.clone();
+        move |event: KeyboardEvent| {
+            event.stop_propagation();
+            let value = match event.key().as_str() {
+                "Enter" => Some(input.value()),
+                "Escape" => None,
+                _ => return,
+            };
+            event.prevent_default();
+            state.borrow_mut().resolve(value);
+            input.remove();
+        }
+    });
+    input.add_event_listener_with_callback("keydown", on_keydown.as_ref().unchecked_ref())?;
+    on_keydown.forget();
+
+    let on_blur = Closure::<dyn FnMut()>::new({
+        let input = input.clone();
+        move || {
+            state.borrow_mut().resolve(None);
+            input.remove();
+        }
+    });
+    input.add_event_listener_with_callback("blur", on_blur.as_ref().unchecked_ref())?;
+    on_blur.forget();
+
+    document
+        .body()
+        .ok_or(Error::UnableToRetrieveBody)?
+        .append_child(&input)?;
+    input.focus()?;
+    Ok(())
+}
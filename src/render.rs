@@ -1,8 +1,221 @@
-use ratatui::{prelude::Backend, Frame, Terminal};
-use std::{cell::RefCell, rc::Rc};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Position, Rect, Size},
+    prelude::Backend,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame, Terminal,
+};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    convert::Infallible,
+    io::Result as IoResult,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+    thread::LocalKey,
+};
 use web_sys::{wasm_bindgen::prelude::*, window};
 
-use crate::event::KeyEvent;
+use crate::{
+    backend::utils::set_stylesheet,
+    capture, clipboard, debugger, effects,
+    error::ErrorReport,
+    error_overlay,
+    event::{
+        self, Capabilities, CopyEvent, EffectEvent, Event, InputBuffering, KeyEvent, KeyPolicy,
+        KeyRepeat, MouseEvent, WheelMode,
+    },
+    glyphs, health, metadata, search, selection, startup, time_travel, toast, utils, web_debug,
+    web_warn,
+};
+
+/// Type of the post-render hooks.
+type PostRenderHook = Box<dyn FnMut(&mut Buffer)>;
+
+/// Type of the render layers.
+type RenderLayer = Box<dyn FnMut(&mut Frame)>;
+
+thread_local! {
+    /// Post-render hooks and their priorities, sorted by priority.
+    static POST_RENDER_HOOKS: RefCell<Vec<(i32, PostRenderHook)>> = const { RefCell::new(Vec::new()) };
+    /// Render layers and their priorities, sorted by priority.
+    static RENDER_LAYERS: RefCell<Vec<(i32, RenderLayer)>> = const { RefCell::new(Vec::new()) };
+    /// Size of the previous frame.
+    static FRAME_SIZE: Cell<Option<Size>> = const { Cell::new(None) };
+    /// Whether the grid is visible, the render loop is suspended otherwise.
+    static VISIBLE: Cell<bool> = const { Cell::new(true) };
+    /// Resumes the suspended render loop.
+    static RESUME: RefCell<Option<Box<dyn FnOnce()>>> = const { RefCell::new(None) };
+    /// Minimum size of the grid that the application supports.
+    static MINIMUM_SIZE: Cell<Option<Size>> = const { Cell::new(None) };
+    /// How the render loop schedules the frames.
+    static RENDER_MODE: Cell<RenderMode> = const { Cell::new(RenderMode::Continuous) };
+    /// Minimum time between two frames in milliseconds, if throttled.
+    static FRAME_INTERVAL: Cell<Option<f64>> = const { Cell::new(None) };
+    /// Time of the last rendered frame in milliseconds.
+    static LAST_FRAME: Cell<f64> = const { Cell::new(f64::NEG_INFINITY) };
+    /// Whether a redraw was requested while no frame was scheduled.
+    static REDRAW_REQUESTED: Cell<bool> = const { Cell::new(false) };
+    /// Schedules the next frame of the idle render loop.
+    static WAKE: RefCell<Option<Box<dyn FnOnce()>>> = const { RefCell::new(None) };
+    /// Times of the frames flushed during the last second in milliseconds.
+    static FRAME_TIMES: RefCell<VecDeque<f64>> = const { RefCell::new(VecDeque::new()) };
+    /// Whether frames are skipped under heavy load.
+    static FRAME_SKIPPING: Cell<bool> = const { Cell::new(true) };
+    /// Number of consecutive frames that exceeded the frame budget.
+    static SLOW_FRAMES: Cell<u32> = const { Cell::new(0) };
+    /// Number of animation frames left to skip.
+    static FRAMES_TO_SKIP: Cell<u32> = const { Cell::new(0) };
+    /// Number of animation frames skipped so far.
+    static SKIPPED_FRAMES: Cell<u64> = const { Cell::new(0) };
+    /// Corner that the content is kept at on resize, if it is kept.
+    static RESIZE_ANCHOR: Cell<Option<ResizeAnchor>> = const { Cell::new(Some(ResizeAnchor::TopLeft)) };
+    /// Last flushed frame, kept for reflowing it on resize.
+    static LAST_BUFFER: RefCell<Buffer> = RefCell::new(Buffer::empty(Rect::ZERO));
+    /// Whether the quality is reduced under sustained load.
+    static ADAPTIVE_QUALITY: Cell<bool> = const { Cell::new(false) };
+    /// Whether the quality is currently reduced.
+    static QUALITY_REDUCED: Cell<bool> = const { Cell::new(false) };
+    /// Moving average of the frame durations in milliseconds.
+    static AVERAGE_FRAME_TIME: Cell<f64> = const { Cell::new(0.0) };
+}
+
+/// Frame budget in milliseconds when the frame rate is not limited, i.e. the
+/// duration of a frame at 60 Hz.
+const FRAME_BUDGET: f64 = 1000.0 / 60.0;
+
+/// Number of consecutive frames over the budget after which frames are
+/// skipped.
+const SLOW_FRAMES_THRESHOLD: u32 = 3;
+
+/// Largest number of animation frames skipped after a slow frame.
+const MAX_SKIPPED_FRAMES: u32 = 4;
+
+/// Weight of the last frame in the moving average of the frame durations.
+///
+/// A single slow frame does not reduce the quality, about ten do.
+const FRAME_TIME_SMOOTHING: f64 = 0.1;
+
+/// Fraction of the frame budget that the average frame duration has to drop
+/// below for restoring the quality.
+///
+/// The restored features cost time again, so restoring at the budget would
+/// switch back and forth.
+const QUALITY_RESTORE_RATIO: f64 = 0.5;
+
+/// Id of the stylesheet that turns off the expensive styles while the
+/// quality is reduced.
+const REDUCED_QUALITY_STYLESHEET_ID: &str = "ratzilla-reduced-quality";
+
+/// Stylesheet that turns off the animations (e.g. blinking text and cursor),
+/// the color transitions and the CRT effect of the grids.
+const REDUCED_QUALITY_CSS: &str = ".ratzilla-grid, .ratzilla-grid * { \
+    animation: none !important; transition: none !important; \
+    text-shadow: none !important; } \
+    .ratzilla-grid { box-shadow: none !important; } \
+    .ratzilla-grid::after { display: none !important; }";
+
+/// How the render loop schedules the frames.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum RenderMode {
+    /// A frame is rendered on every animation frame of the browser.
+    ///
+    /// This is required for animations such as [`CellEffect`]s.
+    ///
+    /// [`CellEffect`]: crate::widgets::CellEffect
+    #[default]
+    Continuous,
+    /// A frame is only rendered when a redraw is requested, either with a
+    /// [`RenderHandle`] or by an event (e.g. a key press or a resize).
+    ///
+    /// This saves battery for mostly idle applications such as dashboards.
+    OnDemand,
+}
+
+/// Corner of the screen that the content is kept at when the grid is resized.
+///
+/// See [`WebRenderer::set_resize_reflow`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum ResizeAnchor {
+    /// The content keeps its top-left corner, the right and bottom edges are
+    /// cut or extended.
+    #[default]
+    TopLeft,
+    /// The content keeps its top-right corner.
+    TopRight,
+    /// The content keeps its bottom-left corner, e.g. for logs and prompts.
+    BottomLeft,
+    /// The content keeps its bottom-right corner.
+    BottomRight,
+}
+
+/// A handle for requesting redraws of the render loop.
+///
+/// This is meant for [`RenderMode::OnDemand`], e.g. for redrawing when new
+/// data arrives from a timer or a network request. Requests are coalesced
+/// into a single frame.
+///
+/// From JavaScript, redraws are requested with the exported `requestRedraw`
+/// function instead.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct RenderHandle {
+    /// Prevents the construction outside of this module.
+    _private: (),
+}
+
+impl RenderHandle {
+    /// Requests a redraw on the next animation frame.
+    pub fn request_redraw(&self) {
+        request_redraw();
+    }
+
+    /// Returns the number of animation frames skipped under heavy load so
+    /// far.
+    ///
+    /// See [`WebRenderer::set_frame_skipping`].
+    pub fn skipped_frames(&self) -> u64 {
+        SKIPPED_FRAMES.get()
+    }
+
+    /// Returns `true` if the quality is currently reduced under sustained
+    /// load, e.g. for turning off expensive parts of the application too.
+    ///
+    /// See [`WebRenderer::set_adaptive_quality`].
+    pub fn is_quality_reduced(&self) -> bool {
+        is_quality_reduced()
+    }
+}
+
+/// Requests a redraw of the render loop from JavaScript.
+///
+/// This is exported as `requestRedraw` from the WebAssembly module, so the
+/// surrounding page can trigger a frame in [`RenderMode::OnDemand`], e.g.
+/// after injecting data or changing the CSS variables of the palette:
+///
+/// ```js
+/// import init, { requestRedraw } from "./pkg/app.js";
+///
+/// await init();
+/// document.documentElement.style.setProperty("--ansi-red", "#ff5555");
+/// requestRedraw();
+/// ```
+///
+/// With Trunk, the exports are available as `window.wasmBindings`.
+#[wasm_bindgen(js_name = requestRedraw)]
+pub fn request_redraw_from_js() {
+    request_redraw();
+}
+
+/// Requests a redraw of the render loop on the next animation frame.
+pub(crate) fn request_redraw() {
+    match WAKE.with(|wake| wake.borrow_mut().take()) {
+        Some(wake) => wake(),
+        None => REDRAW_REQUESTED.set(true),
+    }
+}
 
 /// Trait for rendering on the web.
 ///
@@ -21,6 +234,21 @@ pub trait WebRenderer {
     where
         F: FnMut(&mut Frame) + 'static;
 
+    /// Renders the terminal on the web with a fallible render callback.
+    ///
+    /// This is the same as [`draw_web`] but the render callback can return
+    /// recoverable errors (e.g. a failed data fetch) instead of panicking.
+    /// When it fails, the frame is discarded, the last good frame stays on the
+    /// screen and `on_error` is called with the error. The render loop keeps
+    /// running.
+    ///
+    /// [`draw_web`]: WebRenderer::draw_web
+    fn draw_web_fallible<F, E, H>(self, render_callback: F, on_error: H)
+    where
+        F: FnMut(&mut Frame) -> Result<(), E> + 'static,
+        H: FnMut(E) + 'static,
+        E: 'static;
+
     /// Handles key events.
     ///
     /// This method takes a closure that will be called on every `keydown`
@@ -29,23 +257,292 @@ pub trait WebRenderer {
     where
         F: FnMut(KeyEvent) + 'static,
     {
-        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::KeyboardEvent| {
-            callback(event.into());
+        event::add_key_listener().expect("Unable to add keydown listener");
+        event::add_event_handler(move |event| {
+            if let Event::Key(key_event) = event {
+                callback(key_event.clone());
+            }
+        });
+    }
+
+    /// Handles mouse events.
+    ///
+    /// This method takes a closure that will be called on every mouse event
+    /// that happens on the grid, with the position of the cell it happened in.
+    fn on_mouse_event<F>(&self, mut callback: F)
+    where
+        F: FnMut(MouseEvent) + 'static,
+    {
+        event::add_event_handler(move |event| {
+            if let Event::Mouse(mouse_event) = event {
+                callback(mouse_event.clone());
+            }
         });
-        let window = window().unwrap();
-        let document = window.document().unwrap();
-        document
-            .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
-            .unwrap();
-        closure.forget();
+    }
+
+    /// Handles all events.
+    ///
+    /// This method takes a closure that will be called on every [`Event`],
+    /// e.g. key and mouse events, focus changes, pastes and resizes.
+    fn on_event<F>(&self, mut callback: F)
+    where
+        F: FnMut(Event) + 'static,
+    {
+        event::add_key_listener().expect("Unable to add keydown listener");
+        event::add_page_listeners().expect("Unable to add page listeners");
+        event::add_event_handler(move |event| callback(event.clone()));
+    }
+
+    /// Handles copy events.
+    ///
+    /// This method takes a closure that will be called whenever text is
+    /// written to the clipboard, e.g. by clicking a [`CopyButton`].
+    ///
+    /// [`CopyButton`]: crate::widgets::CopyButton
+    fn on_copy_event<F>(&self, mut callback: F)
+    where
+        F: FnMut(CopyEvent) + 'static,
+    {
+        event::add_event_handler(move |event| {
+            if let Event::Copy(copy_event) = event {
+                callback(copy_event.clone());
+            }
+        });
+    }
+
+    /// Handles effect events.
+    ///
+    /// This method takes a closure that will be called whenever an effect
+    /// (e.g. a [`Typewriter`]) finishes.
+    ///
+    /// [`Typewriter`]: crate::widgets::Typewriter
+    fn on_effect_event<F>(&self, mut callback: F)
+    where
+        F: FnMut(EffectEvent) + 'static,
+    {
+        event::add_event_handler(move |event| {
+            if let Event::Effect(effect_event) = event {
+                callback(effect_event.clone());
+            }
+        });
+    }
+
+    /// Adds an event filter.
+    ///
+    /// Filters can inspect, transform or consume (by returning `None`) every
+    /// [`Event`] before it reaches the handlers registered with e.g.
+    /// [`on_key_event`]. They are applied in the order they are added, the
+    /// output of one filter being the input of the next one.
+    ///
+    /// [`on_key_event`]: WebRenderer::on_key_event
+    fn add_event_filter<F>(&self, filter: F)
+    where
+        F: FnMut(Event) -> Option<Event> + 'static,
+    {
+        event::add_event_filter(filter);
+    }
+
+    /// Adds a hook that post-processes every frame.
+    ///
+    /// The hook is called with the buffer of the frame after the render
+    /// callback is done and right before the frame is flushed, which makes it
+    /// possible to apply cross-cutting effects (e.g. dimming the screen or
+    /// adding a watermark) without touching the application code.
+    ///
+    /// Hooks are called in ascending order of `priority`. Hooks with the same
+    /// priority are called in the order they are added. A hook added by
+    /// another hook is called from the next frame on.
+    fn add_post_render_hook<F>(&self, priority: i32, hook: F)
+    where
+        F: FnMut(&mut Buffer) + 'static,
+    {
+        add_prioritized(&POST_RENDER_HOOKS, priority, Box::new(hook));
+    }
+
+    /// Adds a render layer.
+    ///
+    /// Layers draw into the same frame as the render callback, which makes it
+    /// possible for reusable components (e.g. an FPS counter or a command
+    /// palette) to render themselves without the application calling them.
+    ///
+    /// Layers are drawn in ascending order of `priority`. The render callback
+    /// has a priority of `0`: layers with a negative priority are drawn below
+    /// it (e.g. backgrounds) and the other layers are drawn above it (e.g.
+    /// overlays). Layers with the same priority are drawn in the order they
+    /// are added. A layer added while the layers are drawn is drawn from the
+    /// next frame on.
+    fn add_render_layer<F>(&self, priority: i32, layer: F)
+    where
+        F: FnMut(&mut Frame) + 'static,
+    {
+        add_prioritized(&RENDER_LAYERS, priority, Box::new(layer));
+    }
+
+    /// Sets the minimum size of the grid that the application supports.
+    ///
+    /// While the grid is smaller, a "terminal too small" screen is shown
+    /// instead of calling the render callback. The application is rendered
+    /// again as soon as the window is large enough.
+    ///
+    /// Pass `None` to remove the minimum size, which is the default.
+    fn set_minimum_size(&self, size: Option<Size>) {
+        MINIMUM_SIZE.with(|minimum| minimum.set(size));
+    }
+
+    /// Enables the terminal-style text selection.
+    ///
+    /// See the [`selection`] module for the details.
+    ///
+    /// [`selection`]: crate::selection
+    fn enable_selection(&self) {
+        selection::enable().expect("Unable to enable the selection");
+    }
+
+    /// Sets the buffering of the input events.
+    ///
+    /// Pass `None` to dispatch the events right away, which is the default.
+    /// See [`InputBuffering`].
+    fn set_input_buffering(&self, buffering: Option<InputBuffering>) {
+        event::set_input_buffering(buffering);
+    }
+
+    /// Sets how the code of the key events is determined.
+    ///
+    /// By default, the code is the character of the keyboard layout of the
+    /// user. See [`KeyPolicy`].
+    fn set_key_policy(&self, policy: KeyPolicy) {
+        event::set_key_policy(policy);
+    }
+
+    /// Sets the unit of the distance of the scroll events.
+    ///
+    /// The default is [`WheelMode::Lines`]. See [`MouseEvent::amount`].
+    fn set_wheel_mode(&self, mode: WheelMode) {
+        event::set_wheel_mode(mode);
+    }
+
+    /// Sets whether the multi-touch and gesture events are dispatched.
+    ///
+    /// When enabled, the touches on the grid are dispatched as
+    /// [`Event::Touch`] and the two-finger gestures as [`Event::Gesture`],
+    /// and the browser does not zoom or scroll the page with two fingers.
+    /// This is disabled by default.
+    fn set_multi_touch(&self, enabled: bool) {
+        event::set_multi_touch(enabled);
+    }
+
+    /// Returns the input capabilities.
+    ///
+    /// See [`Capabilities`].
+    fn capabilities(&self) -> Capabilities {
+        event::capabilities()
+    }
+
+    /// Sets how the auto-repeat of the keys is handled.
+    ///
+    /// By default, the repeats of the browser are dispatched. See
+    /// [`KeyRepeat`].
+    fn set_key_repeat(&self, repeat: KeyRepeat) {
+        event::set_key_repeat(repeat);
+    }
+
+    /// Sets how the render loop schedules the frames.
+    ///
+    /// The default is [`RenderMode::Continuous`].
+    fn set_render_mode(&self, mode: RenderMode) {
+        RENDER_MODE.set(mode);
+        request_redraw();
+    }
+
+    /// Sets the maximum number of frames rendered per second.
+    ///
+    /// Pass `None` to render on every animation frame, which is the default.
+    fn set_max_fps(&self, max_fps: Option<u32>) {
+        FRAME_INTERVAL.set(
+            max_fps
+                .filter(|fps| *fps > 0)
+                .map(|fps| 1000.0 / f64::from(fps)),
+        );
+    }
+
+    /// Sets whether frames are skipped when rendering takes longer than the
+    /// frame budget.
+    ///
+    /// When several consecutive frames exceed the budget (one animation frame
+    /// at 60 Hz, or the interval set with [`WebRenderer::set_max_fps`]), the
+    /// render loop skips the next animation frames in proportion to the
+    /// overrun. The browser then gets time to handle the input, and the next
+    /// rendered frame shows the latest state. The number of skipped frames is
+    /// reported by [`RenderHandle::skipped_frames`].
+    ///
+    /// This is enabled by default.
+    fn set_frame_skipping(&self, enabled: bool) {
+        FRAME_SKIPPING.set(enabled);
+        if !enabled {
+            FRAMES_TO_SKIP.set(0);
+        }
+    }
+
+    /// Sets whether expensive features are turned off while rendering is
+    /// overloaded.
+    ///
+    /// When the average frame duration exceeds the frame budget (see
+    /// [`WebRenderer::set_frame_skipping`]), the quality is reduced:
+    ///
+    /// - The animations (blinking text and cursor), the color transitions and
+    ///   the [`CrtEffect`] of the grids are turned off.
+    /// - The [`CellEffect`]s and [`Typewriter`]s are paused, their content is
+    ///   shown as is.
+    /// - Mouse movements, which drive hover effects, are dispatched at most
+    ///   once per frame.
+    ///
+    /// The quality is restored once the average frame duration drops below
+    /// half the budget. See [`RenderHandle::is_quality_reduced`].
+    ///
+    /// This is disabled by default.
+    ///
+    /// [`CrtEffect`]: crate::effects::CrtEffect
+    /// [`CellEffect`]: crate::widgets::CellEffect
+    /// [`Typewriter`]: crate::widgets::Typewriter
+    fn set_adaptive_quality(&self, enabled: bool) {
+        ADAPTIVE_QUALITY.set(enabled);
+        AVERAGE_FRAME_TIME.set(0.0);
+        if !enabled {
+            set_quality_reduced(false);
+        }
+    }
+
+    /// Sets how the content is kept on screen when the grid is resized, or
+    /// `None` to start from a blank screen.
+    ///
+    /// On resize, the last frame is first moved into the new size, keeping
+    /// the given corner in place, and flushed on its own. The application
+    /// renders at the new size on the next animation frame. This avoids a
+    /// blank or garbled screen while the window is dragged, since the
+    /// rendering of the application may be throttled or skipped (see
+    /// [`WebRenderer::set_max_fps`]).
+    ///
+    /// The default is [`ResizeAnchor::TopLeft`].
+    fn set_resize_reflow(&self, anchor: Option<ResizeAnchor>) {
+        RESIZE_ANCHOR.set(anchor);
+        if anchor.is_none() {
+            LAST_BUFFER.with(|last| *last.borrow_mut() = Buffer::empty(Rect::ZERO));
+        }
+    }
+
+    /// Returns a handle for requesting redraws.
+    ///
+    /// See [`RenderMode::OnDemand`].
+    fn render_handle(&self) -> RenderHandle {
+        RenderHandle::default()
     }
 
     /// Requests an animation frame.
     fn request_animation_frame(f: &Closure<dyn FnMut()>) {
         window()
-            .unwrap()
+            .expect("Unable to retrieve window")
             .request_animation_frame(f.as_ref().unchecked_ref())
-            .unwrap();
+            .expect("Unable to request animation frame");
     }
 }
 
@@ -54,23 +551,541 @@ impl<T> WebRenderer for Terminal<T>
 where
     T: Backend + 'static,
 {
-    fn draw_web<F>(mut self, mut render_callback: F)
+    fn draw_web<F>(self, mut render_callback: F)
     where
         F: FnMut(&mut Frame) + 'static,
+    {
+        self.draw_web_fallible(
+            move |frame| {
+                render_callback(frame);
+                Ok::<(), Infallible>(())
+            },
+            |error| match error {},
+        );
+    }
+
+    fn draw_web_fallible<F, E, H>(mut self, mut render_callback: F, mut on_error: H)
+    where
+        F: FnMut(&mut Frame) -> Result<(), E> + 'static,
+        H: FnMut(E) + 'static,
+        E: 'static,
     {
         let callback = Rc::new(RefCell::new(None));
+        let redraw = Rc::new(Cell::new(false));
         *callback.borrow_mut() = Some(Closure::wrap(Box::new({
             let cb = callback.clone();
             move || {
-                self.autoresize().unwrap();
-                let mut frame = self.get_frame();
-                render_callback(&mut frame);
-                self.flush().unwrap();
-                self.swap_buffers();
-                self.backend_mut().flush().unwrap();
-                Self::request_animation_frame(cb.borrow().as_ref().unwrap());
+                let schedule = {
+                    let cb = cb.clone();
+                    move || {
+                        Self::request_animation_frame(
+                            cb.borrow()
+                                .as_ref()
+                                .expect("Render loop is not initialized"),
+                        );
+                    }
+                };
+                if debugger::park(&schedule) {
+                    return;
+                }
+                let now = utils::now();
+                if FRAME_INTERVAL
+                    .get()
+                    .is_some_and(|interval| now - LAST_FRAME.get() < interval)
+                {
+                    // Throttled, try again on the next animation frame.
+                    schedule();
+                    return;
+                }
+                if FRAMES_TO_SKIP.get() > 0 {
+                    // Overloaded, give the browser time to handle the input.
+                    FRAMES_TO_SKIP.set(FRAMES_TO_SKIP.get() - 1);
+                    SKIPPED_FRAMES.set(SKIPPED_FRAMES.get() + 1);
+                    schedule();
+                    return;
+                }
+                LAST_FRAME.set(now);
+                REDRAW_REQUESTED.set(false);
+                let started_at = utils::performance_now();
+                let result = render_frame(
+                    &mut self,
+                    &mut render_callback,
+                    &mut on_error,
+                    redraw.take(),
+                );
+                record_frame_duration(utils::performance_now() - started_at);
+                if result.is_ok() {
+                    startup::record_frame(started_at);
+                }
+                if let Err(e) = result {
+                    health::record_fatal_error();
+                    let on_retry = {
+                        let cb = cb.clone();
+                        let redraw = redraw.clone();
+                        move || {
+                            redraw.set(true);
+                            Self::request_animation_frame(
+                                cb.borrow()
+                                    .as_ref()
+                                    .expect("Render loop is not initialized"),
+                            );
+                        }
+                    };
+                    error_overlay::show(&ErrorReport::from_io_error(&e), on_retry)
+                        .expect("Unable to show error overlay");
+                    return;
+                }
+                if !VISIBLE.get() {
+                    RESUME.with(|resume| *resume.borrow_mut() = Some(Box::new(schedule)));
+                    return;
+                }
+                match RENDER_MODE.get() {
+                    RenderMode::Continuous => schedule(),
+                    // Redraws requested during the frame are not lost.
+                    RenderMode::OnDemand if REDRAW_REQUESTED.take() => schedule(),
+                    RenderMode::OnDemand => {
+                        WAKE.with(|wake| *wake.borrow_mut() = Some(Box::new(schedule)));
+                    }
+                }
             }
         }) as Box<dyn FnMut()>));
-        Self::request_animation_frame(callback.borrow().as_ref().unwrap());
+        Self::request_animation_frame(
+            callback
+                .borrow()
+                .as_ref()
+                .expect("Render loop is not initialized"),
+        );
+    }
+}
+
+/// Sets whether the grid is visible.
+///
+/// The render loop is suspended after the current frame while the grid is not
+/// visible and resumed as soon as it is visible again.
+pub(crate) fn set_visible(visible: bool) {
+    VISIBLE.set(visible);
+    if visible {
+        if let Some(resume) = RESUME.with(|resume| resume.borrow_mut().take()) {
+            resume();
+        }
+    }
+}
+
+/// Renders a single frame.
+///
+/// If `redraw` is `true`, the terminal is cleared first so that the whole
+/// screen is drawn again.
+///
+/// If the render callback fails or panics, the frame is discarded.
+fn render_frame<T, F, E, H>(
+    terminal: &mut Terminal<T>,
+    render_callback: &mut F,
+    on_error: &mut H,
+    redraw: bool,
+) -> IoResult<()>
+where
+    T: Backend,
+    F: FnMut(&mut Frame) -> Result<(), E>,
+    H: FnMut(E),
+{
+    if redraw {
+        terminal.clear()?;
+    }
+    event::dispatch_queued();
+    if reflow_on_resize(terminal)? {
+        return Ok(());
+    }
+    terminal.autoresize()?;
+    metadata::begin_frame();
+    let mut frame = terminal.get_frame();
+    let size = frame.area().as_size();
+    let previous_size = FRAME_SIZE.with(|previous| previous.replace(Some(size)));
+    if previous_size != Some(size) {
+        if previous_size.is_some() {
+            health::record_resize();
+        }
+        event::dispatch(Event::Resize(size));
+    }
+    let result = panic::catch_unwind(AssertUnwindSafe(|| match MINIMUM_SIZE.with(Cell::get) {
+        Some(minimum)
+            if frame.area().width < minimum.width || frame.area().height < minimum.height =>
+        {
+            render_size_guard(&mut frame, minimum);
+            Ok(())
+        }
+        _ => {
+            render_layers(&mut frame, |priority| priority < 0);
+            let result = render_callback(&mut frame);
+            render_layers(&mut frame, |priority| priority >= 0);
+            toast::render(&mut frame);
+            result
+        }
+    }));
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(error)) => {
+            // Discard the partial frame and keep the last good one on the screen.
+            terminal.current_buffer_mut().reset();
+            health::record_error();
+            on_error(error);
+            return Ok(());
+        }
+        Err(payload) => {
+            terminal.current_buffer_mut().reset();
+            health::record_panic();
+            error_overlay::show_banner(&format!("Rendering failed: {}", panic_message(&*payload)))?;
+            return Ok(());
+        }
+    }
+    error_overlay::hide_banner();
+    time_travel::record();
+    let buffer = terminal.current_buffer_mut();
+    #[cfg(debug_assertions)]
+    crate::cells::warn_invalid_cells(buffer);
+    call_post_render_hooks(buffer);
+    crate::cells::expand_buffer_tabs(buffer);
+    if !QUALITY_REDUCED.get() {
+        effects::apply_timed_effects(buffer);
+    }
+    effects::apply_color_filter(buffer);
+    clipboard::copy_pending(buffer);
+    search::apply(buffer);
+    selection::apply(buffer);
+    glyphs::apply(buffer);
+    debugger::record(buffer);
+    if RESIZE_ANCHOR.get().is_some() {
+        LAST_BUFFER.with(|last| {
+            let mut last = last.borrow_mut();
+            last.area = buffer.area;
+            last.content.clone_from(&buffer.content);
+        });
+    }
+    terminal.flush()?;
+    capture::record(terminal.current_buffer_mut());
+    terminal.swap_buffers();
+    terminal.backend_mut().flush()?;
+    record_frame_time();
+    event::record_latency();
+    Ok(())
+}
+
+/// Flushes the last frame moved into the new size if the grid was resized.
+///
+/// Returns `true` if the frame was flushed, the application then renders on
+/// the next animation frame. See [`WebRenderer::set_resize_reflow`].
+fn reflow_on_resize<T: Backend>(terminal: &mut Terminal<T>) -> IoResult<bool> {
+    let Some(anchor) = RESIZE_ANCHOR.get() else {
+        return Ok(false);
+    };
+    let area = Rect::from((Position::ORIGIN, terminal.size()?));
+    let reflowed = LAST_BUFFER.with(|last| {
+        let last = last.borrow();
+        (!last.area.is_empty() && last.area != area).then(|| reflow(&last, area, anchor))
+    });
+    let Some(reflowed) = reflowed else {
+        return Ok(false);
+    };
+    terminal.resize(area)?;
+    *terminal.current_buffer_mut() = reflowed;
+    LAST_BUFFER.with(|last| last.borrow_mut().area = area);
+    terminal.flush()?;
+    terminal.swap_buffers();
+    terminal.backend_mut().flush()?;
+    request_redraw();
+    Ok(true)
+}
+
+/// Moves the content of a buffer into a buffer of the given area, keeping
+/// the given corner in place.
+///
+/// Wide symbols cut by the left edge are dropped.
+fn reflow(buffer: &Buffer, area: Rect, anchor: ResizeAnchor) -> Buffer {
+    let mut reflowed = Buffer::empty(area);
+    let dx = match anchor {
+        ResizeAnchor::TopLeft | ResizeAnchor::BottomLeft => 0,
+        ResizeAnchor::TopRight | ResizeAnchor::BottomRight => {
+            i32::from(buffer.area.width) - i32::from(area.width)
+        }
+    };
+    let dy = match anchor {
+        ResizeAnchor::TopLeft | ResizeAnchor::TopRight => 0,
+        ResizeAnchor::BottomLeft | ResizeAnchor::BottomRight => {
+            i32::from(buffer.area.height) - i32::from(area.height)
+        }
+    };
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let (Ok(from_x), Ok(from_y)) = (
+                u16::try_from(i32::from(x) + dx),
+                u16::try_from(i32::from(y) + dy),
+            ) else {
+                continue;
+            };
+            let Some(cell) = buffer.cell((from_x, from_y)) else {
+                continue;
+            };
+            // The trailing cell of a wide symbol whose leading cell is cut.
+            if x == area.left() && cell.symbol().is_empty() {
+                continue;
+            }
+            reflowed[(x, y)] = cell.clone();
+        }
+    }
+    reflowed
+}
+
+/// Records the time of a flushed frame for [`frame_rate`].
+fn record_frame_time() {
+    let now = utils::now();
+    FRAME_TIMES.with(|times| {
+        let mut times = times.borrow_mut();
+        times.push_back(now);
+        while times.front().is_some_and(|time| now - time > 1000.0) {
+            times.pop_front();
+        }
+    });
+}
+
+/// Records the duration of a frame and schedules skipped frames if the
+/// render loop is overloaded.
+fn record_frame_duration(duration: f64) {
+    let budget = FRAME_INTERVAL.get().unwrap_or(FRAME_BUDGET);
+    health::record_frame(duration, budget);
+    if ADAPTIVE_QUALITY.get() {
+        let average = AVERAGE_FRAME_TIME.get() * (1.0 - FRAME_TIME_SMOOTHING)
+            + duration * FRAME_TIME_SMOOTHING;
+        AVERAGE_FRAME_TIME.set(average);
+        set_quality_reduced(is_overloaded(QUALITY_REDUCED.get(), average, budget));
+    }
+    if duration <= budget {
+        SLOW_FRAMES.set(0);
+        return;
+    }
+    let slow_frames = SLOW_FRAMES.get() + 1;
+    SLOW_FRAMES.set(slow_frames);
+    if FRAME_SKIPPING.get() && slow_frames >= SLOW_FRAMES_THRESHOLD {
+        let overrun = (duration / budget).ceil() as u32 - 1;
+        FRAMES_TO_SKIP.set(overrun.clamp(1, MAX_SKIPPED_FRAMES));
+    }
+}
+
+/// Returns `true` if the render loop is overloaded given the average frame
+/// duration, with some hysteresis if the quality is already reduced.
+fn is_overloaded(reduced: bool, average: f64, budget: f64) -> bool {
+    if reduced {
+        average >= budget * QUALITY_RESTORE_RATIO
+    } else {
+        average > budget
+    }
+}
+
+/// Reduces or restores the quality.
+fn set_quality_reduced(reduced: bool) {
+    if QUALITY_REDUCED.replace(reduced) == reduced {
+        return;
+    }
+    if let Some(document) = window().and_then(|window| window.document()) {
+        let css = reduced.then_some(REDUCED_QUALITY_CSS);
+        if let Err(error) = set_stylesheet(&document, REDUCED_QUALITY_STYLESHEET_ID, css) {
+            web_warn!("unable to update the quality: {error}");
+        }
+    }
+    if reduced {
+        web_debug!("rendering is overloaded, reducing the quality");
+    } else {
+        web_debug!("rendering recovered, restoring the quality");
+    }
+}
+
+/// Returns `true` if the quality is currently reduced, see
+/// [`WebRenderer::set_adaptive_quality`].
+pub(crate) fn is_quality_reduced() -> bool {
+    QUALITY_REDUCED.get()
+}
+
+/// Returns the number of animation frames skipped so far.
+pub(crate) fn skipped_frames() -> u64 {
+    SKIPPED_FRAMES.get()
+}
+
+/// Returns the number of frames flushed during the last second.
+pub(crate) fn frame_rate() -> usize {
+    let now = utils::now();
+    FRAME_TIMES.with(|times| {
+        times
+            .borrow()
+            .iter()
+            .filter(|time| now - **time <= 1000.0)
+            .count()
+    })
+}
+
+/// Returns the size of the last frame, if any.
+pub(crate) fn frame_size() -> Option<Size> {
+    FRAME_SIZE.with(Cell::get)
+}
+
+/// Draws the render layers whose priority matches the predicate.
+fn render_layers<P>(frame: &mut Frame, predicate: P)
+where
+    P: Fn(i32) -> bool,
+{
+    for_each_prioritized(&RENDER_LAYERS, |priority, layer| {
+        if predicate(priority) {
+            layer(frame);
+        }
+    });
+}
+
+/// Calls the post-render hooks with the buffer of the frame.
+fn call_post_render_hooks(buffer: &mut Buffer) {
+    for_each_prioritized(&POST_RENDER_HOOKS, |_, hook| hook(buffer));
+}
+
+/// Adds an item to a list that is sorted by priority, after the items with
+/// the same priority.
+fn add_prioritized<T>(list: &'static LocalKey<RefCell<Vec<(i32, T)>>>, priority: i32, item: T) {
+    list.with(|list| {
+        let mut list = list.borrow_mut();
+        let index = list.partition_point(|(p, _)| *p <= priority);
+        list.insert(index, (priority, item));
+    });
+}
+
+/// Calls `f` for the items of a list that is sorted by priority.
+///
+/// The list is taken out while the items are called, so that they can add
+/// new items, which are merged back afterwards.
+fn for_each_prioritized<T, F>(list: &'static LocalKey<RefCell<Vec<(i32, T)>>>, mut f: F)
+where
+    F: FnMut(i32, &mut T),
+{
+    /// Puts the items back into the list, also if an item panics.
+    struct Restore<T: 'static> {
+        list: &'static LocalKey<RefCell<Vec<(i32, T)>>>,
+        items: Vec<(i32, T)>,
+    }
+    impl<T> Drop for Restore<T> {
+        fn drop(&mut self) {
+            let added = self.list.with(|list| {
+                std::mem::replace(&mut *list.borrow_mut(), std::mem::take(&mut self.items))
+            });
+            for (priority, item) in added {
+                add_prioritized(self.list, priority, item);
+            }
+        }
+    }
+    let mut restore = Restore {
+        list,
+        items: list.with(|list| std::mem::take(&mut *list.borrow_mut())),
+    };
+    for (priority, item) in &mut restore.items {
+        f(*priority, item);
+    }
+}
+
+/// Returns the message of a panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Renders the screen that is shown while the grid is smaller than the
+/// minimum size.
+fn render_size_guard(frame: &mut Frame, minimum: Size) {
+    let area = frame.area();
+    let color = |current: u16, needed: u16| {
+        Style::default().fg(if current < needed {
+            Color::Red
+        } else {
+            Color::Green
+        })
+    };
+    let lines = vec![
+        Line::from("Terminal size too small"),
+        Line::from(vec![
+            "Width = ".into(),
+            Span::styled(area.width.to_string(), color(area.width, minimum.width)),
+            " Height = ".into(),
+            Span::styled(area.height.to_string(), color(area.height, minimum.height)),
+        ]),
+        Line::from(format!(
+            "Needed: Width = {} Height = {}",
+            minimum.width, minimum.height
+        )),
+    ];
+    let [area] = Layout::vertical([Constraint::Length(lines.len() as u16)])
+        .flex(Flex::Center)
+        .areas(area);
+    frame.render_widget(Paragraph::new(lines).centered(), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_post_render_hook_from_hook() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        add_prioritized(&POST_RENDER_HOOKS, 0, {
+            let calls = Rc::clone(&calls);
+            Box::new(move |_: &mut Buffer| {
+                calls.borrow_mut().push("outer");
+                let calls = Rc::clone(&calls);
+                let inner: PostRenderHook = Box::new(move |_| calls.borrow_mut().push("inner"));
+                add_prioritized(&POST_RENDER_HOOKS, -1, inner);
+            }) as PostRenderHook
+        });
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 1, 1));
+        call_post_render_hooks(&mut buffer);
+        assert_eq!(*calls.borrow(), ["outer"]);
+        calls.borrow_mut().clear();
+        call_post_render_hooks(&mut buffer);
+        assert_eq!(*calls.borrow(), ["inner", "outer"]);
+    }
+
+    #[test]
+    fn reflow_anchors() {
+        let buffer = Buffer::with_lines(["abc", "def", "ghi"]);
+        let area = Rect::new(0, 0, 2, 2);
+        assert_eq!(
+            reflow(&buffer, area, ResizeAnchor::TopLeft),
+            Buffer::with_lines(["ab", "de"])
+        );
+        assert_eq!(
+            reflow(&buffer, area, ResizeAnchor::BottomRight),
+            Buffer::with_lines(["ef", "hi"])
+        );
+        assert_eq!(
+            reflow(&buffer, Rect::new(0, 0, 4, 4), ResizeAnchor::BottomLeft),
+            Buffer::with_lines(["    ", "abc ", "def ", "ghi "])
+        );
+        assert_eq!(
+            reflow(&buffer, Rect::new(0, 0, 4, 2), ResizeAnchor::TopRight),
+            Buffer::with_lines([" abc", " def"])
+        );
+    }
+
+    #[test]
+    fn reflow_wide_symbols() {
+        let buffer = Buffer::with_lines(["a界b"]);
+        assert_eq!(
+            reflow(&buffer, Rect::new(0, 0, 2, 1), ResizeAnchor::TopRight),
+            Buffer::with_lines([" b"])
+        );
+    }
+
+    #[test]
+    fn overload_hysteresis() {
+        assert!(!is_overloaded(false, 16.0, 16.7));
+        assert!(is_overloaded(false, 20.0, 16.7));
+        // Reduced quality is kept until there is enough headroom.
+        assert!(is_overloaded(true, 12.0, 16.7));
+        assert!(!is_overloaded(true, 8.0, 16.7));
     }
 }
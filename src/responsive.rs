@@ -0,0 +1,323 @@
+//! ## Responsive helpers
+//!
+//! The size of the grid follows the size of the browser window, which can be
+//! anything from a narrow phone screen to a 4K monitor. The helpers in this
+//! module adapt the content to the current grid size.
+//!
+//! They are meant to be called from the render callback with the size of the
+//! frame, so the content is reflowed automatically when the window is
+//! resized.
+//!
+//! [`Breakpoints`] map the width of the grid to a named [`Breakpoint`], so an
+//! application can switch between e.g. a mobile and a desktop layout:
+//!
+//! ```rust no_run
+//! use ratzilla::responsive::{Breakpoint, Breakpoints};
+//!
+//! # fn render(frame: &mut ratzilla::ratatui::Frame, breakpoints: &mut Breakpoints) {
+//! match breakpoints.update(frame.area().width) {
+//!     Breakpoint::Small => { /* single column */ }
+//!     Breakpoint::Medium | Breakpoint::Large => { /* sidebar and content */ }
+//! }
+//! # }
+//! ```
+//!
+//! Long text can be wrapped to the width of the grid with [`wrap_text`]:
+//!
+//! ```rust no_run
+//! use ratzilla::{
+//!     ratatui::{text::Text, widgets::Paragraph},
+//!     responsive::{wrap_text, WrapOptions},
+//! };
+//!
+//! # fn render(frame: &mut ratzilla::ratatui::Frame) {
+//! let text = Text::raw("Some long text that needs to fit on narrow screens.");
+//! let area = frame.area();
+//! let wrapped = wrap_text(&text, area.width, WrapOptions::new().hyphenate(true));
+//! frame.render_widget(Paragraph::new(wrapped), area);
+//! # }
+//! ```
+
+use ratatui::{
+    style::Style,
+    text::{Line, Span, StyledGrapheme, Text},
+};
+
+use crate::backend::utils::Callback;
+
+/// A named range of grid widths.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Breakpoint {
+    /// Narrow grids, e.g. phones.
+    Small,
+    /// Medium grids, e.g. tablets.
+    Medium,
+    /// Wide grids, e.g. desktops.
+    Large,
+}
+
+/// Maps the width of the grid to a [`Breakpoint`].
+///
+/// By default, grids narrower than 80 columns are [`Breakpoint::Small`] and
+/// grids narrower than 120 columns are [`Breakpoint::Medium`].
+#[derive(Debug)]
+pub struct Breakpoints {
+    /// Minimum width of [`Breakpoint::Medium`].
+    medium: u16,
+    /// Minimum width of [`Breakpoint::Large`].
+    large: u16,
+    /// Breakpoint of the last update.
+    current: Option<Breakpoint>,
+    /// Callback for the breakpoint changes.
+    on_change: Option<Callback<dyn FnMut(Breakpoint)>>,
+}
+
+impl Default for Breakpoints {
+    fn default() -> Self {
+        Self {
+            medium: 80,
+            large: 120,
+            current: None,
+            on_change: None,
+        }
+    }
+}
+
+impl Breakpoints {
+    /// Constructs new [`Breakpoints`] with the default widths.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the minimum width of [`Breakpoint::Medium`].
+    pub fn medium(mut self, width: u16) -> Self {
+        self.medium = width;
+        self
+    }
+
+    /// Sets the minimum width of [`Breakpoint::Large`].
+    pub fn large(mut self, width: u16) -> Self {
+        self.large = width;
+        self
+    }
+
+    /// Sets the callback that is called when the breakpoint changes.
+    ///
+    /// The callback is also called on the first update.
+    pub fn on_change<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(Breakpoint) + 'static,
+    {
+        self.on_change = Some(Callback(Box::new(callback)));
+        self
+    }
+
+    /// Returns the breakpoint of the given width.
+    pub fn breakpoint(&self, width: u16) -> Breakpoint {
+        if width >= self.large {
+            Breakpoint::Large
+        } else if width >= self.medium {
+            Breakpoint::Medium
+        } else {
+            Breakpoint::Small
+        }
+    }
+
+    /// Returns the breakpoint of the last update, if any.
+    pub fn current(&self) -> Option<Breakpoint> {
+        self.current
+    }
+
+    /// Updates the current breakpoint from the width of the grid and returns
+    /// it.
+    ///
+    /// This is meant to be called on every frame, the change callback is only
+    /// called when the breakpoint changes.
+    pub fn update(&mut self, width: u16) -> Breakpoint {
+        let breakpoint = self.breakpoint(width);
+        if self.current != Some(breakpoint) {
+            self.current = Some(breakpoint);
+            if let Some(Callback(callback)) = self.on_change.as_mut() {
+                callback(breakpoint);
+            }
+        }
+        breakpoint
+    }
+}
+
+/// Options of [`wrap_text`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct WrapOptions {
+    /// Whether words that are longer than the width are split with a hyphen.
+    hyphenate: bool,
+    /// Whether the whitespace at the start of the wrapped lines is removed.
+    trim: bool,
+}
+
+impl Default for WrapOptions {
+    fn default() -> Self {
+        Self {
+            hyphenate: false,
+            trim: true,
+        }
+    }
+}
+
+impl WrapOptions {
+    /// Constructs new [`WrapOptions`].
+    ///
+    /// By default, long words are split without a hyphen and the wrapped
+    /// lines are trimmed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether words that are longer than the width are split with a
+    /// hyphen.
+    pub fn hyphenate(mut self, hyphenate: bool) -> Self {
+        self.hyphenate = hyphenate;
+        self
+    }
+
+    /// Sets whether the whitespace at the start of the wrapped lines is
+    /// removed.
+    pub fn trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
+    }
+}
+
+/// Wraps the text to the given width.
+///
+/// Lines are broken between words. Words that do not fit on a line of their
+/// own are split, with a hyphen if [`WrapOptions::hyphenate`] is set. The
+/// styles of the spans and the alignment of the lines are kept.
+pub fn wrap_text(text: &Text<'_>, width: u16, options: WrapOptions) -> Text<'static> {
+    let width = usize::from(width.max(1));
+    let lines = text
+        .lines
+        .iter()
+        .flat_map(|line| {
+            wrap_line(line, text.style, width, options)
+                .into_iter()
+                .map(|graphemes| {
+                    let mut wrapped = to_line(&graphemes);
+                    wrapped.style = line.style;
+                    wrapped.alignment = line.alignment;
+                    wrapped
+                })
+        })
+        .collect::<Vec<_>>();
+    let mut wrapped = Text::from(lines);
+    wrapped.style = text.style;
+    wrapped.alignment = text.alignment;
+    wrapped
+}
+
+/// A grapheme of a line along with its width.
+type Grapheme<'a> = (StyledGrapheme<'a>, usize);
+
+/// Wraps a line into rows of graphemes that fit into the width.
+fn wrap_line<'a>(
+    line: &'a Line<'a>,
+    base_style: Style,
+    width: usize,
+    options: WrapOptions,
+) -> Vec<Vec<Grapheme<'a>>> {
+    let graphemes = line
+        .styled_graphemes(base_style)
+        .map(|grapheme| {
+            let grapheme_width = Span::raw(grapheme.symbol).width();
+            (grapheme, grapheme_width)
+        })
+        .collect::<Vec<_>>();
+
+    let mut rows = Vec::new();
+    let mut row: Vec<Grapheme> = Vec::new();
+    let mut row_width = 0;
+    let mut start = 0;
+    while start < graphemes.len() {
+        // A word along with the whitespace before it.
+        let is_space = |g: &Grapheme| g.0.symbol.chars().all(char::is_whitespace);
+        let word_start = start
+            + graphemes[start..]
+                .iter()
+                .take_while(|g| is_space(g))
+                .count();
+        let end = word_start
+            + graphemes[word_start..]
+                .iter()
+                .take_while(|g| !is_space(g))
+                .count();
+        let spaces = &graphemes[start..word_start];
+        let word = &graphemes[word_start..end];
+        start = end;
+
+        let spaces_width = spaces.iter().map(|g| g.1).sum::<usize>();
+        let word_width = word.iter().map(|g| g.1).sum::<usize>();
+        if row_width + spaces_width + word_width <= width {
+            row.extend_from_slice(spaces);
+            row.extend_from_slice(word);
+            row_width += spaces_width + word_width;
+            continue;
+        }
+        if !row.is_empty() {
+            rows.push(std::mem::take(&mut row));
+            row_width = 0;
+        }
+        if !options.trim {
+            for grapheme in spaces {
+                if row_width + grapheme.1 > width {
+                    rows.push(std::mem::take(&mut row));
+                    row_width = 0;
+                }
+                row.push(grapheme.clone());
+                row_width += grapheme.1;
+            }
+        }
+        if row_width + word_width <= width {
+            row.extend_from_slice(word);
+            row_width += word_width;
+            continue;
+        }
+        // The word does not fit on a line of its own, split it.
+        let hyphenate = options.hyphenate && width > 1;
+        for (i, grapheme) in word.iter().enumerate() {
+            let is_last = i + 1 == word.len();
+            let limit = if hyphenate && !is_last {
+                width - 1
+            } else {
+                width
+            };
+            if row_width + grapheme.1 > limit && !row.is_empty() {
+                if hyphenate {
+                    let style = row.last().map(|g| g.0.style).unwrap_or(base_style);
+                    row.push((StyledGrapheme::new("-", style), 1));
+                }
+                rows.push(std::mem::take(&mut row));
+                row_width = 0;
+            }
+            row.push(grapheme.clone());
+            row_width += grapheme.1;
+        }
+    }
+    if !row.is_empty() || rows.is_empty() {
+        rows.push(row);
+    }
+    rows
+}
+
+/// Converts a row of graphemes into a line, merging the graphemes that share
+/// a style into a single span.
+fn to_line(graphemes: &[Grapheme<'_>]) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for (grapheme, _) in graphemes {
+        match spans.last_mut() {
+            Some(span) if span.style == grapheme.style => {
+                span.content.to_mut().push_str(grapheme.symbol);
+            }
+            _ => spans.push(Span::styled(grapheme.symbol.to_string(), grapheme.style)),
+        }
+    }
+    Line::from(spans)
+}
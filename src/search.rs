@@ -0,0 +1,228 @@
+//! ## Search
+//!
+//! Finds text on the screen, e.g. for a `/`-style search prompt.
+//!
+//! [`DomBackend::search`] and [`CanvasBackend::search`] return the matches in
+//! the current content of a backend. To highlight the matches, set the
+//! pattern with [`set_pattern`]: the matches are then found and highlighted
+//! in every frame until [`clear`] is called, and [`next`] and [`previous`]
+//! move between them.
+//!
+//! ```rust no_run
+//! use ratzilla::search;
+//!
+//! search::set_pattern("error");
+//! // On `n`:
+//! let current = search::next();
+//! ```
+//!
+//! The search is case-insensitive unless the pattern contains uppercase
+//! characters ("smart case").
+//!
+//! [`DomBackend::search`]: crate::DomBackend::search
+//! [`CanvasBackend::search`]: crate::CanvasBackend::search
+
+use std::cell::{Cell, RefCell};
+
+use ratatui::{
+    buffer::{Buffer, Cell as BufferCell},
+    layout::Rect,
+    style::Color,
+};
+
+use crate::{cells, render};
+
+thread_local! {
+    /// Pattern to highlight.
+    static PATTERN: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// Matches of the pattern in the last frame.
+    static MATCHES: RefCell<Vec<Match>> = const { RefCell::new(Vec::new()) };
+    /// Index of the current match.
+    static CURRENT: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A match of a search pattern.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Match {
+    /// Column of the first cell of the match.
+    pub column: u16,
+    /// Row of the match.
+    pub row: u16,
+    /// Number of cells of the match.
+    pub width: u16,
+}
+
+impl Match {
+    /// Returns the area covered by the match.
+    pub fn area(&self) -> Rect {
+        Rect::new(self.column, self.row, self.width, 1)
+    }
+}
+
+/// Finds the pattern in rows of cells.
+///
+/// Matches do not span multiple rows and do not overlap.
+pub(crate) fn find<'a, I>(rows: I, pattern: &str) -> Vec<Match>
+where
+    I: IntoIterator<Item = &'a [BufferCell]>,
+{
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+    let ignore_case = !pattern.chars().any(char::is_uppercase);
+    let normalize = |text: &str| {
+        if ignore_case {
+            text.to_lowercase()
+        } else {
+            text.to_string()
+        }
+    };
+    let pattern = normalize(pattern);
+    let mut matches = Vec::new();
+    for (row, cells) in rows.into_iter().enumerate() {
+        // The text of the row along with the first and the last column of
+        // the cell of each byte. The cells covered by wide symbols are not
+        // part of the text.
+        let mut text = String::new();
+        let mut columns = Vec::new();
+        for (column, cell) in cells::visible_cells(cells) {
+            let symbol = normalize(cell.symbol());
+            let last = column + cells::symbol_width(&symbol).max(1) - 1;
+            columns.extend(std::iter::repeat_n((column, last), symbol.len()));
+            text.push_str(&symbol);
+        }
+        for (start, _) in text.match_indices(&pattern) {
+            let (first, _) = columns[start];
+            let (_, last) = columns[start + pattern.len() - 1];
+            matches.push(Match {
+                column: first as u16,
+                row: row as u16,
+                width: (last - first + 1) as u16,
+            });
+        }
+    }
+    matches
+}
+
+/// Sets the pattern to highlight on the screen.
+///
+/// The current match is reset to the first one.
+pub fn set_pattern<S: Into<String>>(pattern: S) {
+    PATTERN.with(|current| current.replace(Some(pattern.into())));
+    CURRENT.set(0);
+    render::request_redraw();
+}
+
+/// Clears the pattern and removes the highlight.
+pub fn clear() {
+    PATTERN.with(|pattern| pattern.replace(None));
+    MATCHES.with(|matches| matches.borrow_mut().clear());
+    render::request_redraw();
+}
+
+/// Returns the matches of the pattern in the last frame.
+pub fn matches() -> Vec<Match> {
+    MATCHES.with(|matches| matches.borrow().clone())
+}
+
+/// Returns the current match, if any.
+pub fn current() -> Option<Match> {
+    MATCHES.with(|matches| matches.borrow().get(CURRENT.get()).copied())
+}
+
+/// Moves to the next match, wrapping around, and returns it.
+pub fn next() -> Option<Match> {
+    step(1)
+}
+
+/// Moves to the previous match, wrapping around, and returns it.
+pub fn previous() -> Option<Match> {
+    step(-1)
+}
+
+/// Moves the current match by the given offset.
+fn step(offset: isize) -> Option<Match> {
+    let len = MATCHES.with(|matches| matches.borrow().len());
+    if len == 0 {
+        return None;
+    }
+    CURRENT.set((CURRENT.get() as isize + offset).rem_euclid(len as isize) as usize);
+    render::request_redraw();
+    current()
+}
+
+/// Finds and highlights the pattern in the buffer.
+///
+/// This is called by the renderer right before the frame is flushed.
+pub(crate) fn apply(buffer: &mut Buffer) {
+    let Some(pattern) = PATTERN.with(|pattern| pattern.borrow().clone()) else {
+        return;
+    };
+    let width = usize::from(buffer.area.width);
+    let matches = if width == 0 {
+        Vec::new()
+    } else {
+        find(buffer.content.chunks(width), &pattern)
+    };
+    if CURRENT.get() >= matches.len() {
+        CURRENT.set(0);
+    }
+    for (i, found) in matches.iter().enumerate() {
+        let background = if i == CURRENT.get() {
+            Color::LightYellow
+        } else {
+            Color::Yellow
+        };
+        let area = found.area();
+        for x in area.left()..area.right() {
+            if let Some(cell) = buffer.cell_mut((buffer.area.x + x, buffer.area.y + area.y)) {
+                cell.set_fg(Color::Black).set_bg(background);
+            }
+        }
+    }
+    MATCHES.with(|current| current.replace(matches));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Finds the pattern in the rows of a buffer.
+    fn find_in(lines: &[&str], pattern: &str) -> Vec<Match> {
+        let buffer = Buffer::with_lines(lines.iter().copied());
+        find(buffer.content.chunks(buffer.area.width as usize), pattern)
+    }
+
+    /// Returns a match.
+    fn at(column: u16, row: u16, width: u16) -> Match {
+        Match { column, row, width }
+    }
+
+    #[test]
+    fn smart_case() {
+        let lines = ["Error error", "ERROR"];
+        assert_eq!(
+            find_in(&lines, "error"),
+            [at(0, 0, 5), at(6, 0, 5), at(0, 1, 5)]
+        );
+        assert_eq!(find_in(&lines, "Error"), [at(0, 0, 5)]);
+        assert_eq!(find_in(&lines, ""), []);
+    }
+
+    #[test]
+    fn multi_byte_lowercase() {
+        assert_eq!(find_in(&["xÄÖx"], "äö"), [at(1, 0, 2)]);
+        assert_eq!(find_in(&["xÄÖx"], "ÄÖ"), [at(1, 0, 2)]);
+        assert_eq!(find_in(&["xäöx"], "ÄÖ"), []);
+    }
+
+    #[test]
+    fn wide_symbols() {
+        let lines = ["a日本b 🦀"];
+        assert_eq!(find_in(&lines, "日本"), [at(1, 0, 4)]);
+        assert_eq!(find_in(&lines, "本b"), [at(3, 0, 3)]);
+        assert_eq!(find_in(&lines, "🦀"), [at(7, 0, 2)]);
+        // The cells covered by the wide symbols are not blanks.
+        assert_eq!(find_in(&lines, "日 "), []);
+    }
+}
@@ -0,0 +1,476 @@
+//! ## Text selection
+//!
+//! The grid is made of individual cells, so the native selection of the
+//! browser does not know about the layout of the terminal: it selects whole
+//! `<pre>` lines and does not work at all with the [`CanvasBackend`]. This
+//! module implements a terminal-style selection instead:
+//!
+//! - Click and drag to select cells, the selection follows the reading order.
+//! - Double-click to select a word and triple-click to select a line, then
+//!   drag to extend the selection by words or lines. The characters that are
+//!   part of a word can be set with [`set_word_chars`].
+//! - Hold <kbd>Alt</kbd> while clicking to select a rectangular block.
+//! - Use <kbd>Shift</kbd> and the arrow keys (or <kbd>Shift</kbd>+click) to
+//!   extend the selection.
+//! - Press <kbd>Esc</kbd> or click without dragging to clear it.
+//!
+//! The selection is highlighted by reversing the colors of the cells and the
+//! selected text can be read with [`selected_text`]. It is also what the
+//! browser copies, e.g. with <kbd>Ctrl</kbd>+<kbd>C</kbd>. It is enabled with
+//! [`WebRenderer::enable_selection`].
+//!
+//! [`CanvasBackend`]: crate::CanvasBackend
+//! [`WebRenderer::enable_selection`]: crate::WebRenderer::enable_selection
+
+use std::cell::{Cell, RefCell};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::Modifier,
+};
+
+use web_sys::{
+    wasm_bindgen::{prelude::Closure, JsCast},
+    window, ClipboardEvent,
+};
+
+use crate::{
+    cells,
+    error::Error,
+    event::{self, Event, KeyCode, MouseButton, MouseEventKind},
+    utils::now,
+};
+
+/// Characters that are part of a word besides the alphanumeric ones, by
+/// default.
+const DEFAULT_WORD_CHARS: &str = "-#%&+,./=?@\\_~:";
+
+/// Maximum delay between the clicks of a double or triple click in
+/// milliseconds.
+const MULTI_CLICK_DELAY: f64 = 400.0;
+
+thread_local! {
+    /// Whether the selection is enabled.
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    /// Current selection, as driven by the events.
+    static SELECTION: Cell<Option<Selection>> = const { Cell::new(None) };
+    /// Selection shown in the last frame, expanded to whole words or lines.
+    static SHOWN: Cell<Option<Selection>> = const { Cell::new(None) };
+    /// Time, position and count of the last clicks.
+    static LAST_CLICK: Cell<Option<(f64, Position, u8)>> = const { Cell::new(None) };
+    /// Characters that are part of a word besides the alphanumeric ones.
+    static WORD_CHARS: RefCell<String> = RefCell::new(DEFAULT_WORD_CHARS.to_string());
+    /// Whether the selection is being dragged with the mouse.
+    static DRAGGING: Cell<bool> = const { Cell::new(false) };
+    /// Text of the selection in the last frame.
+    static TEXT: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Unit that a selection is expanded to.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum SelectionUnit {
+    /// Individual cells.
+    #[default]
+    Cell,
+    /// Whole words, after a double click.
+    Word,
+    /// Whole lines, after a triple click.
+    Line,
+}
+
+/// Class of a character for the word selection.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum CharClass {
+    /// Whitespace.
+    Space,
+    /// Part of a word.
+    Word,
+    /// Anything else, which is selected on its own.
+    Other,
+}
+
+/// Returns the class of a cell symbol.
+fn char_class(symbol: &str, word_chars: &str) -> CharClass {
+    if symbol.chars().all(char::is_whitespace) {
+        CharClass::Space
+    } else if symbol
+        .chars()
+        .all(|c| c.is_alphanumeric() || word_chars.contains(c))
+    {
+        CharClass::Word
+    } else {
+        CharClass::Other
+    }
+}
+
+/// A selection of cells.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Selection {
+    /// Cell where the selection started.
+    pub anchor: Position,
+    /// Cell where the selection ends, which moves when it is extended.
+    pub cursor: Position,
+    /// Whether the selection is a rectangular block.
+    pub block: bool,
+    /// Unit that the selection is expanded to.
+    ///
+    /// This is ignored for block selections.
+    pub unit: SelectionUnit,
+}
+
+impl Selection {
+    /// Returns the first and the last cell of the selection in reading order.
+    fn bounds(&self) -> (Position, Position) {
+        let key = |position: Position| (position.y, position.x);
+        if key(self.anchor) <= key(self.cursor) {
+            (self.anchor, self.cursor)
+        } else {
+            (self.cursor, self.anchor)
+        }
+    }
+
+    /// Returns the rectangle covered by a block selection.
+    fn rect(&self) -> Rect {
+        let left = self.anchor.x.min(self.cursor.x);
+        let top = self.anchor.y.min(self.cursor.y);
+        let right = self.anchor.x.max(self.cursor.x);
+        let bottom = self.anchor.y.max(self.cursor.y);
+        Rect::new(left, top, right - left + 1, bottom - top + 1)
+    }
+
+    /// Returns the range of selected columns of a row, given the width of the
+    /// grid.
+    fn columns(&self, row: u16, width: u16) -> Option<(u16, u16)> {
+        if self.block {
+            let rect = self.rect();
+            return (rect.top()..rect.bottom())
+                .contains(&row)
+                .then(|| (rect.left(), rect.right().min(width)));
+        }
+        let (start, end) = self.bounds();
+        if row < start.y || row > end.y {
+            return None;
+        }
+        let first = if row == start.y { start.x } else { 0 };
+        let last = if row == end.y {
+            end.x.saturating_add(1)
+        } else {
+            width
+        };
+        Some((first, last.min(width)))
+    }
+
+    /// Returns the selection expanded to whole words or lines of the buffer.
+    fn expand(&self, buffer: &Buffer) -> Self {
+        if self.block || self.unit == SelectionUnit::Cell {
+            return *self;
+        }
+        let area = buffer.area;
+        let (mut start, mut end) = self.bounds();
+        if self.unit == SelectionUnit::Line {
+            start.x = area.left();
+            end.x = area.right().saturating_sub(1);
+        } else {
+            WORD_CHARS.with(|word_chars| {
+                let word_chars = word_chars.borrow();
+                let class = |x: u16, y: u16| {
+                    buffer
+                        .cell((x, y))
+                        .map(|cell| char_class(cell.symbol(), &word_chars))
+                };
+                let start_class = class(start.x, start.y);
+                if start_class != Some(CharClass::Other) {
+                    while start.x > area.left() && class(start.x - 1, start.y) == start_class {
+                        start.x -= 1;
+                    }
+                }
+                let end_class = class(end.x, end.y);
+                if end_class != Some(CharClass::Other) {
+                    while end.x + 1 < area.right() && class(end.x + 1, end.y) == end_class {
+                        end.x += 1;
+                    }
+                }
+            });
+        }
+        Self {
+            anchor: start,
+            cursor: end,
+            block: false,
+            unit: SelectionUnit::Cell,
+        }
+    }
+
+    /// Returns `true` if the cell is selected.
+    pub fn contains(&self, position: Position) -> bool {
+        self.columns(position.y, u16::MAX)
+            .is_some_and(|(first, last)| (first..last).contains(&position.x))
+    }
+
+    /// Returns the selected text of the buffer.
+    ///
+    /// Rows are separated with newlines and their trailing whitespace is
+    /// removed, like in a terminal.
+    pub fn text(&self, buffer: &Buffer) -> String {
+        let area = buffer.area;
+        let rows = (area.top()..area.bottom())
+            .filter_map(|y| {
+                let (first, last) = self.columns(y, area.right())?;
+                let row = cells::row_text(buffer, y, first..last);
+                Some(row.trim_end().to_string())
+            })
+            .collect::<Vec<_>>();
+        rows.join("\n")
+    }
+}
+
+/// Enables the selection.
+///
+/// This adds the event filter that drives the selection, it does nothing if
+/// the selection is already enabled.
+pub(crate) fn enable() -> Result<(), Error> {
+    if ENABLED.get() {
+        return Ok(());
+    }
+    event::add_key_listener()?;
+    event::add_event_filter(handle_event);
+    add_copy_listener()?;
+    ENABLED.set(true);
+    Ok(())
+}
+
+/// Adds a listener to the document copy event that copies the selected text
+/// instead of the native selection of the browser.
+fn add_copy_listener() -> Result<(), Error> {
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: ClipboardEvent| {
+        let (Some(text), Some(data)) = (selected_text(), event.clipboard_data()) else {
+            return;
+        };
+        if data.set_data("text/plain", &text).is_ok() {
+            event.prevent_default();
+        }
+    });
+    window()
+        .ok_or(Error::UnableToRetrieveWindow)?
+        .document()
+        .ok_or(Error::UnableToRetrieveDocument)?
+        .add_event_listener_with_callback("copy", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    Ok(())
+}
+
+/// Updates the selection from an event.
+///
+/// Mouse events are passed through, the key events that extend the
+/// selection are consumed.
+fn handle_event(event: Event) -> Option<Event> {
+    match &event {
+        Event::Mouse(mouse) => {
+            let position = mouse.position();
+            match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left) => {
+                    let now = now();
+                    let clicks = match LAST_CLICK.get() {
+                        Some((time, last, clicks))
+                            if now - time < MULTI_CLICK_DELAY && last == position =>
+                        {
+                            clicks % 3 + 1
+                        }
+                        _ => 1,
+                    };
+                    LAST_CLICK.set(Some((now, position, clicks)));
+                    let unit = match clicks {
+                        2 => SelectionUnit::Word,
+                        3 => SelectionUnit::Line,
+                        _ => SelectionUnit::Cell,
+                    };
+                    let selection = match SELECTION.get() {
+                        Some(selection) if mouse.shift => Selection {
+                            cursor: position,
+                            ..selection
+                        },
+                        _ => Selection {
+                            anchor: position,
+                            cursor: position,
+                            block: mouse.alt,
+                            unit,
+                        },
+                    };
+                    SELECTION.set(Some(selection));
+                    DRAGGING.set(true);
+                }
+                // Small movements while clicking do not select.
+                MouseEventKind::Moved if DRAGGING.get() && event::is_drag() => {
+                    if let Some(selection) = SELECTION.get() {
+                        SELECTION.set(Some(Selection {
+                            cursor: position,
+                            ..selection
+                        }));
+                    }
+                }
+                MouseEventKind::Up(MouseButton::Left) if DRAGGING.get() => {
+                    DRAGGING.set(false);
+                    // A click without dragging clears the selection.
+                    if SELECTION.get().is_some_and(|selection| {
+                        selection.anchor == selection.cursor
+                            && selection.unit == SelectionUnit::Cell
+                    }) {
+                        clear();
+                    }
+                }
+                _ => {}
+            }
+            Some(event)
+        }
+        Event::Key(key) => {
+            let Some(selection) = SELECTION.get() else {
+                return Some(event);
+            };
+            let cursor = selection.cursor;
+            let cursor = match key.code {
+                KeyCode::Left if key.shift => Position::new(cursor.x.saturating_sub(1), cursor.y),
+                KeyCode::Right if key.shift => Position::new(cursor.x.saturating_add(1), cursor.y),
+                KeyCode::Up if key.shift => Position::new(cursor.x, cursor.y.saturating_sub(1)),
+                KeyCode::Down if key.shift => Position::new(cursor.x, cursor.y.saturating_add(1)),
+                KeyCode::Esc => {
+                    clear();
+                    return Some(event);
+                }
+                _ => return Some(event),
+            };
+            SELECTION.set(Some(Selection {
+                cursor,
+                ..selection
+            }));
+            None
+        }
+        _ => Some(event),
+    }
+}
+
+/// Returns the selection shown in the last frame, if any.
+///
+/// Word and line selections are returned expanded to the cells they cover.
+pub fn selection() -> Option<Selection> {
+    SHOWN.get()
+}
+
+/// Sets the characters that are part of a word for the double-click
+/// selection, besides the alphanumeric ones.
+///
+/// The default is `-#%&+,./=?@\_~:`, which selects URLs and paths as a whole.
+pub fn set_word_chars<S: Into<String>>(word_chars: S) {
+    WORD_CHARS.with(|chars| chars.replace(word_chars.into()));
+}
+
+/// Returns the selected text as of the last frame, if any.
+pub fn selected_text() -> Option<String> {
+    TEXT.with(|text| text.borrow().clone())
+}
+
+/// Clears the selection.
+pub fn clear() {
+    SELECTION.set(None);
+    SHOWN.set(None);
+    TEXT.with(|text| text.replace(None));
+}
+
+/// Highlights the selection in the buffer and records its text.
+///
+/// This is called by the renderer right before the frame is flushed.
+pub(crate) fn apply(buffer: &mut Buffer) {
+    let Some(selection) = SELECTION.get() else {
+        return;
+    };
+    // Keep the cursor in the grid, e.g. after extending it with the keys.
+    let area = buffer.area;
+    let selection = Selection {
+        cursor: Position::new(
+            selection.cursor.x.min(area.right().saturating_sub(1)),
+            selection.cursor.y.min(area.bottom().saturating_sub(1)),
+        ),
+        ..selection
+    };
+    SELECTION.set(Some(selection));
+    let selection = selection.expand(buffer);
+    SHOWN.set(Some(selection));
+    TEXT.with(|text| text.replace(Some(selection.text(buffer))));
+    for y in area.top()..area.bottom() {
+        let Some((first, last)) = selection.columns(y, area.right()) else {
+            continue;
+        };
+        for x in first..last {
+            if let Some(cell) = buffer.cell_mut((x, y)) {
+                cell.modifier.toggle(Modifier::REVERSED);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a selection of cells between two positions.
+    fn selection(anchor: (u16, u16), cursor: (u16, u16), block: bool) -> Selection {
+        Selection {
+            anchor: anchor.into(),
+            cursor: cursor.into(),
+            block,
+            unit: SelectionUnit::Cell,
+        }
+    }
+
+    #[test]
+    fn columns() {
+        let stream = selection((3, 4), (2, 2), false);
+        assert_eq!(stream.columns(1, 10), None);
+        assert_eq!(stream.columns(2, 10), Some((2, 10)));
+        assert_eq!(stream.columns(3, 10), Some((0, 10)));
+        assert_eq!(stream.columns(4, 10), Some((0, 4)));
+        assert_eq!(stream.columns(5, 10), None);
+
+        let block = selection((3, 2), (1, 0), true);
+        assert_eq!(block.columns(0, 10), Some((1, 4)));
+        assert_eq!(block.columns(2, 3), Some((1, 3)));
+        assert_eq!(block.columns(3, 10), None);
+    }
+
+    #[test]
+    fn expand() {
+        let buffer = Buffer::with_lines(["foo bar(baz"]);
+        let word = Selection {
+            unit: SelectionUnit::Word,
+            ..selection((5, 0), (5, 0), false)
+        };
+        assert_eq!(word.expand(&buffer), selection((4, 0), (6, 0), false));
+        // Symbols that are not part of a word are selected on their own.
+        let other = Selection {
+            unit: SelectionUnit::Word,
+            ..selection((7, 0), (7, 0), false)
+        };
+        assert_eq!(other.expand(&buffer), selection((7, 0), (7, 0), false));
+        let line = Selection {
+            unit: SelectionUnit::Line,
+            ..selection((5, 0), (5, 0), false)
+        };
+        assert_eq!(line.expand(&buffer), selection((0, 0), (10, 0), false));
+        let block = Selection {
+            unit: SelectionUnit::Line,
+            ..selection((5, 0), (5, 0), true)
+        };
+        assert_eq!(block.expand(&buffer), block);
+    }
+
+    #[test]
+    fn text() {
+        let buffer = Buffer::with_lines(["ab  ", "日本 c", "defg"]);
+        assert_eq!(
+            selection((1, 0), (1, 2), false).text(&buffer),
+            "b\n日本 c\nde"
+        );
+        assert_eq!(selection((0, 1), (3, 1), false).text(&buffer), "日本");
+        // The wide symbols starting in a block are included whole.
+        assert_eq!(selection((2, 0), (2, 2), true).text(&buffer), "\n本\nf");
+    }
+}
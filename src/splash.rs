@@ -0,0 +1,114 @@
+//! ## Splash screen
+//!
+//! Large applications can take a while to start: the WebAssembly module has
+//! to be downloaded and instantiated, the fonts have to be loaded and the
+//! first frame has to be built. A splash screen is shown in the meantime and
+//! removed in one go when the first frame is flushed by the backend.
+//!
+//! To show the splash screen before the WebAssembly module is loaded, add an
+//! element with the [`SPLASH_ID`] id to the page:
+//!
+//! ```html
+//! <div id="ratzilla-splash">Loading...</div>
+//! ```
+//!
+//! Or show it from the application, e.g. before a slow initialization:
+//!
+//! ```rust no_run
+//! use ratzilla::splash::Splash;
+//!
+//! Splash::new("my-app").spinner(true).show().unwrap();
+//! ```
+
+use web_sys::window;
+
+use crate::{backend::utils::set_stylesheet, error::Error};
+
+/// Id of the splash screen element.
+pub const SPLASH_ID: &str = "ratzilla-splash";
+
+/// Style of the splash screen element.
+const SPLASH_STYLE: &str = "position: fixed; inset: 0; display: flex; \
+    flex-direction: column; align-items: center; justify-content: center; \
+    gap: 1em; white-space: pre; font-family: monospace; z-index: 2147483646;";
+
+/// Stylesheet of the spinner animation.
+const SPINNER_CSS: &str = "#ratzilla-splash .ratzilla-spinner::after { \
+    content: '|'; animation: ratzilla-spin 0.8s steps(4) infinite; } \
+    @keyframes ratzilla-spin { 0% { content: '|'; } 25% { content: '/'; } \
+    50% { content: '-'; } 75% { content: '\\\\'; } }";
+
+/// A splash screen.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Splash {
+    /// Text of the splash screen (e.g. a logo).
+    text: String,
+    /// Whether to show a spinner below the text.
+    spinner: bool,
+}
+
+impl Splash {
+    /// Constructs a new [`Splash`] with the given text.
+    ///
+    /// The text is shown as is, so it can be a multi-line logo.
+    pub fn new<T: Into<String>>(text: T) -> Self {
+        Self {
+            text: text.into(),
+            spinner: false,
+        }
+    }
+
+    /// Sets whether to show a spinner below the text.
+    pub fn spinner(mut self, spinner: bool) -> Self {
+        self.spinner = spinner;
+        self
+    }
+
+    /// Shows the splash screen.
+    ///
+    /// An existing splash screen (e.g. from the page) is replaced.
+    pub fn show(&self) -> Result<(), Error> {
+        let document = window()
+            .ok_or(Error::UnableToRetrieveWindow)?
+            .document()
+            .ok_or(Error::UnableToRetrieveDocument)?;
+        if let Some(splash) = document.get_element_by_id(SPLASH_ID) {
+            splash.remove();
+        }
+        let splash = document.create_element("div")?;
+        splash.set_attribute("id", SPLASH_ID)?;
+        splash.set_attribute("style", SPLASH_STYLE)?;
+        splash.set_attribute("aria-busy", "true")?;
+        let text = document.create_element("div")?;
+        text.set_text_content(Some(&self.text));
+        splash.append_child(&text)?;
+        if self.spinner {
+            let spinner = document.create_element("div")?;
+            spinner.set_attribute("class", "ratzilla-spinner")?;
+            splash.append_child(&spinner)?;
+        }
+        set_stylesheet(
+            &document,
+            &format!("{SPLASH_ID}-style"),
+            self.spinner.then_some(SPINNER_CSS),
+        )?;
+        document
+            .body()
+            .ok_or(Error::UnableToRetrieveBody)?
+            .append_child(&splash)?;
+        Ok(())
+    }
+}
+
+/// Removes the splash screen, if any.
+///
+/// This is called by the backends once the first frame is flushed.
+pub(crate) fn hide() {
+    let Some(document) = window().and_then(|window| window.document()) else {
+        return;
+    };
+    if let Some(splash) = document.get_element_by_id(SPLASH_ID) {
+        splash.remove();
+    }
+    let _ = set_stylesheet(&document, &format!("{SPLASH_ID}-style"), None);
+}
@@ -0,0 +1,96 @@
+//! ## Startup timings
+//!
+//! The backends defer the work that the first frame does not need, e.g. the
+//! event listeners are added on the first render instead of when the backend
+//! is built, and the optional subsystems (selection, analytics, debugger, ...)
+//! add theirs when they are first used. The phases that remain are timed, so
+//! the time to the first frame of a page can be checked:
+//!
+//! ```rust no_run
+//! use ratzilla::startup;
+//!
+//! for phase in startup::phases() {
+//!     println!("{}: {:.1}ms", phase.name, phase.duration);
+//! }
+//! if let Some(time) = startup::time_to_first_frame() {
+//!     println!("first frame after {time:.1}ms");
+//! }
+//! ```
+//!
+//! The phases are also logged to the console with
+//! [`Level::Debug`](crate::console::Level::Debug) once the first frame is
+//! rendered.
+
+use std::cell::{Cell, RefCell};
+
+use crate::{utils, web_debug};
+
+thread_local! {
+    /// Timed phases of the startup.
+    static PHASES: RefCell<Vec<Phase>> = const { RefCell::new(Vec::new()) };
+    /// Time of the first rendered frame.
+    static FIRST_FRAME: Cell<Option<f64>> = const { Cell::new(None) };
+}
+
+/// A timed phase of the startup.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Phase {
+    /// Name of the phase, e.g. `backend`.
+    pub name: &'static str,
+    /// Start of the phase in milliseconds since the time origin of the page.
+    pub start: f64,
+    /// Duration of the phase in milliseconds.
+    pub duration: f64,
+}
+
+/// Returns the phases of the startup timed so far, in the order they
+/// started.
+pub fn phases() -> Vec<Phase> {
+    PHASES.with(|phases| phases.borrow().clone())
+}
+
+/// Returns the time of the first rendered frame in milliseconds since the
+/// time origin of the page, or `None` if no frame has been rendered yet.
+pub fn time_to_first_frame() -> Option<f64> {
+    FIRST_FRAME.get()
+}
+
+/// Runs a phase of the startup and records how long it took.
+pub(crate) fn measure<T>(name: &'static str, phase: impl FnOnce() -> T) -> T {
+    let start = utils::performance_now();
+    let result = phase();
+    let duration = utils::performance_now() - start;
+    PHASES.with(|phases| {
+        phases.borrow_mut().push(Phase {
+            name,
+            start,
+            duration,
+        })
+    });
+    result
+}
+
+/// Records a rendered frame that started at the given time.
+///
+/// Only the first frame is recorded, the phases are logged then.
+pub(crate) fn record_frame(start: f64) {
+    if FIRST_FRAME.get().is_some() {
+        return;
+    }
+    let now = utils::performance_now();
+    FIRST_FRAME.set(Some(now));
+    PHASES.with(|phases| {
+        let mut phases = phases.borrow_mut();
+        phases.push(Phase {
+            name: "first frame",
+            start,
+            duration: now - start,
+        });
+        let summary = phases
+            .iter()
+            .map(|phase| format!("{} {:.1}ms", phase.name, phase.duration))
+            .collect::<Vec<_>>()
+            .join(", ");
+        web_debug!("first frame after {now:.1}ms ({summary})");
+    });
+}
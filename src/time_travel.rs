@@ -0,0 +1,350 @@
+//! ## Time-travel debugging
+//!
+//! Records the state of the application after every frame and lets it be
+//! rewound and replayed, similar to the Redux DevTools. The state is shared
+//! with the render callback, which renders whatever state it finds:
+//!
+//! ```rust no_run
+//! use std::{cell::RefCell, rc::Rc};
+//!
+//! use ratzilla::time_travel;
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! struct State {
+//!     counter: u32,
+//! }
+//!
+//! let state = Rc::new(RefCell::new(State { counter: 0 }));
+//! time_travel::track(Rc::clone(&state)).unwrap();
+//! // Render `state` in the render callback and update it in the event
+//! // handlers as usual...
+//! ```
+//!
+//! A snapshot is recorded whenever the state differs from the previous one.
+//! Rewinding restores an older snapshot into the shared state and redraws, so
+//! the frame of that state is rendered again. Changing the state while
+//! rewound discards the snapshots after it, like undo and redo.
+//!
+//! The snapshots are stepped through with the rewind and forward keys
+//! (<kbd>F8</kbd> and <kbd>F9</kbd> by default), with [`rewind`] and
+//! [`forward`], or from the developer tools of the browser:
+//!
+//! ```js
+//! ratzillaRewind();  // Restores the previous snapshot.
+//! ratzillaForward(); // Restores the next snapshot.
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    fmt::Debug,
+    rc::Rc,
+};
+
+use web_sys::{
+    js_sys::Reflect,
+    wasm_bindgen::{prelude::Closure, JsValue},
+    window,
+};
+
+use crate::{
+    error::Error,
+    event::{self, Event, KeyBinding, KeyCode},
+    render, web_debug,
+};
+
+/// Default number of snapshots that are kept.
+const DEFAULT_CAPACITY: usize = 100;
+
+thread_local! {
+    /// History of the tracked state.
+    static HISTORY: RefCell<Option<Box<dyn Timeline>>> = const { RefCell::new(None) };
+    /// Number of snapshots that are kept.
+    static CAPACITY: Cell<usize> = const { Cell::new(DEFAULT_CAPACITY) };
+    /// Keys that restore the previous and the next snapshot.
+    static KEYS: RefCell<(Option<KeyBinding>, Option<KeyBinding>)> = RefCell::new((
+        Some(KeyBinding::new(KeyCode::F(8))),
+        Some(KeyBinding::new(KeyCode::F(9))),
+    ));
+    /// Whether the key filter and the global functions are added.
+    static INSTALLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Starts recording the snapshots of the given state.
+///
+/// The state must be [`Clone`] for taking snapshots and [`PartialEq`] for
+/// recording only the frames that changed it. Restored snapshots are logged
+/// with their [`Debug`] representation. A previously tracked state is
+/// replaced, together with its snapshots.
+pub fn track<T>(state: Rc<RefCell<T>>) -> Result<(), Error>
+where
+    T: Clone + PartialEq + Debug + 'static,
+{
+    install()?;
+    let snapshot = state.borrow().clone();
+    let mut history = History::new(CAPACITY.get());
+    history.record(snapshot);
+    HISTORY.with(|h| {
+        *h.borrow_mut() = Some(Box::new(Tracked { state, history }));
+    });
+    Ok(())
+}
+
+/// Stops recording the snapshots and drops them.
+///
+/// The tracked state is left as it is.
+pub fn untrack() {
+    HISTORY.with(|history| history.borrow_mut().take());
+}
+
+/// Restores the previous snapshot and redraws.
+pub fn rewind() {
+    travel(|timeline| timeline.rewind());
+}
+
+/// Restores the next snapshot and redraws.
+pub fn forward() {
+    travel(|timeline| timeline.forward());
+}
+
+/// Returns the index of the current snapshot and the number of snapshots, or
+/// `None` if no state is tracked.
+pub fn position() -> Option<(usize, usize)> {
+    HISTORY.with(|history| {
+        history
+            .borrow()
+            .as_ref()
+            .map(|timeline| timeline.position())
+    })
+}
+
+/// Sets the number of snapshots that are kept, the oldest ones are dropped
+/// first.
+///
+/// This applies to states that are tracked afterwards. The default is 100.
+pub fn set_capacity(capacity: usize) {
+    CAPACITY.set(capacity.max(1));
+}
+
+/// Sets the keys that restore the previous and the next snapshot, or `None`
+/// to only step with [`rewind`] and [`forward`].
+///
+/// The key events of these keys are not passed to the handlers while a state
+/// is tracked. The defaults are <kbd>F8</kbd> and <kbd>F9</kbd>.
+pub fn set_keys(rewind: Option<KeyBinding>, forward: Option<KeyBinding>) {
+    KEYS.with(|keys| keys.replace((rewind, forward)));
+}
+
+/// Records a snapshot of the tracked state if it changed.
+///
+/// This is called after the render callback of every frame.
+pub(crate) fn record() {
+    HISTORY.with(|history| {
+        if let Some(timeline) = history.borrow_mut().as_mut() {
+            timeline.record();
+        }
+    });
+}
+
+/// Moves through the snapshots and redraws if a snapshot was restored.
+fn travel(step: impl FnOnce(&mut Box<dyn Timeline>) -> Option<String>) {
+    let restored = HISTORY.with(|history| history.borrow_mut().as_mut().and_then(step));
+    if let Some(snapshot) = restored {
+        if let Some((index, len)) = position() {
+            web_debug!("time travel to snapshot {}/{len}: {snapshot}", index + 1);
+        }
+        render::request_redraw();
+    }
+}
+
+/// Adds the key filter and the global functions, unless they are already
+/// added.
+fn install() -> Result<(), Error> {
+    if INSTALLED.get() {
+        return Ok(());
+    }
+    event::add_key_listener()?;
+    event::add_event_filter(|event| {
+        let Event::Key(key) = &event else {
+            return Some(event);
+        };
+        if position().is_none() {
+            return Some(event);
+        }
+        let (is_rewind, is_forward) = KEYS.with(|keys| {
+            let (rewind, forward) = &*keys.borrow();
+            (
+                rewind.as_ref().is_some_and(|binding| binding.matches(key)),
+                forward.as_ref().is_some_and(|binding| binding.matches(key)),
+            )
+        });
+        if is_rewind {
+            rewind();
+        } else if is_forward {
+            forward();
+        } else {
+            return Some(event);
+        }
+        None
+    });
+    let window = window().ok_or(Error::UnableToRetrieveWindow)?;
+    let rewind = Closure::<dyn FnMut()>::new(rewind);
+    Reflect::set(
+        &window,
+        &JsValue::from_str("ratzillaRewind"),
+        rewind.as_ref(),
+    )?;
+    rewind.forget();
+    let forward = Closure::<dyn FnMut()>::new(forward);
+    Reflect::set(
+        &window,
+        &JsValue::from_str("ratzillaForward"),
+        forward.as_ref(),
+    )?;
+    forward.forget();
+    INSTALLED.set(true);
+    Ok(())
+}
+
+/// Type-erased history of a tracked state.
+trait Timeline {
+    /// Records a snapshot of the state if it changed.
+    fn record(&mut self);
+
+    /// Restores the previous snapshot and returns its description.
+    fn rewind(&mut self) -> Option<String>;
+
+    /// Restores the next snapshot and returns its description.
+    fn forward(&mut self) -> Option<String>;
+
+    /// Returns the index of the current snapshot and the number of snapshots.
+    fn position(&self) -> (usize, usize);
+}
+
+/// A tracked state and its snapshots.
+struct Tracked<T> {
+    /// State shared with the application.
+    state: Rc<RefCell<T>>,
+    /// Snapshots of the state.
+    history: History<T>,
+}
+
+impl<T: Clone + PartialEq + Debug> Tracked<T> {
+    /// Restores the current snapshot into the state.
+    fn restore(&mut self) -> Option<String> {
+        let snapshot = self.history.current()?.clone();
+        let description = format!("{snapshot:?}");
+        *self.state.borrow_mut() = snapshot;
+        Some(description)
+    }
+}
+
+impl<T: Clone + PartialEq + Debug> Timeline for Tracked<T> {
+    fn record(&mut self) {
+        let state = self.state.borrow();
+        if self.history.current() != Some(&*state) {
+            self.history.record(state.clone());
+        }
+    }
+
+    fn rewind(&mut self) -> Option<String> {
+        self.history.rewind().then(|| self.restore()).flatten()
+    }
+
+    fn forward(&mut self) -> Option<String> {
+        self.history.forward().then(|| self.restore()).flatten()
+    }
+
+    fn position(&self) -> (usize, usize) {
+        (self.history.cursor, self.history.snapshots.len())
+    }
+}
+
+/// Ring buffer of snapshots with a cursor.
+#[derive(Debug, Clone)]
+struct History<T> {
+    /// Snapshots from the oldest to the newest.
+    snapshots: VecDeque<T>,
+    /// Index of the current snapshot.
+    cursor: usize,
+    /// Largest number of snapshots.
+    capacity: usize,
+}
+
+impl<T> History<T> {
+    /// Constructs an empty [`History`] with the given capacity.
+    fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            cursor: 0,
+            capacity,
+        }
+    }
+
+    /// Returns the current snapshot.
+    fn current(&self) -> Option<&T> {
+        self.snapshots.get(self.cursor)
+    }
+
+    /// Adds a snapshot after the current one.
+    ///
+    /// The snapshots after the current one are discarded and the oldest
+    /// snapshot is dropped if the history is full.
+    fn record(&mut self, snapshot: T) {
+        self.snapshots.truncate(self.cursor + 1);
+        if self.snapshots.len() >= self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+        self.cursor = self.snapshots.len() - 1;
+    }
+
+    /// Moves to the previous snapshot, returns `false` at the oldest one.
+    fn rewind(&mut self) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+        self.cursor -= 1;
+        true
+    }
+
+    /// Moves to the next snapshot, returns `false` at the newest one.
+    fn forward(&mut self) -> bool {
+        if self.cursor + 1 >= self.snapshots.len() {
+            return false;
+        }
+        self.cursor += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history() {
+        let mut history = History::new(3);
+        assert_eq!(history.current(), None);
+        assert!(!history.rewind());
+        for snapshot in 1..=4 {
+            history.record(snapshot);
+        }
+        // The oldest snapshot is dropped.
+        assert_eq!(Vec::from(history.snapshots.clone()), [2, 3, 4]);
+        assert_eq!(history.current(), Some(&4));
+        assert!(!history.forward());
+
+        assert!(history.rewind());
+        assert!(history.rewind());
+        assert!(!history.rewind());
+        assert_eq!(history.current(), Some(&2));
+        assert!(history.forward());
+        assert_eq!(history.current(), Some(&3));
+
+        // Recording while rewound discards the newer snapshots.
+        history.record(5);
+        assert_eq!(Vec::from(history.snapshots.clone()), [2, 3, 5]);
+        assert_eq!(history.current(), Some(&5));
+    }
+}
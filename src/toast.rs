@@ -0,0 +1,219 @@
+//! ## Toasts
+//!
+//! Toasts are short notifications that slide in at the top right corner of
+//! the grid and disappear after a while. They can be shown from anywhere,
+//! e.g. from an event handler, and are drawn over the frame after the render
+//! callback and the render layers.
+//!
+//! ```rust no_run
+//! use std::time::Duration;
+//!
+//! use ratzilla::toast::{Severity, Toast};
+//!
+//! Toast::new("Settings saved")
+//!     .severity(Severity::Success)
+//!     .duration(Duration::from_secs(2))
+//!     .show();
+//! ```
+//!
+//! At most [`MAX_VISIBLE`] toasts are shown at once, the others are queued
+//! and shown as soon as there is room.
+
+use std::{cell::RefCell, collections::VecDeque, time::Duration};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::Text,
+    widgets::{Block, BorderType, Padding, Paragraph, Widget},
+    Frame,
+};
+
+use crate::{
+    render,
+    responsive::{wrap_text, WrapOptions},
+    utils::now,
+};
+
+/// Maximum number of toasts shown at once.
+pub const MAX_VISIBLE: usize = 3;
+
+/// Maximum width of a toast, including the borders.
+const MAX_WIDTH: u16 = 40;
+
+/// Duration of the slide-in animation in milliseconds.
+const SLIDE_DURATION: f64 = 200.0;
+
+thread_local! {
+    /// Toasts that are shown or queued.
+    static TOASTS: RefCell<VecDeque<Entry>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// Severity of a toast, which sets its color.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum Severity {
+    /// Informational message.
+    #[default]
+    Info,
+    /// Successful operation.
+    Success,
+    /// Something that may need attention.
+    Warning,
+    /// Failed operation.
+    Error,
+}
+
+impl Severity {
+    /// Returns the color of the severity.
+    fn color(self) -> Color {
+        match self {
+            Self::Info => Color::Blue,
+            Self::Success => Color::Green,
+            Self::Warning => Color::Yellow,
+            Self::Error => Color::Red,
+        }
+    }
+}
+
+/// A toast notification.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Toast {
+    /// Message of the toast.
+    message: String,
+    /// Optional title, shown in the border.
+    title: Option<String>,
+    /// Severity of the toast.
+    severity: Severity,
+    /// How long the toast is shown.
+    duration: Duration,
+}
+
+impl Toast {
+    /// Constructs a new [`Toast`] with the given message.
+    ///
+    /// By default, the toast is informational and shown for 3 seconds.
+    pub fn new<T: Into<String>>(message: T) -> Self {
+        Self {
+            message: message.into(),
+            title: None,
+            severity: Severity::default(),
+            duration: Duration::from_secs(3),
+        }
+    }
+
+    /// Sets the title of the toast.
+    pub fn title<T: Into<String>>(mut self, title: T) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the severity of the toast.
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Sets how long the toast is shown.
+    ///
+    /// The time spent in the queue is not counted.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Shows the toast, or queues it if [`MAX_VISIBLE`] toasts are already
+    /// shown.
+    pub fn show(self) {
+        TOASTS.with(|toasts| {
+            toasts.borrow_mut().push_back(Entry {
+                toast: self,
+                shown_at: None,
+            })
+        });
+        render::request_redraw();
+    }
+
+    /// Renders the toast into a buffer of the given width.
+    fn render(&self, width: u16) -> Buffer {
+        let text = wrap_text(
+            &Text::raw(self.message.as_str()),
+            width.saturating_sub(4),
+            WrapOptions::new(),
+        );
+        let height = u16::try_from(text.lines.len())
+            .unwrap_or(u16::MAX)
+            .saturating_add(2);
+        let style = Style::default().fg(self.severity.color());
+        let mut block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(style);
+        if let Some(title) = &self.title {
+            block = block.title(format!(" {title} "));
+        }
+        let mut buffer = Buffer::empty(Rect::new(0, 0, width, height));
+        Paragraph::new(text)
+            .block(block.padding(Padding::horizontal(1)))
+            .style(Style::default().bg(Color::Black))
+            .render(buffer.area, &mut buffer);
+        buffer
+    }
+}
+
+/// A toast along with the time it was first shown.
+#[derive(Debug)]
+struct Entry {
+    /// The toast.
+    toast: Toast,
+    /// Time the toast was first shown in milliseconds, `None` if queued.
+    shown_at: Option<f64>,
+}
+
+/// Removes all the toasts, including the queued ones.
+pub fn clear() {
+    TOASTS.with(|toasts| toasts.borrow_mut().clear());
+}
+
+/// Draws the visible toasts over the frame.
+///
+/// This is called by the renderer after the render callback.
+pub(crate) fn render(frame: &mut Frame) {
+    let now = now();
+    let area = frame.area();
+    TOASTS.with(|toasts| {
+        let mut toasts = toasts.borrow_mut();
+        toasts.retain(|entry| {
+            entry
+                .shown_at
+                .is_none_or(|shown_at| now - shown_at < entry.toast.duration.as_secs_f64() * 1000.0)
+        });
+        let width = area.width.saturating_sub(2).min(MAX_WIDTH);
+        if width < 5 {
+            return;
+        }
+        let mut y = area.top().saturating_add(1);
+        for entry in toasts.iter_mut().take(MAX_VISIBLE) {
+            let shown_at = *entry.shown_at.get_or_insert(now);
+            let progress = ((now - shown_at) / SLIDE_DURATION).clamp(0.0, 1.0);
+            // Slide in from the right edge of the grid.
+            let offset = (f64::from(width + 1) * (1.0 - progress)).round() as u16;
+            let toast = entry.toast.render(width);
+            let left = area.right() - width - 1 + offset;
+            let buffer = frame.buffer_mut();
+            for row in 0..toast.area.height {
+                for column in 0..width {
+                    let position = (left.saturating_add(column), y.saturating_add(row));
+                    if position.0 >= area.right() || position.1 >= area.bottom() {
+                        continue;
+                    }
+                    if let (Some(cell), Some(toast_cell)) =
+                        (buffer.cell_mut(position), toast.cell((column, row)))
+                    {
+                        *cell = toast_cell.clone();
+                    }
+                }
+            }
+            y = y.saturating_add(toast.area.height);
+        }
+    });
+}
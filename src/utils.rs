@@ -1,3 +1,5 @@
+use web_sys::js_sys::Date;
+
 use crate::error::Error;
 
 /// Sets the document title.
@@ -22,3 +24,17 @@ pub fn open_url(url: &str, new_tab: bool) -> Result<(), Error> {
     }
     Ok(())
 }
+
+/// Returns the current time in milliseconds.
+pub(crate) fn now() -> f64 {
+    Date::now()
+}
+
+/// Returns the time since the time origin of the page in milliseconds.
+///
+/// This is the clock of the `timeStamp` of the DOM events.
+pub(crate) fn performance_now() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or_else(now, |performance| performance.now())
+}
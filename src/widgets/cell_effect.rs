@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+use crate::{
+    effects::EffectKind,
+    metadata::{self, RegionKind},
+};
+
+/// A widget that applies an animated effect to the cells of an area.
+///
+/// This provides shader-like transitions similar to [tachyonfx]: fades,
+/// sweeps and dissolves. Render it over an area after rendering the content.
+/// The effect starts on the first frame the area appears in and an
+/// [`EffectEvent`] with the name of the effect is emitted once it finishes.
+///
+/// ```rust no_run
+/// use std::time::Duration;
+///
+/// use ratzilla::{
+///     effects::{Direction, EffectKind},
+///     ratatui::style::Color,
+///     widgets::CellEffect,
+/// };
+///
+/// let fade = CellEffect::new("fade", EffectKind::FadeIn(Color::Black))
+///     .duration(Duration::from_millis(500));
+/// let sweep = CellEffect::new("sweep", EffectKind::SweepIn(Direction::LeftToRight));
+///
+/// // Render the content and then the effect over it:
+/// // frame.render_widget(paragraph, area);
+/// // frame.render_widget(fade, area);
+/// ```
+///
+/// The effect restarts if it is not rendered for a frame.
+///
+/// [tachyonfx]: https://github.com/junkdog/tachyonfx
+/// [`EffectEvent`]: crate::event::EffectEvent
+pub struct CellEffect {
+    /// Name.
+    name: String,
+    /// Kind of the effect.
+    kind: EffectKind,
+    /// Duration.
+    duration: Duration,
+}
+
+impl CellEffect {
+    /// Constructs a new [`CellEffect`] with the given name and kind.
+    ///
+    /// The name identifies the effect across frames.
+    pub fn new<T: Into<String>>(name: T, kind: EffectKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            duration: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets the duration of the effect.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+}
+
+impl Widget for CellEffect {
+    fn render(self, area: Rect, _buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        metadata::register(
+            area,
+            RegionKind::Effect {
+                name: self.name,
+                kind: self.kind,
+                duration: self.duration.as_secs_f64() * 1000.0,
+            },
+        );
+    }
+}
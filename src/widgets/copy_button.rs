@@ -0,0 +1,68 @@
+use ratatui::{buffer::Buffer, layout::Rect, style::Style, text::Span, widgets::Widget};
+
+use crate::metadata::{self, RegionKind};
+
+/// A button that copies text to the clipboard when clicked.
+///
+/// The label is rendered as usual and the area is registered as a clickable
+/// region with the [`DomBackend`]. Clicking it writes the text to the
+/// clipboard, shows a short "Copied!" label and emits a [`CopyEvent`] which can
+/// be handled with [`WebRenderer::on_copy_event`].
+///
+/// ```rust no_run
+/// use ratzilla::widgets::CopyButton;
+///
+/// let button = CopyButton::new("cargo add ratzilla").label("[copy]");
+///
+/// // Then you can render it as usual:
+/// // frame.render_widget(button, area);
+/// ```
+///
+/// [`DomBackend`]: crate::DomBackend
+/// [`CopyEvent`]: crate::event::CopyEvent
+/// [`WebRenderer::on_copy_event`]: crate::WebRenderer::on_copy_event
+pub struct CopyButton<'a> {
+    /// Text to copy.
+    text: String,
+    /// Label.
+    label: Span<'a>,
+}
+
+impl<'a> CopyButton<'a> {
+    /// Constructs a new [`CopyButton`] widget.
+    pub fn new<T: Into<String>>(text: T) -> Self {
+        Self {
+            text: text.into(),
+            label: Span::raw("[Copy]"),
+        }
+    }
+
+    /// Sets the label of the button.
+    pub fn label<T>(mut self, label: T) -> Self
+    where
+        T: Into<Span<'a>>,
+    {
+        self.label = label.into();
+        self
+    }
+
+    /// Sets the style of the label.
+    pub fn style<S: Into<Style>>(mut self, style: S) -> Self {
+        self.label = self.label.style(style);
+        self
+    }
+}
+
+impl Widget for CopyButton<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let width = (self.label.width() as u16).min(area.width);
+        self.label.render(area, buf);
+        metadata::register(
+            Rect::new(area.x, area.y, width, area.height.min(1)),
+            RegionKind::CopyButton(self.text),
+        );
+    }
+}
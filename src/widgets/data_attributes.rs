@@ -0,0 +1,88 @@
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+use crate::{
+    metadata::{self, RegionKind},
+    web_warn,
+};
+
+/// A widget that attaches `data-*` attributes to an area.
+///
+/// It does not draw anything, instead the attributes are set on every cell
+/// element of the area when rendered with the [`DomBackend`]. This makes it
+/// possible for end-to-end tests and analytics tools to target specific parts
+/// of the UI.
+///
+/// ```rust no_run
+/// use ratzilla::widgets::DataAttributes;
+///
+/// let attributes = DataAttributes::new()
+///     .test_id("sidebar")
+///     .attribute("section", "navigation");
+///
+/// // Then you can render it over the area to tag:
+/// // frame.render_widget(attributes, area);
+/// ```
+///
+/// [`DomBackend`]: crate::DomBackend
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataAttributes {
+    /// Attribute names (without the `data-` prefix) and values.
+    attributes: Vec<(String, String)>,
+}
+
+impl DataAttributes {
+    /// Constructs a new [`DataAttributes`] widget.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `data-testid` attribute.
+    pub fn test_id<T: Into<String>>(self, id: T) -> Self {
+        self.attribute("testid", id)
+    }
+
+    /// Sets a `data-*` attribute.
+    ///
+    /// The `data-` prefix is added to the name if it is missing. The name may
+    /// only contain letters, digits, `-`, `_` and `.`, otherwise the
+    /// attribute is dropped with a warning, since the browser would reject
+    /// it.
+    pub fn attribute<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        let name = name.into();
+        let name = if name.starts_with("data-") {
+            name
+        } else {
+            format!("data-{name}")
+        };
+        if !is_valid_name(&name) {
+            web_warn!("ignoring the invalid data attribute name {name:?}");
+            return self;
+        }
+        self.attributes.push((name, value.into()));
+        self
+    }
+}
+
+/// Returns `true` if the name of a `data-*` attribute can be set on an
+/// element.
+fn is_valid_name(name: &str) -> bool {
+    name.strip_prefix("data-").is_some_and(|suffix| {
+        !suffix.is_empty()
+            && suffix
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    })
+}
+
+impl Widget for DataAttributes {
+    fn render(self, area: Rect, _buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        metadata::register(area, RegionKind::DataAttributes(self.attributes));
+    }
+}
@@ -0,0 +1,94 @@
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+
+use crate::{
+    backend::utils::ansi_to_rgb,
+    metadata::{self, RegionKind},
+};
+
+/// A widget that adds the text decorations that ratatui styles lack to an
+/// area.
+///
+/// It does not draw anything, instead the decorations are drawn over the
+/// cell elements of the area when rendered with the [`DomBackend`]:
+///
+/// - An overline, which has no [`Modifier`].
+/// - A strikethrough line with its own color, while [`Modifier::CROSSED_OUT`]
+///   always uses the color of the text.
+///
+/// ```rust no_run
+/// use ratzilla::{ratatui::style::Color, widgets::Decoration};
+///
+/// let decoration = Decoration::new()
+///     .overline()
+///     .strikethrough_color(Color::Red);
+///
+/// // Then you can render it over the area to decorate:
+/// // frame.render_widget(decoration, area);
+/// ```
+///
+/// [`DomBackend`]: crate::DomBackend
+/// [`Modifier`]: ratatui::style::Modifier
+/// [`Modifier::CROSSED_OUT`]: ratatui::style::Modifier::CROSSED_OUT
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Decoration {
+    /// Color of the overline, [`Color::Reset`] for the color of the text.
+    overline: Option<Color>,
+    /// Color of the strikethrough line, [`Color::Reset`] for the color of the
+    /// text.
+    strikethrough: Option<Color>,
+}
+
+impl Decoration {
+    /// Constructs a new [`Decoration`] widget without decorations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an overline in the color of the text.
+    pub fn overline(self) -> Self {
+        self.overline_color(Color::Reset)
+    }
+
+    /// Adds an overline in the given color.
+    pub fn overline_color(mut self, color: Color) -> Self {
+        self.overline = Some(color);
+        self
+    }
+
+    /// Adds a strikethrough line in the color of the text.
+    pub fn strikethrough(self) -> Self {
+        self.strikethrough_color(Color::Reset)
+    }
+
+    /// Adds a strikethrough line in the given color.
+    pub fn strikethrough_color(mut self, color: Color) -> Self {
+        self.strikethrough = Some(color);
+        self
+    }
+}
+
+/// Converts the color of a decoration to CSS.
+fn decoration_color(color: Color) -> String {
+    ansi_to_rgb(color).map_or_else(
+        || "currentColor".to_string(),
+        |(r, g, b)| format!("rgb({r}, {g}, {b})"),
+    )
+}
+
+impl Widget for Decoration {
+    fn render(self, area: Rect, _buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        if self.overline.is_none() && self.strikethrough.is_none() {
+            return;
+        }
+        metadata::register(
+            area,
+            RegionKind::Decoration {
+                overline: self.overline.map(decoration_color),
+                strikethrough: self.strikethrough.map(decoration_color),
+            },
+        );
+    }
+}
@@ -1,35 +1,65 @@
-use ratatui::{buffer::Buffer, layout::Rect, style::Modifier, text::Span, widgets::Widget};
+use ratatui::{buffer::Buffer, layout::Rect, style::Style, text::Span, widgets::Widget};
 
-/// Hyperlink modifier.
-///
-/// When added as a modifier to a style, the styled element is marked as
-/// hyperlink.
-pub(crate) const HYPERLINK_MODIFIER: Modifier = Modifier::SLOW_BLINK;
+use crate::metadata::{self, RegionKind};
 
 /// A widget that can be used to render hyperlinks.
 ///
+/// The text is rendered as usual and the area is registered as a hyperlink
+/// with the [`DomBackend`], which renders it as an `<a>` element. By default,
+/// the text is the URL itself.
+///
 /// ```rust no_run
 /// use ratzilla::widgets::Hyperlink;
 ///
-/// let link = Hyperlink::new("https://ratatui.rs");
+/// let link = Hyperlink::new("https://ratatui.rs/introduction/")
+///     .text("Docs")
+///     .target("_blank");
 ///
 /// // Then you can render it as usual:
 /// // frame.render_widget(link, frame.area());
 /// ```
+///
+/// [`DomBackend`]: crate::DomBackend
 pub struct Hyperlink<'a> {
-    /// Line.
-    line: Span<'a>,
+    /// URL of the hyperlink.
+    url: String,
+    /// Displayed text.
+    text: Span<'a>,
+    /// Browsing context to open the URL in.
+    target: Option<String>,
 }
 
 impl<'a> Hyperlink<'a> {
     /// Constructs a new [`Hyperlink`] widget.
-    pub fn new<T>(url: T) -> Self
+    pub fn new<T: Into<String>>(url: T) -> Self {
+        let url = url.into();
+        Self {
+            text: Span::raw(url.clone()),
+            url,
+            target: None,
+        }
+    }
+
+    /// Sets the displayed text of the hyperlink.
+    pub fn text<T>(mut self, text: T) -> Self
     where
         T: Into<Span<'a>>,
     {
-        Self {
-            line: url.into().style(HYPERLINK_MODIFIER),
-        }
+        self.text = text.into();
+        self
+    }
+
+    /// Sets the style of the text.
+    pub fn style<S: Into<Style>>(mut self, style: S) -> Self {
+        self.text = self.text.style(style);
+        self
+    }
+
+    /// Sets the browsing context to open the URL in, e.g. `_blank` for a new
+    /// tab.
+    pub fn target<T: Into<String>>(mut self, target: T) -> Self {
+        self.target = Some(target.into());
+        self
     }
 }
 
@@ -38,6 +68,14 @@ impl Widget for Hyperlink<'_> {
     where
         Self: Sized,
     {
-        self.line.render(area, buf);
+        let width = (self.text.width() as u16).min(area.width);
+        self.text.render(area, buf);
+        metadata::register(
+            Rect::new(area.x, area.y, width, area.height.min(1)),
+            RegionKind::Hyperlink {
+                url: self.url,
+                target: self.target,
+            },
+        );
     }
 }
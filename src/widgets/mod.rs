@@ -2,6 +2,22 @@
 //!
 //! **Ratzilla** provides web-only widgets that you can use while building TUIs.
 
+pub(crate) mod cell_effect;
+pub(crate) mod copy_button;
+pub(crate) mod data_attributes;
+pub(crate) mod decoration;
 pub(crate) mod hyperlink;
+pub(crate) mod priority;
+pub(crate) mod secret;
+pub(crate) mod status_bar;
+pub(crate) mod typewriter;
 
+pub use cell_effect::CellEffect;
+pub use copy_button::CopyButton;
+pub use data_attributes::DataAttributes;
+pub use decoration::Decoration;
 pub use hyperlink::Hyperlink;
+pub use priority::Priority;
+pub use secret::Secret;
+pub use status_bar::StatusBar;
+pub use typewriter::Typewriter;
@@ -0,0 +1,32 @@
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+use crate::metadata::{self, RegionKind};
+
+/// A widget that marks an area as high priority.
+///
+/// It does not draw anything. When the [`DomBackend`] has an update budget
+/// and a frame exceeds it, the lines of the area are updated first and the
+/// other lines are deferred to the next frame. This keeps critical parts of
+/// the UI, such as an input line, responsive under load.
+///
+/// ```rust no_run
+/// use ratzilla::widgets::Priority;
+///
+/// // Render it over the area of the input line:
+/// // frame.render_widget(Priority, input_area);
+/// ```
+///
+/// The line of the cursor always has priority.
+///
+/// [`DomBackend`]: crate::DomBackend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Priority;
+
+impl Widget for Priority {
+    fn render(self, area: Rect, _buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        metadata::register(area, RegionKind::Priority);
+    }
+}
@@ -0,0 +1,63 @@
+use ratatui::{buffer::Buffer, layout::Rect, text::Span, widgets::Widget};
+
+use crate::metadata::{self, RegionKind};
+
+/// Default mask character.
+const DEFAULT_MASK: char = '•';
+
+/// A widget for rendering secrets such as passwords.
+///
+/// The value is written into the buffer as usual, but the area is marked as
+/// secret so that the [`DomBackend`] never puts the plaintext into the DOM.
+/// Instead, every non-blank cell of the area is rendered as the mask
+/// character. This keeps the value out of reach of browser extensions and DOM
+/// scraping.
+///
+/// ```rust no_run
+/// use ratzilla::widgets::Secret;
+///
+/// let password = Secret::new("hunter2").mask('*');
+///
+/// // Then you can render it as usual:
+/// // frame.render_widget(password, area);
+/// ```
+///
+/// Rendering an empty secret over an area masks whatever other widgets render
+/// there.
+///
+/// [`DomBackend`]: crate::DomBackend
+pub struct Secret<'a> {
+    /// Value.
+    value: Span<'a>,
+    /// Mask character.
+    mask: char,
+}
+
+impl<'a> Secret<'a> {
+    /// Constructs a new [`Secret`] widget.
+    pub fn new<T>(value: T) -> Self
+    where
+        T: Into<Span<'a>>,
+    {
+        Self {
+            value: value.into(),
+            mask: DEFAULT_MASK,
+        }
+    }
+
+    /// Sets the mask character.
+    pub fn mask(mut self, mask: char) -> Self {
+        self.mask = mask;
+        self
+    }
+}
+
+impl Widget for Secret<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        self.value.render(area, buf);
+        metadata::register(area, RegionKind::Secret(self.mask));
+    }
+}
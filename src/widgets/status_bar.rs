@@ -0,0 +1,155 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+use web_sys::window;
+
+use crate::{event, render};
+
+/// Separator between the indicators.
+const SEPARATOR: &str = " │ ";
+
+/// A status bar with web-specific indicators.
+///
+/// It shows, from left to right:
+///
+/// - whether the browser is online,
+/// - the number of frames rendered during the last second, along with the
+///   number of frames skipped under heavy load if any,
+/// - the latency of the last input, from the browser event to the flush of
+///   the frame that handled it,
+/// - the size of the grid,
+/// - the key chord in progress, if any.
+///
+/// The indicators are read from the renderer, so the status bar only needs a
+/// single line at the bottom of the frame:
+///
+/// ```rust no_run
+/// use ratzilla::widgets::StatusBar;
+///
+/// let status_bar = StatusBar::new().chord("g-");
+///
+/// // Then you can render it as usual:
+/// // frame.render_widget(status_bar, area);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StatusBar {
+    /// Key chord in progress.
+    chord: Option<String>,
+    /// Whether the connection state is shown.
+    connection: bool,
+    /// Whether the frame rate is shown.
+    frame_rate: bool,
+    /// Whether the input latency is shown.
+    latency: bool,
+    /// Whether the grid size is shown.
+    size: bool,
+    /// Style of the status bar.
+    style: Style,
+}
+
+impl Default for StatusBar {
+    fn default() -> Self {
+        Self {
+            chord: None,
+            connection: true,
+            frame_rate: true,
+            latency: true,
+            size: true,
+            style: Style::default().fg(Color::Black).bg(Color::Gray),
+        }
+    }
+}
+
+impl StatusBar {
+    /// Constructs a new [`StatusBar`] widget with all the indicators.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the key chord in progress, e.g. `"Ctrl-x"`.
+    pub fn chord<T: Into<String>>(mut self, chord: T) -> Self {
+        self.chord = Some(chord.into());
+        self
+    }
+
+    /// Sets whether the connection state is shown.
+    pub fn connection(mut self, connection: bool) -> Self {
+        self.connection = connection;
+        self
+    }
+
+    /// Sets whether the frame rate is shown.
+    pub fn frame_rate(mut self, frame_rate: bool) -> Self {
+        self.frame_rate = frame_rate;
+        self
+    }
+
+    /// Sets whether the input latency is shown.
+    pub fn latency(mut self, latency: bool) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Sets whether the grid size is shown.
+    pub fn size(mut self, size: bool) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the style of the status bar.
+    pub fn style<S: Into<Style>>(mut self, style: S) -> Self {
+        self.style = style.into();
+        self
+    }
+}
+
+impl Widget for StatusBar {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let mut indicators = Vec::new();
+        if self.connection {
+            let online = window().is_none_or(|window| window.navigator().on_line());
+            indicators.push(if online {
+                Span::styled("● online", Style::default().fg(Color::Green))
+            } else {
+                Span::styled("● offline", Style::default().fg(Color::Red))
+            });
+        }
+        if self.frame_rate {
+            let skipped = render::skipped_frames();
+            indicators.push(Span::raw(if skipped > 0 {
+                format!("{} fps, {skipped} skipped", render::frame_rate())
+            } else {
+                format!("{} fps", render::frame_rate())
+            }));
+        }
+        if self.latency {
+            if let Some(latency) = event::input_latency() {
+                indicators.push(Span::raw(format!("{latency:.0} ms")));
+            }
+        }
+        if self.size {
+            if let Some(size) = render::frame_size() {
+                indicators.push(Span::raw(format!("{}x{}", size.width, size.height)));
+            }
+        }
+        if let Some(chord) = self.chord {
+            indicators.push(Span::raw(chord));
+        }
+        let mut spans = vec![Span::raw(" ")];
+        for (i, indicator) in indicators.into_iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(SEPARATOR));
+            }
+            spans.push(indicator);
+        }
+        buf.set_style(area, self.style);
+        Line::from(spans).style(self.style).render(area, buf);
+    }
+}
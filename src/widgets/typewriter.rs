@@ -0,0 +1,68 @@
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+use crate::metadata::{self, RegionKind};
+
+/// Default number of cells revealed per second.
+const DEFAULT_SPEED: f64 = 30.0;
+
+/// A widget that reveals the content of an area progressively.
+///
+/// Render it over an area after rendering the content. The cells of the area
+/// are then revealed one by one, row by row, starting from the first frame
+/// the area appears in. Once everything is revealed, an [`EffectEvent`] with
+/// the name of the typewriter is emitted, which can be handled with
+/// [`WebRenderer::on_effect_event`].
+///
+/// ```rust no_run
+/// use ratzilla::widgets::Typewriter;
+///
+/// let typewriter = Typewriter::new("intro").speed(50.0);
+///
+/// // Render the content and then the typewriter over it:
+/// // frame.render_widget(paragraph, area);
+/// // frame.render_widget(typewriter, area);
+/// ```
+///
+/// The typewriter restarts if it is not rendered for a frame.
+///
+/// [`EffectEvent`]: crate::event::EffectEvent
+/// [`WebRenderer::on_effect_event`]: crate::WebRenderer::on_effect_event
+pub struct Typewriter {
+    /// Name.
+    name: String,
+    /// Number of cells revealed per second.
+    speed: f64,
+}
+
+impl Typewriter {
+    /// Constructs a new [`Typewriter`] widget with the given name.
+    ///
+    /// The name identifies the typewriter across frames.
+    pub fn new<T: Into<String>>(name: T) -> Self {
+        Self {
+            name: name.into(),
+            speed: DEFAULT_SPEED,
+        }
+    }
+
+    /// Sets the number of cells revealed per second.
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+}
+
+impl Widget for Typewriter {
+    fn render(self, area: Rect, _buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        metadata::register(
+            area,
+            RegionKind::Typewriter {
+                name: self.name,
+                speed: self.speed,
+            },
+        );
+    }
+}